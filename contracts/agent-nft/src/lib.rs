@@ -1,11 +1,16 @@
 #![no_std]
 
 extern crate alloc;
-use alloc::format;
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Symbol, Vec};
+
+mod tests;
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, Address, Env, IntoVal, Map, String, Symbol, Val, Vec,
+};
 use stellai_lib::{
-    errors::ContractError, Agent, ADMIN_KEY, AGENT_COUNTER_KEY, AGENT_KEY_PREFIX,
-    AGENT_LEASE_STATUS_PREFIX, APPROVED_MINTERS_KEY, MAX_CAPABILITIES, MAX_STRING_LENGTH,
+    errors::ContractError, Agent, ADMIN_KEY, AGENT_COUNTER_KEY, APPROVED_MINTERS_KEY,
+    MAX_AGENTS_PER_OWNER, MAX_ATTRIBUTES, MAX_BATCH_SIZE, MAX_CAPABILITIES, MAX_DURATION_DAYS,
+    MAX_HISTORY_SIZE, MAX_STRING_LENGTH,
 };
 
 // ============================================================================
@@ -19,6 +24,14 @@ pub enum AgentEvent {
     AgentTransferred,
     LeaseStarted,
     LeaseEnded,
+    TransferLockSet,
+    AttributeSet,
+    AttributeRemoved,
+    ApprovalForAll,
+    AgentBurned,
+    Approval,
+    MinterAdded,
+    MinterRemoved,
 }
 
 #[contract]
@@ -54,6 +67,11 @@ impl AgentNFT {
         Ok(())
     }
 
+    /// Whether the contract has been initialized with an admin
+    pub fn is_initialized(env: Env) -> bool {
+        env.storage().instance().has(&Symbol::new(&env, ADMIN_KEY))
+    }
+
     /// Add an approved minter (admin only)
     pub fn add_approved_minter(
         env: Env,
@@ -69,22 +87,91 @@ impl AgentNFT {
             .get(&Symbol::new(&env, APPROVED_MINTERS_KEY))
             .unwrap_or_else(|| Vec::new(&env));
 
-        approved_minters.push_back(minter);
+        if !approved_minters.contains(&minter) {
+            approved_minters.push_back(minter.clone());
+            env.storage()
+                .instance()
+                .set(&Symbol::new(&env, APPROVED_MINTERS_KEY), &approved_minters);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "agent_nft"), AgentEvent::MinterAdded),
+            minter,
+        );
+
+        Ok(())
+    }
+
+    /// Revoke an approved minter (admin only)
+    pub fn remove_approved_minter(
+        env: Env,
+        admin: Address,
+        minter: Address,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin)?;
+
+        let mut approved_minters = Self::list_approved_minters(env.clone());
+        let position = approved_minters
+            .first_index_of(&minter)
+            .ok_or(ContractError::InvalidInput)?;
+        approved_minters.remove(position);
         env.storage()
             .instance()
             .set(&Symbol::new(&env, APPROVED_MINTERS_KEY), &approved_minters);
 
+        env.events().publish(
+            (Symbol::new(&env, "agent_nft"), AgentEvent::MinterRemoved),
+            minter,
+        );
+
         Ok(())
     }
 
+    /// Whether `addr` is on the approved minter list (the admin can always mint)
+    pub fn is_approved_minter(env: Env, addr: Address) -> bool {
+        Self::list_approved_minters(env).contains(&addr)
+    }
+
+    /// Get all approved minters
+    pub fn list_approved_minters(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, APPROVED_MINTERS_KEY))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
     /// Helper to get storage key for an agent
     fn get_agent_key(env: &Env, agent_id: u64) -> (Symbol, u64) {
         (Symbol::new(env, "agent"), agent_id)
     }
 
-    /// Helper to get storage key for agent lease status
+    /// Helper to get storage key for an agent's `(lessee, expires_at)` lease record
     fn get_agent_lease_key(env: &Env, agent_id: u64) -> (Symbol, u64) {
-        (Symbol::new(env, "lease"), agent_id)
+        (Symbol::new(env, "lease_info"), agent_id)
+    }
+
+    /// Helper to get storage key for an agent's transfer lock
+    fn get_transfer_lock_key(env: &Env, agent_id: u64) -> (Symbol, u64) {
+        (Symbol::new(env, "xfer_lock"), agent_id)
+    }
+
+    /// Helper to get storage key for an agent's approved operator
+    fn get_approved_key(env: &Env, agent_id: u64) -> (Symbol, u64) {
+        (Symbol::new(env, "approved"), agent_id)
+    }
+
+    /// Check whether `spender` may move `owner`'s agent: the owner, the
+    /// per-agent approved operator, or an operator approved for all
+    fn may_transfer(env: &Env, agent_id: u64, owner: &Address, spender: &Address) -> bool {
+        spender == owner
+            || Self::get_approved(env.clone(), agent_id).as_ref() == Some(spender)
+            || Self::is_approved_for_all(env.clone(), owner.clone(), spender.clone())
+    }
+
+    /// Helper to get storage key for an owner's agent index
+    fn get_owner_index_key(env: &Env, owner: &Address) -> (Symbol, Address) {
+        (Symbol::new(env, "owner_idx"), owner.clone())
     }
 
     /// Verify caller is admin
@@ -93,7 +180,7 @@ impl AgentNFT {
             .storage()
             .instance()
             .get(&Symbol::new(env, ADMIN_KEY))
-            .ok_or(ContractError::Unauthorized)?;
+            .ok_or(ContractError::NotInitialized)?;
 
         if caller != &admin {
             return Err(ContractError::Unauthorized);
@@ -137,19 +224,106 @@ impl AgentNFT {
         a.checked_add(b).ok_or(ContractError::OverflowError)
     }
 
+    /// Active lease of an agent; a lease stops being active once its expiry passes
+    fn active_lease(env: &Env, agent_id: u64) -> Option<(Address, u64)> {
+        let lease_key = Self::get_agent_lease_key(env, agent_id);
+        env.storage()
+            .instance()
+            .get::<_, (Address, u64)>(&lease_key)
+            .filter(|(_, expires_at)| env.ledger().timestamp() < *expires_at)
+    }
+
     /// Check if agent is currently leased
     fn is_agent_leased(env: &Env, agent_id: u64) -> bool {
-        let lease_key = Self::get_agent_lease_key(env, agent_id);
+        Self::active_lease(env, agent_id).is_some()
+    }
+
+    /// Check if agent is transfer-locked by its owner
+    fn is_agent_transfer_locked(env: &Env, agent_id: u64) -> bool {
+        let lock_key = Self::get_transfer_lock_key(env, agent_id);
         env.storage()
             .instance()
-            .get::<_, bool>(&lease_key)
+            .get::<_, bool>(&lock_key)
             .unwrap_or(false)
     }
 
-    /// Set agent lease status
-    fn set_agent_lease_status(env: &Env, agent_id: u64, is_leased: bool) {
-        let lease_key = Self::get_agent_lease_key(env, agent_id);
-        env.storage().instance().set(&lease_key, &is_leased);
+    /// Add an agent to its owner's index (no-op if already present).
+    /// Fails with `InvalidInput` once the owner holds `MAX_AGENTS_PER_OWNER` agents.
+    fn add_to_owner_index(env: &Env, owner: &Address, agent_id: u64) -> Result<(), ContractError> {
+        let index_key = Self::get_owner_index_key(env, owner);
+        let mut agent_ids: Vec<u64> = env
+            .storage()
+            .instance()
+            .get(&index_key)
+            .unwrap_or_else(|| Vec::new(env));
+        if !agent_ids.contains(agent_id) {
+            if agent_ids.len() >= MAX_AGENTS_PER_OWNER {
+                return Err(ContractError::InvalidInput);
+            }
+            agent_ids.push_back(agent_id);
+            env.storage().instance().set(&index_key, &agent_ids);
+        }
+        Ok(())
+    }
+
+    /// Remove an agent from an owner's index
+    fn remove_from_owner_index(env: &Env, owner: &Address, agent_id: u64) {
+        let index_key = Self::get_owner_index_key(env, owner);
+        let mut agent_ids: Vec<u64> = env
+            .storage()
+            .instance()
+            .get(&index_key)
+            .unwrap_or_else(|| Vec::new(env));
+        if let Some(position) = agent_ids.first_index_of(agent_id) {
+            agent_ids.remove(position);
+            if agent_ids.is_empty() {
+                env.storage().instance().remove(&index_key);
+            } else {
+                env.storage().instance().set(&index_key, &agent_ids);
+            }
+        }
+    }
+
+    /// Helper to get storage key for an agent's ownership-change log
+    fn get_owner_log_key(env: &Env, agent_id: u64) -> (Symbol, u64) {
+        (Symbol::new(env, "owner_log"), agent_id)
+    }
+
+    /// Append `(now, owner)` to the agent's ownership log, dropping the oldest entry
+    /// once the log holds `MAX_HISTORY_SIZE` changes
+    fn record_owner(env: &Env, agent_id: u64, owner: &Address) {
+        let log_key = Self::get_owner_log_key(env, agent_id);
+        let mut log: Vec<(u64, Address)> = env
+            .storage()
+            .instance()
+            .get(&log_key)
+            .unwrap_or_else(|| Vec::new(env));
+        if log.len() >= MAX_HISTORY_SIZE {
+            log.pop_front();
+        }
+        log.push_back((env.ledger().timestamp(), owner.clone()));
+        env.storage().instance().set(&log_key, &log);
+    }
+
+    /// Check a capability list: at most `MAX_CAPABILITIES` entries, each within
+    /// `MAX_STRING_LENGTH`, and no entry repeated
+    fn validate_capabilities(capabilities: &Vec<String>) -> Result<(), ContractError> {
+        if capabilities.len() as usize > MAX_CAPABILITIES {
+            return Err(ContractError::InvalidInput);
+        }
+
+        for i in 0..capabilities.len() {
+            let cap = capabilities.get_unchecked(i);
+            if cap.len() > MAX_STRING_LENGTH {
+                return Err(ContractError::InvalidInput);
+            }
+            for j in (i + 1)..capabilities.len() {
+                if capabilities.get_unchecked(j) == cap {
+                    return Err(ContractError::InvalidInput);
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Check if agent ID already exists
@@ -162,7 +336,8 @@ impl AgentNFT {
     ///
     /// # Arguments
     /// * `agent_id` - Unique identifier for the agent (u128 in spec, using u64 for storage efficiency)
-    /// * `owner` - Address of the agent owner
+    /// * `minter` - Admin or approved minter authorizing the mint
+    /// * `owner` - Address of the agent owner (needs no minter rights)
     /// * `metadata_cid` - IPFS CID for agent metadata
     /// * `initial_evolution_level` - Starting evolution level
     ///
@@ -170,21 +345,114 @@ impl AgentNFT {
     /// Result<(), ContractError>
     ///
     /// # Errors
-    /// - ContractError::Unauthorized if caller is not admin or approved minter
+    /// - ContractError::Unauthorized if `minter` is not admin or approved minter
     /// - ContractError::DuplicateAgentId if agent_id already exists
     /// - ContractError::InvalidInput if validation fails
     pub fn mint_agent(
         env: Env,
         agent_id: u128,
+        minter: Address,
         owner: Address,
         metadata_cid: String,
         initial_evolution_level: u32,
     ) -> Result<(), ContractError> {
-        owner.require_auth();
+        minter.require_auth();
 
         // Validate caller authorization (admin or approved minter)
-        Self::verify_minter(&env, &owner)?;
+        Self::verify_minter(&env, &minter)?;
+
+        Self::mint_to(env, agent_id, owner, metadata_cid, initial_evolution_level)
+    }
 
+    /// Mint several agents in one call; entry `i` of each vector describes one agent.
+    ///
+    /// At most `MAX_BATCH_SIZE` agents per call, to stay within the instruction
+    /// budget. The batch is all-or-nothing: any failing entry reverts every mint
+    /// in it.
+    ///
+    /// # Errors
+    /// - ContractError::Unauthorized if `minter` is not admin or approved minter
+    /// - ContractError::DuplicateAgentId if any agent_id already exists or repeats
+    /// - ContractError::InvalidInput if the vectors are empty, differ in length,
+    ///   exceed `MAX_BATCH_SIZE`, or any entry fails validation
+    pub fn mint_batch(
+        env: Env,
+        minter: Address,
+        owners: Vec<Address>,
+        agent_ids: Vec<u128>,
+        metadata_cids: Vec<String>,
+        evolution_levels: Vec<u32>,
+    ) -> Result<(), ContractError> {
+        minter.require_auth();
+
+        let count = agent_ids.len();
+        if count == 0
+            || count > MAX_BATCH_SIZE
+            || owners.len() != count
+            || metadata_cids.len() != count
+            || evolution_levels.len() != count
+        {
+            return Err(ContractError::InvalidInput);
+        }
+
+        Self::verify_minter(&env, &minter)?;
+
+        for i in 0..count {
+            Self::mint_to(
+                env.clone(),
+                agent_ids.get_unchecked(i),
+                owners.get_unchecked(i),
+                metadata_cids.get_unchecked(i),
+                evolution_levels.get_unchecked(i),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Mint a new agent directly to `recipient` as an operator approved for all of the
+    /// admin's agents (see `set_approval_for_all`). The recipient does not sign.
+    ///
+    /// # Errors
+    /// - ContractError::Unauthorized if `operator` is not approved by the admin
+    /// - ContractError::DuplicateAgentId if agent_id already exists
+    /// - ContractError::InvalidInput if validation fails
+    pub fn mint_agent_for(
+        env: Env,
+        operator: Address,
+        agent_id: u128,
+        recipient: Address,
+        metadata_cid: String,
+        initial_evolution_level: u32,
+    ) -> Result<(), ContractError> {
+        operator.require_auth();
+
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, ADMIN_KEY))
+            .ok_or(ContractError::NotInitialized)?;
+        if !Self::is_approved_for_all(env.clone(), admin, operator) {
+            return Err(ContractError::Unauthorized);
+        }
+
+        Self::mint_to(
+            env,
+            agent_id,
+            recipient,
+            metadata_cid,
+            initial_evolution_level,
+        )
+    }
+
+    /// Create and store a new agent owned by `owner`; callers handle authorization
+    fn mint_to(
+        env: Env,
+        agent_id: u128,
+        owner: Address,
+        metadata_cid: String,
+        initial_evolution_level: u32,
+    ) -> Result<(), ContractError> {
         // Convert u128 to u64 for storage (validate it fits)
         let agent_id_u64 = agent_id
             .try_into()
@@ -196,7 +464,7 @@ impl AgentNFT {
         }
 
         // Input validation
-        if metadata_cid.len() > MAX_STRING_LENGTH.try_into().unwrap() {
+        if metadata_cid.len() > MAX_STRING_LENGTH {
             return Err(ContractError::InvalidInput);
         }
 
@@ -204,6 +472,7 @@ impl AgentNFT {
         let agent = Agent {
             id: agent_id_u64,
             owner: owner.clone(),
+            creator: owner.clone(),
             name: String::from_str(&env, ""), // Can be set via update_agent
             model_hash: String::from_str(&env, ""), // Can be set via update_agent
             metadata_cid,
@@ -220,8 +489,24 @@ impl AgentNFT {
         let key = Self::get_agent_key(&env, agent_id_u64);
         env.storage().instance().set(&key, &agent);
 
-        // Initialize lease status to false (not leased)
-        Self::set_agent_lease_status(&env, agent_id_u64, false);
+        Self::add_to_owner_index(&env, &owner, agent_id_u64)?;
+        Self::record_owner(&env, agent_id_u64, &owner);
+        let minted = Self::safe_add(Self::total_minted(env.clone()), 1)?;
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "total_minted"), &minted);
+
+        // Claiming the next counter ID moves the counter past it and any explicit IDs
+        // already taken after it, so `next_agent_id` does not re-probe them
+        let counter_key = Symbol::new(&env, AGENT_COUNTER_KEY);
+        let counter: u64 = env.storage().instance().get(&counter_key).unwrap_or(0);
+        if agent_id_u64 == Self::safe_add(counter, 1)? {
+            let mut counter = agent_id_u64;
+            while Self::agent_exists(&env, Self::safe_add(counter, 1)?) {
+                counter = Self::safe_add(counter, 1)?;
+            }
+            env.storage().instance().set(&counter_key, &counter);
+        }
 
         // Emit AgentMinted event
         env.events().publish(
@@ -232,6 +517,91 @@ impl AgentNFT {
         Ok(())
     }
 
+    /// Approve `operator` to transfer a single agent on the owner's behalf (owner only).
+    /// Replaces any earlier approval; cleared when the agent is transferred.
+    pub fn approve(
+        env: Env,
+        agent_id: u64,
+        owner: Address,
+        operator: Address,
+    ) -> Result<(), ContractError> {
+        if agent_id == 0 {
+            return Err(ContractError::InvalidAgentId);
+        }
+
+        owner.require_auth();
+
+        let agent: Agent = env
+            .storage()
+            .instance()
+            .get(&Self::get_agent_key(&env, agent_id))
+            .ok_or(ContractError::AgentNotFound)?;
+
+        if agent.owner != owner {
+            return Err(ContractError::NotOwner);
+        }
+
+        if owner == operator {
+            return Err(ContractError::InvalidInput);
+        }
+
+        env.storage()
+            .instance()
+            .set(&Self::get_approved_key(&env, agent_id), &operator);
+
+        env.events().publish(
+            (Symbol::new(&env, "agent_nft"), AgentEvent::Approval),
+            (agent_id, owner, operator),
+        );
+
+        Ok(())
+    }
+
+    /// Get the operator approved for a single agent, if any
+    pub fn get_approved(env: Env, agent_id: u64) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&Self::get_approved_key(&env, agent_id))
+    }
+
+    /// Allow or revoke `operator` acting on all of `owner`'s agents (owner only)
+    pub fn set_approval_for_all(
+        env: Env,
+        owner: Address,
+        operator: Address,
+        approved: bool,
+    ) -> Result<(), ContractError> {
+        owner.require_auth();
+
+        if owner == operator {
+            return Err(ContractError::InvalidInput);
+        }
+
+        let key = (
+            Symbol::new(&env, "approved_all"),
+            owner.clone(),
+            operator.clone(),
+        );
+        if approved {
+            env.storage().instance().set(&key, &true);
+        } else {
+            env.storage().instance().remove(&key);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "agent_nft"), AgentEvent::ApprovalForAll),
+            (owner, operator, approved),
+        );
+
+        Ok(())
+    }
+
+    /// Check whether `operator` may act on all of `owner`'s agents
+    pub fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool {
+        let key = (Symbol::new(&env, "approved_all"), owner, operator);
+        env.storage().instance().has(&key)
+    }
+
     /// Legacy mint function for backward compatibility
     pub fn mint_agent_legacy(
         env: Env,
@@ -246,38 +616,22 @@ impl AgentNFT {
         Self::verify_minter(&env, &owner)?;
 
         // Input validation
-        if name.len() > MAX_STRING_LENGTH.try_into().unwrap() {
+        if name.len() > MAX_STRING_LENGTH {
             return Err(ContractError::InvalidInput);
         }
-        if model_hash.len() > MAX_STRING_LENGTH.try_into().unwrap() {
+        if model_hash.len() > MAX_STRING_LENGTH {
             return Err(ContractError::InvalidInput);
         }
-        if capabilities.len() > MAX_CAPABILITIES.try_into().unwrap() {
-            return Err(ContractError::InvalidInput);
-        }
-
-        // Validate each capability string
-        for i in 0..capabilities.len() {
-            if let Some(cap) = capabilities.get(i) {
-                if cap.len() > MAX_STRING_LENGTH.try_into().unwrap() {
-                    return Err(ContractError::InvalidInput);
-                }
-            }
-        }
-
-        // Increment agent counter safely
-        let counter: u64 = env
-            .storage()
-            .instance()
-            .get(&Symbol::new(&env, AGENT_COUNTER_KEY))
-            .unwrap_or(0);
+        Self::validate_capabilities(&capabilities)?;
 
-        let agent_id = Self::safe_add(counter, 1)?;
+        // Next free counter ID, skipping any taken by explicit-ID mints
+        let agent_id = Self::next_agent_id(env.clone())?;
 
         // Create agent
         let agent = Agent {
             id: agent_id,
             owner: owner.clone(),
+            creator: owner.clone(),
             name,
             model_hash,
             metadata_cid: String::from_str(&env, ""),
@@ -294,8 +648,12 @@ impl AgentNFT {
         let key = Self::get_agent_key(&env, agent_id);
         env.storage().instance().set(&key, &agent);
 
-        // Initialize lease status
-        Self::set_agent_lease_status(&env, agent_id, false);
+        Self::add_to_owner_index(&env, &owner, agent_id)?;
+        Self::record_owner(&env, agent_id, &owner);
+        let minted = Self::safe_add(Self::total_minted(env.clone()), 1)?;
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "total_minted"), &minted);
 
         // Update counter
         env.storage()
@@ -324,6 +682,67 @@ impl AgentNFT {
             .ok_or(ContractError::AgentNotFound)
     }
 
+    /// Read several agents at once; results are positional, with `None` for
+    /// zero or unknown IDs
+    pub fn get_agents(env: Env, ids: Vec<u64>) -> Result<Vec<Option<Agent>>, ContractError> {
+        if ids.len() > MAX_BATCH_SIZE {
+            return Err(ContractError::InvalidInput);
+        }
+
+        let mut agents = Vec::new(&env);
+        for agent_id in ids.iter() {
+            agents.push_back(Self::get_agent(env.clone(), agent_id).ok());
+        }
+        Ok(agents)
+    }
+
+    /// Check whether the agent has exactly `capability` (false for unknown agents)
+    pub fn has_capability(env: Env, agent_id: u64, capability: String) -> bool {
+        match Self::get_agent(env, agent_id) {
+            Ok(agent) => agent.capabilities.contains(capability),
+            Err(_) => false,
+        }
+    }
+
+    /// Check whether any of the agent's capabilities starts with `prefix`
+    /// (e.g. `defi:` matches `defi:swap`). False for missing agents, like `has_capability`.
+    pub fn has_capability_prefix(env: Env, agent_id: u64, prefix: String) -> bool {
+        match Self::get_capabilities_with_prefix(env, agent_id, prefix) {
+            Ok(matches) => !matches.is_empty(),
+            Err(_) => false,
+        }
+    }
+
+    /// Get the agent's capabilities that start with `prefix`, in stored order
+    pub fn get_capabilities_with_prefix(
+        env: Env,
+        agent_id: u64,
+        prefix: String,
+    ) -> Result<Vec<String>, ContractError> {
+        if prefix.len() > MAX_STRING_LENGTH {
+            return Err(ContractError::InvalidInput);
+        }
+
+        let agent = Self::get_agent(env.clone(), agent_id)?;
+        let mut prefix_buf = [0u8; MAX_STRING_LENGTH as usize];
+        let prefix_bytes = &mut prefix_buf[..prefix.len() as usize];
+        prefix.copy_into_slice(prefix_bytes);
+
+        let mut matches = Vec::new(&env);
+        for cap in agent.capabilities.iter() {
+            if cap.len() < prefix.len() || cap.len() > MAX_STRING_LENGTH {
+                continue;
+            }
+            let mut cap_buf = [0u8; MAX_STRING_LENGTH as usize];
+            let cap_bytes = &mut cap_buf[..cap.len() as usize];
+            cap.copy_into_slice(cap_bytes);
+            if cap_bytes.starts_with(prefix_bytes) {
+                matches.push_back(cap);
+            }
+        }
+        Ok(matches)
+    }
+
     /// Update agent metadata with authorization check
     pub fn update_agent(
         env: Env,
@@ -357,23 +776,14 @@ impl AgentNFT {
 
         // Update fields with validation
         if let Some(new_name) = name {
-            if new_name.len() > MAX_STRING_LENGTH.try_into().unwrap() {
+            if new_name.len() > MAX_STRING_LENGTH {
                 return Err(ContractError::InvalidInput);
             }
             agent.name = new_name;
         }
 
         if let Some(new_capabilities) = capabilities {
-            if new_capabilities.len() > MAX_CAPABILITIES.try_into().unwrap() {
-                return Err(ContractError::InvalidInput);
-            }
-            for i in 0..new_capabilities.len() {
-                if let Some(cap) = new_capabilities.get(i) {
-                    if cap.len() > MAX_STRING_LENGTH.try_into().unwrap() {
-                        return Err(ContractError::InvalidInput);
-                    }
-                }
-            }
+            Self::validate_capabilities(&new_capabilities)?;
             agent.capabilities = new_capabilities;
         }
 
@@ -394,14 +804,229 @@ impl AgentNFT {
         Ok(())
     }
 
-    /// Get total agents minted
+    /// Replace an agent's metadata CID (owner only, not while leased)
+    pub fn set_metadata_cid(
+        env: Env,
+        agent_id: u64,
+        owner: Address,
+        new_cid: String,
+    ) -> Result<(), ContractError> {
+        owner.require_auth();
+
+        let mut agent = Self::get_agent_for_owner(&env, agent_id, &owner)?;
+        if Self::is_agent_leased(&env, agent_id) {
+            return Err(ContractError::AgentLeased);
+        }
+        if new_cid.len() > MAX_STRING_LENGTH {
+            return Err(ContractError::InvalidInput);
+        }
+
+        agent.metadata_cid = new_cid;
+        Self::save_updated_agent(&env, agent, owner)
+    }
+
+    /// Replace an agent's model hash (owner only, not while leased)
+    pub fn set_model_hash(
+        env: Env,
+        agent_id: u64,
+        owner: Address,
+        new_hash: String,
+    ) -> Result<(), ContractError> {
+        owner.require_auth();
+
+        let mut agent = Self::get_agent_for_owner(&env, agent_id, &owner)?;
+        if Self::is_agent_leased(&env, agent_id) {
+            return Err(ContractError::AgentLeased);
+        }
+        if new_hash.len() > MAX_STRING_LENGTH {
+            return Err(ContractError::InvalidInput);
+        }
+
+        agent.model_hash = new_hash;
+        Self::save_updated_agent(&env, agent, owner)
+    }
+
+    /// Bump the nonce and `updated_at` of a modified agent, store it and emit `AgentUpdated`
+    fn save_updated_agent(
+        env: &Env,
+        mut agent: Agent,
+        owner: Address,
+    ) -> Result<(), ContractError> {
+        agent.nonce = agent
+            .nonce
+            .checked_add(1)
+            .ok_or(ContractError::OverflowError)?;
+        agent.updated_at = env.ledger().timestamp();
+
+        env.storage()
+            .instance()
+            .set(&Self::get_agent_key(env, agent.id), &agent);
+
+        env.events().publish(
+            (Symbol::new(env, "agent_nft"), AgentEvent::AgentUpdated),
+            (agent.id, owner),
+        );
+
+        Ok(())
+    }
+
+    /// Whether an agent has a non-empty metadata CID, name and model hash. Agents minted
+    /// by ID start without a name or model hash until their owner sets them.
+    pub fn is_metadata_complete(env: Env, agent_id: u64) -> Result<bool, ContractError> {
+        let agent = Self::get_agent(env, agent_id)?;
+        Ok(
+            !agent.metadata_cid.is_empty()
+                && !agent.name.is_empty()
+                && !agent.model_hash.is_empty(),
+        )
+    }
+
+    /// Helper to get an agent as its owner, for owner-only mutations
+    fn get_agent_for_owner(
+        env: &Env,
+        agent_id: u64,
+        owner: &Address,
+    ) -> Result<Agent, ContractError> {
+        if agent_id == 0 {
+            return Err(ContractError::InvalidAgentId);
+        }
+
+        let key = Self::get_agent_key(env, agent_id);
+        let agent: Agent = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(ContractError::AgentNotFound)?;
+
+        if &agent.owner != owner {
+            return Err(ContractError::NotOwner);
+        }
+        Ok(agent)
+    }
+
+    /// Set a custom attribute on an agent (owner only).
+    /// New keys are rejected once the agent has `MAX_ATTRIBUTES`; existing keys can always be overwritten.
+    pub fn set_attribute(
+        env: Env,
+        agent_id: u64,
+        owner: Address,
+        key: Symbol,
+        value: String,
+    ) -> Result<(), ContractError> {
+        owner.require_auth();
+        Self::get_agent_for_owner(&env, agent_id, &owner)?;
+
+        if Self::is_agent_leased(&env, agent_id) {
+            return Err(ContractError::AgentLeased);
+        }
+        if value.len() > MAX_STRING_LENGTH {
+            return Err(ContractError::InvalidInput);
+        }
+
+        let mut attributes = Self::get_attributes(env.clone(), agent_id);
+        if !attributes.contains_key(key.clone()) && attributes.len() >= MAX_ATTRIBUTES {
+            return Err(ContractError::InvalidInput);
+        }
+
+        attributes.set(key.clone(), value);
+        env.storage()
+            .instance()
+            .set(&(Symbol::new(&env, "attrs"), agent_id), &attributes);
+
+        env.events().publish(
+            (Symbol::new(&env, "agent_nft"), AgentEvent::AttributeSet),
+            (agent_id, key),
+        );
+
+        Ok(())
+    }
+
+    /// Remove a custom attribute from an agent (owner only)
+    pub fn remove_attribute(
+        env: Env,
+        agent_id: u64,
+        owner: Address,
+        key: Symbol,
+    ) -> Result<(), ContractError> {
+        owner.require_auth();
+        Self::get_agent_for_owner(&env, agent_id, &owner)?;
+
+        if Self::is_agent_leased(&env, agent_id) {
+            return Err(ContractError::AgentLeased);
+        }
+
+        let mut attributes = Self::get_attributes(env.clone(), agent_id);
+        if attributes.remove(key.clone()).is_none() {
+            return Err(ContractError::InvalidInput);
+        }
+        env.storage()
+            .instance()
+            .set(&(Symbol::new(&env, "attrs"), agent_id), &attributes);
+
+        env.events().publish(
+            (Symbol::new(&env, "agent_nft"), AgentEvent::AttributeRemoved),
+            (agent_id, key),
+        );
+
+        Ok(())
+    }
+
+    /// Get a single custom attribute of an agent
+    pub fn get_attribute(env: Env, agent_id: u64, key: Symbol) -> Option<String> {
+        Self::get_attributes(env, agent_id).get(key)
+    }
+
+    /// Get all custom attributes of an agent
+    pub fn get_attributes(env: Env, agent_id: u64) -> Map<Symbol, String> {
+        env.storage()
+            .instance()
+            .get(&(Symbol::new(&env, "attrs"), agent_id))
+            .unwrap_or_else(|| Map::new(&env))
+    }
+
+    /// Get the live supply: agents minted and not burned
     pub fn total_agents(env: Env) -> u64 {
+        Self::total_minted(env.clone()).saturating_sub(Self::total_burned(env))
+    }
+
+    /// Get how many agents have ever been minted, by either mint path. Deployments that
+    /// predate this counter start from the legacy ID counter.
+    pub fn total_minted(env: Env) -> u64 {
         env.storage()
             .instance()
-            .get(&Symbol::new(&env, AGENT_COUNTER_KEY))
+            .get(&Symbol::new(&env, "total_minted"))
+            .unwrap_or_else(|| {
+                env.storage()
+                    .instance()
+                    .get(&Symbol::new(&env, AGENT_COUNTER_KEY))
+                    .unwrap_or(0)
+            })
+    }
+
+    /// Get how many agents have been burned
+    pub fn total_burned(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "total_burned"))
             .unwrap_or(0)
     }
 
+    /// ID the next `mint_agent_legacy` call will assign: the first ID after the legacy
+    /// counter that no explicit-ID `mint_agent` call has already taken
+    pub fn next_agent_id(env: Env) -> Result<u64, ContractError> {
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, AGENT_COUNTER_KEY))
+            .unwrap_or(0);
+
+        let mut agent_id = Self::safe_add(counter, 1)?;
+        while Self::agent_exists(&env, agent_id) {
+            agent_id = Self::safe_add(agent_id, 1)?;
+        }
+        Ok(agent_id)
+    }
+
     /// Get nonce for replay protection
     pub fn get_nonce(env: Env, agent_id: u64) -> Result<u64, ContractError> {
         if agent_id == 0 {
@@ -416,10 +1041,13 @@ impl AgentNFT {
             .ok_or(ContractError::AgentNotFound)
     }
 
-    /// Transfer ownership of an Agent NFT
+    /// Transfer ownership of an Agent NFT. `spender` is the owner itself, the
+    /// operator approved for this agent, or an operator approved for all of the
+    /// owner's agents.
     pub fn transfer_agent(
         env: Env,
         agent_id: u64,
+        spender: Address,
         from: Address,
         to: Address,
     ) -> Result<(), ContractError> {
@@ -427,7 +1055,7 @@ impl AgentNFT {
             return Err(ContractError::InvalidAgentId);
         }
 
-        from.require_auth();
+        spender.require_auth();
 
         if from == to {
             return Err(ContractError::SameAddressTransfer);
@@ -444,10 +1072,22 @@ impl AgentNFT {
             return Err(ContractError::NotOwner);
         }
 
+        if !Self::may_transfer(&env, agent_id, &from, &spender) {
+            return Err(ContractError::Unauthorized);
+        }
+
         if Self::is_agent_leased(&env, agent_id) {
             return Err(ContractError::AgentLeased);
         }
 
+        if Self::is_agent_transfer_locked(&env, agent_id) {
+            return Err(ContractError::TransferLocked);
+        }
+
+        env.storage()
+            .instance()
+            .remove(&Self::get_approved_key(&env, agent_id));
+
         let previous_owner = agent.owner.clone();
         agent.owner = to.clone();
         agent.nonce = agent
@@ -457,6 +1097,9 @@ impl AgentNFT {
         agent.updated_at = env.ledger().timestamp();
 
         env.storage().instance().set(&key, &agent);
+        Self::remove_from_owner_index(&env, &previous_owner, agent_id);
+        Self::add_to_owner_index(&env, &to, agent_id)?;
+        Self::record_owner(&env, agent_id, &to);
 
         env.events().publish(
             (Symbol::new(&env, "agent_nft"), AgentEvent::AgentTransferred),
@@ -466,6 +1109,57 @@ impl AgentNFT {
         Ok(())
     }
 
+    /// Permanently destroy an agent (owner only). Leased agents cannot be burned. The
+    /// ownership log is kept so `owner_at` still answers for the agent's lifetime.
+    pub fn burn_agent(env: Env, agent_id: u64, owner: Address) -> Result<(), ContractError> {
+        if agent_id == 0 {
+            return Err(ContractError::InvalidAgentId);
+        }
+
+        owner.require_auth();
+
+        let key = Self::get_agent_key(&env, agent_id);
+        let agent: Agent = env
+            .storage()
+            .instance()
+            .get(&key)
+            .ok_or(ContractError::AgentNotFound)?;
+
+        if agent.owner != owner {
+            return Err(ContractError::NotOwner);
+        }
+
+        if Self::is_agent_leased(&env, agent_id) {
+            return Err(ContractError::AgentLeased);
+        }
+
+        env.storage().instance().remove(&key);
+        env.storage()
+            .instance()
+            .remove(&Self::get_agent_lease_key(&env, agent_id));
+        env.storage()
+            .instance()
+            .remove(&Self::get_transfer_lock_key(&env, agent_id));
+        env.storage()
+            .instance()
+            .remove(&Self::get_approved_key(&env, agent_id));
+        env.storage()
+            .instance()
+            .remove(&(Symbol::new(&env, "attrs"), agent_id));
+        Self::remove_from_owner_index(&env, &owner, agent_id);
+        let burned = Self::safe_add(Self::total_burned(env.clone()), 1)?;
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "total_burned"), &burned);
+
+        env.events().publish(
+            (Symbol::new(&env, "agent_nft"), AgentEvent::AgentBurned),
+            (agent_id, owner),
+        );
+
+        Ok(())
+    }
+
     /// Get current owner of an agent
     pub fn get_agent_owner(env: Env, agent_id: u64) -> Result<Address, ContractError> {
         if agent_id == 0 {
@@ -480,6 +1174,114 @@ impl AgentNFT {
             .ok_or(ContractError::AgentNotFound)
     }
 
+    /// Get the address an agent was first minted to; unlike the owner, this never
+    /// changes on transfer
+    pub fn get_creator(env: Env, agent_id: u64) -> Result<Address, ContractError> {
+        Self::get_agent(env, agent_id).map(|agent| agent.creator)
+    }
+
+    /// Owner of an agent as of `timestamp`, from its ownership-change log. `None` if the
+    /// agent had not been minted yet (or its log does not reach back that far). The log
+    /// survives a burn, so timestamps after it report the owner the agent was burned by;
+    /// if the ID is minted again, the new owners are appended to the same log.
+    pub fn owner_at(env: Env, agent_id: u64, timestamp: u64) -> Option<Address> {
+        let log: Vec<(u64, Address)> = env
+            .storage()
+            .instance()
+            .get(&Self::get_owner_log_key(&env, agent_id))?;
+
+        let mut owner = None;
+        for (changed_at, new_owner) in log.iter() {
+            if changed_at > timestamp {
+                break;
+            }
+            owner = Some(new_owner);
+        }
+        owner
+    }
+
+    /// Get the IDs of all agents held by an owner
+    pub fn get_agents_by_owner(env: Env, owner: Address) -> Vec<u64> {
+        let index_key = Self::get_owner_index_key(&env, &owner);
+        env.storage()
+            .instance()
+            .get(&index_key)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Get one page of the agent IDs held by an owner, in index order
+    pub fn agents_of_owner(env: Env, owner: Address, offset: u32, limit: u32) -> Vec<u64> {
+        let agent_ids = Self::get_agents_by_owner(env.clone(), owner);
+        let start = offset.min(agent_ids.len());
+        let end = start.saturating_add(limit).min(agent_ids.len());
+        agent_ids.slice(start..end)
+    }
+
+    /// Number of agents held by an owner
+    pub fn balance_of(env: Env, owner: Address) -> u32 {
+        Self::get_agents_by_owner(env, owner).len()
+    }
+
+    /// Re-derive owner index entries for agents stored before the index existed (admin only).
+    /// Safe to re-run: agents already indexed under their owner are skipped.
+    pub fn rebuild_owner_index(
+        env: Env,
+        admin: Address,
+        agent_ids: Vec<u64>,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin)?;
+
+        if agent_ids.len() > MAX_BATCH_SIZE {
+            return Err(ContractError::InvalidInput);
+        }
+
+        for agent_id in agent_ids.iter() {
+            let key = Self::get_agent_key(&env, agent_id);
+            let agent: Agent = env
+                .storage()
+                .instance()
+                .get(&key)
+                .ok_or(ContractError::AgentNotFound)?;
+            Self::add_to_owner_index(&env, &agent.owner, agent_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Record the creator of agents stored before `Agent` carried one (admin only). Such
+    /// records do not decode as `Agent` until back-filled. Safe to re-run: agents that
+    /// already have a creator are skipped.
+    pub fn backfill_creators(
+        env: Env,
+        admin: Address,
+        creators: Vec<(u64, Address)>,
+    ) -> Result<(), ContractError> {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin)?;
+
+        if creators.len() > MAX_BATCH_SIZE {
+            return Err(ContractError::InvalidInput);
+        }
+
+        let creator_field = Symbol::new(&env, "creator");
+        for (agent_id, creator) in creators.iter() {
+            let key = Self::get_agent_key(&env, agent_id);
+            let mut record: Map<Symbol, Val> = env
+                .storage()
+                .instance()
+                .get(&key)
+                .ok_or(ContractError::AgentNotFound)?;
+            if record.contains_key(creator_field.clone()) {
+                continue;
+            }
+            record.set(creator_field.clone(), creator.into_val(&env));
+            env.storage().instance().set(&key, &record);
+        }
+
+        Ok(())
+    }
+
     /// Check if agent can be transferred
     pub fn can_transfer_agent(env: Env, agent_id: u64, caller: Address) -> bool {
         if agent_id == 0 {
@@ -492,36 +1294,113 @@ impl AgentNFT {
             None => return false,
         };
 
-        if agent.owner != caller {
+        if !Self::may_transfer(&env, agent_id, &agent.owner, &caller) {
             return false;
         }
 
-        !Self::is_agent_leased(&env, agent_id)
+        !Self::is_agent_leased(&env, agent_id) && !Self::is_agent_transfer_locked(&env, agent_id)
     }
 
-    /// Start leasing an agent
-    pub fn start_lease(env: Env, agent_id: u64) -> Result<(), ContractError> {
+    /// Lock or unlock an agent against transfer (owner only).
+    /// Independent of lease status, which marketplace logic keys on.
+    pub fn set_transfer_lock(
+        env: Env,
+        agent_id: u64,
+        owner: Address,
+        locked: bool,
+    ) -> Result<(), ContractError> {
         if agent_id == 0 {
             return Err(ContractError::InvalidAgentId);
         }
 
-        Self::set_agent_lease_status(&env, agent_id, true);
+        owner.require_auth();
+
+        let agent_owner = Self::get_agent_owner(env.clone(), agent_id)?;
+        if agent_owner != owner {
+            return Err(ContractError::NotOwner);
+        }
+
+        let lock_key = Self::get_transfer_lock_key(&env, agent_id);
+        if locked {
+            env.storage().instance().set(&lock_key, &true);
+        } else {
+            env.storage().instance().remove(&lock_key);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "agent_nft"), AgentEvent::TransferLockSet),
+            (agent_id, locked),
+        );
+
+        Ok(())
+    }
+
+    /// Check if agent is transfer-locked
+    pub fn is_transfer_locked(env: Env, agent_id: u64) -> Result<bool, ContractError> {
+        if agent_id == 0 {
+            return Err(ContractError::InvalidAgentId);
+        }
+        Ok(Self::is_agent_transfer_locked(&env, agent_id))
+    }
+
+    /// Lease an agent to `lessee` for `duration_seconds` (owner only).
+    /// Fails with `AgentLeased` while a previous lease is still active.
+    pub fn start_lease(
+        env: Env,
+        agent_id: u64,
+        owner: Address,
+        lessee: Address,
+        duration_seconds: u64,
+    ) -> Result<(), ContractError> {
+        if agent_id == 0 {
+            return Err(ContractError::InvalidAgentId);
+        }
+
+        owner.require_auth();
+
+        let agent = Self::get_agent(env.clone(), agent_id)?;
+        if agent.owner != owner {
+            return Err(ContractError::NotOwner);
+        }
+        if lessee == owner || duration_seconds == 0 || duration_seconds > MAX_DURATION_DAYS * 86400
+        {
+            return Err(ContractError::InvalidInput);
+        }
+        if Self::is_agent_leased(&env, agent_id) {
+            return Err(ContractError::AgentLeased);
+        }
+
+        let expires_at = Self::safe_add(env.ledger().timestamp(), duration_seconds)?;
+        let lease_key = Self::get_agent_lease_key(&env, agent_id);
+        env.storage()
+            .instance()
+            .set(&lease_key, &(lessee.clone(), expires_at));
 
         env.events().publish(
             (Symbol::new(&env, "agent_nft"), AgentEvent::LeaseStarted),
-            (agent_id, env.ledger().timestamp()),
+            (agent_id, lessee, expires_at),
         );
 
         Ok(())
     }
 
-    /// End leasing an agent
-    pub fn end_lease(env: Env, agent_id: u64) -> Result<(), ContractError> {
+    /// End an active lease early (owner or lessee)
+    pub fn end_lease(env: Env, agent_id: u64, caller: Address) -> Result<(), ContractError> {
         if agent_id == 0 {
             return Err(ContractError::InvalidAgentId);
         }
 
-        Self::set_agent_lease_status(&env, agent_id, false);
+        caller.require_auth();
+
+        let (lessee, _) = Self::active_lease(&env, agent_id).ok_or(ContractError::InvalidInput)?;
+        let owner = Self::get_agent_owner(env.clone(), agent_id)?;
+        if caller != owner && caller != lessee {
+            return Err(ContractError::Unauthorized);
+        }
+
+        env.storage()
+            .instance()
+            .remove(&Self::get_agent_lease_key(&env, agent_id));
 
         env.events().publish(
             (Symbol::new(&env, "agent_nft"), AgentEvent::LeaseEnded),
@@ -531,6 +1410,11 @@ impl AgentNFT {
         Ok(())
     }
 
+    /// Lessee and expiry timestamp of the agent's active lease, if any
+    pub fn lease_info(env: Env, agent_id: u64) -> Option<(Address, u64)> {
+        Self::active_lease(&env, agent_id)
+    }
+
     /// Check if agent is leased
     pub fn is_leased(env: Env, agent_id: u64) -> Result<bool, ContractError> {
         if agent_id == 0 {
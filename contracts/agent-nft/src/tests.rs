@@ -0,0 +1,1087 @@
+#![cfg(test)]
+
+use crate::{AgentNFT, AgentNFTClient};
+use soroban_sdk::testutils::{Address as _, Events, Ledger};
+use soroban_sdk::{vec, Address, Env, IntoVal, Map, String, Symbol, TryFromVal, Val, Vec};
+use stellai_lib::{
+    errors::ContractError, Agent, AGENT_COUNTER_KEY, MAX_AGENTS_PER_OWNER, MAX_ATTRIBUTES,
+    MAX_BATCH_SIZE,
+};
+
+/// Registers the contract and mints agent 1 to the admin (the admin is always a minter)
+fn setup_agent_nft(env: &Env) -> (AgentNFTClient<'_>, Address) {
+    let contract_id = env.register_contract(None, AgentNFT);
+    let client = AgentNFTClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+
+    env.mock_all_auths();
+    client.init_contract(&admin);
+    client.mint_agent(
+        &1,
+        &admin,
+        &admin,
+        &String::from_str(env, "ipfs://agent-1"),
+        &0,
+    );
+
+    (client, admin)
+}
+
+#[test]
+fn test_is_initialized() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, AgentNFT);
+    let client = AgentNFTClient::new(&env, &contract_id);
+    assert!(!client.is_initialized());
+
+    env.mock_all_auths();
+    client.init_contract(&Address::generate(&env));
+    assert!(client.is_initialized());
+}
+
+#[test]
+fn test_removed_minter_can_no_longer_mint() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let minter = Address::generate(&env);
+    let cid = String::from_str(&env, "ipfs://agent");
+
+    client.add_approved_minter(&admin, &minter);
+    client.add_approved_minter(&admin, &minter);
+    assert!(client.is_approved_minter(&minter));
+    assert_eq!(client.list_approved_minters(), vec![&env, minter.clone()]);
+    client.mint_agent(&2, &minter, &minter, &cid, &0);
+
+    client.remove_approved_minter(&admin, &minter);
+    assert!(!client.is_approved_minter(&minter));
+    assert_eq!(client.list_approved_minters().len(), 0);
+    assert_eq!(
+        client.try_mint_agent(&3, &minter, &minter, &cid, &0),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert_eq!(
+        client.try_remove_approved_minter(&admin, &minter),
+        Err(Ok(ContractError::InvalidInput))
+    );
+}
+
+#[test]
+fn test_minter_mints_on_behalf_of_owner() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let minter = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let cid = String::from_str(&env, "ipfs://agent");
+    client.add_approved_minter(&admin, &minter);
+
+    client.mint_agent(&2, &minter, &owner, &cid, &0);
+    assert_eq!(client.get_agent_owner(&2), owner);
+    assert_eq!(client.get_agents_by_owner(&owner), vec![&env, 2u64]);
+
+    // Owning an agent confers no minter rights
+    assert_eq!(
+        client.try_mint_agent(&3, &owner, &owner, &cid, &0),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_set_metadata_cid_and_model_hash() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+    let before = client.get_agent(&1);
+
+    client.set_metadata_cid(&1, &owner, &String::from_str(&env, "ipfs://agent-1-v2"));
+    client.set_model_hash(&1, &owner, &String::from_str(&env, "model_v2"));
+
+    let agent = client.get_agent(&1);
+    assert_eq!(
+        agent.metadata_cid,
+        String::from_str(&env, "ipfs://agent-1-v2")
+    );
+    assert_eq!(agent.model_hash, String::from_str(&env, "model_v2"));
+    assert_eq!(agent.nonce, before.nonce + 2);
+
+    let too_long = String::from_bytes(&env, &[b'a'; 257]);
+    assert_eq!(
+        client.try_set_model_hash(&1, &owner, &too_long),
+        Err(Ok(ContractError::InvalidInput))
+    );
+    assert_eq!(
+        client.try_set_metadata_cid(&1, &Address::generate(&env), &String::from_str(&env, "x")),
+        Err(Ok(ContractError::NotOwner))
+    );
+
+    client.start_lease(&1, &owner, &Address::generate(&env), &3600);
+    assert_eq!(
+        client.try_set_metadata_cid(&1, &owner, &String::from_str(&env, "ipfs://leased")),
+        Err(Ok(ContractError::AgentLeased))
+    );
+}
+
+#[test]
+fn test_metadata_completeness() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+
+    client.mint_agent(&2, &owner, &owner, &String::from_str(&env, ""), &0);
+    assert!(!client.is_metadata_complete(&2));
+
+    // Agent 1 has a CID but no name or model hash yet
+    assert!(!client.is_metadata_complete(&1));
+    client.update_agent(
+        &1,
+        &owner,
+        &Some(String::from_str(&env, "Agent One")),
+        &None,
+    );
+    client.set_model_hash(&1, &owner, &String::from_str(&env, "model_v1"));
+    assert!(client.is_metadata_complete(&1));
+
+    assert_eq!(
+        client.try_is_metadata_complete(&9),
+        Err(Ok(ContractError::AgentNotFound))
+    );
+}
+
+#[test]
+fn test_transfer_locked_agent_cannot_be_transferred() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+    let recipient = Address::generate(&env);
+
+    client.set_transfer_lock(&1, &owner, &true);
+    assert!(client.is_transfer_locked(&1));
+    assert!(!client.can_transfer_agent(&1, &owner));
+    assert_eq!(
+        client.try_transfer_agent(&1, &owner, &owner, &recipient),
+        Err(Ok(ContractError::TransferLocked))
+    );
+
+    client.set_transfer_lock(&1, &owner, &false);
+    assert!(client.can_transfer_agent(&1, &owner));
+    client.transfer_agent(&1, &owner, &owner, &recipient);
+    assert_eq!(client.get_agent_owner(&1), recipient);
+}
+
+#[test]
+fn test_transfer_lock_is_independent_of_lease() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+
+    client.set_transfer_lock(&1, &owner, &true);
+    assert!(!client.is_leased(&1));
+
+    client.start_lease(&1, &owner, &Address::generate(&env), &3600);
+    client.set_transfer_lock(&1, &owner, &false);
+    assert!(client.is_leased(&1));
+    assert!(!client.is_transfer_locked(&1));
+    assert!(!client.can_transfer_agent(&1, &owner));
+}
+
+#[test]
+fn test_lease_expires_after_duration() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+    let lessee = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    client.start_lease(&1, &owner, &lessee, &3600);
+    assert_eq!(client.lease_info(&1), Some((lessee.clone(), 4_600)));
+    assert_eq!(
+        client.try_start_lease(&1, &owner, &Address::generate(&env), &60),
+        Err(Ok(ContractError::AgentLeased))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 4_600);
+    assert!(!client.is_leased(&1));
+    assert_eq!(client.lease_info(&1), None);
+    assert_eq!(
+        client.try_end_lease(&1, &owner),
+        Err(Ok(ContractError::InvalidInput))
+    );
+
+    // The agent can be leased again once the previous lease has lapsed
+    client.start_lease(&1, &owner, &lessee, &60);
+    assert!(client.is_leased(&1));
+}
+
+#[test]
+fn test_end_lease_by_owner_or_lessee_only() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+    let lessee = Address::generate(&env);
+
+    assert_eq!(
+        client.try_start_lease(&1, &lessee, &Address::generate(&env), &3600),
+        Err(Ok(ContractError::NotOwner))
+    );
+
+    client.start_lease(&1, &owner, &lessee, &3600);
+    assert_eq!(
+        client.try_end_lease(&1, &Address::generate(&env)),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    client.end_lease(&1, &lessee);
+    assert!(!client.is_leased(&1));
+
+    client.start_lease(&1, &owner, &lessee, &3600);
+    client.end_lease(&1, &owner);
+    assert!(client.can_transfer_agent(&1, &owner));
+}
+
+#[test]
+fn test_transfer_lock_requires_owner() {
+    let env = Env::default();
+    let (client, _owner) = setup_agent_nft(&env);
+
+    assert_eq!(
+        client.try_set_transfer_lock(&1, &Address::generate(&env), &true),
+        Err(Ok(ContractError::NotOwner))
+    );
+}
+
+#[test]
+fn test_burn_agent_removes_agent() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+
+    client.set_transfer_lock(&1, &owner, &true);
+    client.burn_agent(&1, &owner);
+
+    assert_eq!(
+        client.try_get_agent(&1),
+        Err(Ok(ContractError::AgentNotFound))
+    );
+    assert_eq!(client.get_agents_by_owner(&owner).len(), 0);
+    assert!(!client.is_transfer_locked(&1));
+
+    // The freed ID can be minted again explicitly
+    client.mint_agent(
+        &1,
+        &owner,
+        &owner,
+        &String::from_str(&env, "ipfs://agent-1b"),
+        &0,
+    );
+    assert_eq!(client.get_agent_owner(&1), owner);
+}
+
+#[test]
+fn test_supply_counts_explicit_mints_and_burns() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+    let cid = String::from_str(&env, "ipfs://agent");
+    assert_eq!(client.total_agents(), 1);
+
+    client.mint_agent(&2, &owner, &owner, &cid, &0);
+    assert_eq!(
+        client.try_mint_agent(&2, &owner, &owner, &cid, &0),
+        Err(Ok(ContractError::DuplicateAgentId))
+    );
+    assert_eq!(client.total_minted(), 2);
+
+    client.burn_agent(&1, &owner);
+    assert_eq!(client.total_minted(), 2);
+    assert_eq!(client.total_burned(), 1);
+    assert_eq!(client.total_agents(), 1);
+}
+
+#[test]
+fn test_burn_agent_rejects_leased_and_non_owner() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+
+    assert_eq!(
+        client.try_burn_agent(&1, &Address::generate(&env)),
+        Err(Ok(ContractError::NotOwner))
+    );
+
+    client.start_lease(&1, &owner, &Address::generate(&env), &3600);
+    assert_eq!(
+        client.try_burn_agent(&1, &owner),
+        Err(Ok(ContractError::AgentLeased))
+    );
+    assert_eq!(client.get_agent_owner(&1), owner);
+}
+
+/// Writes an agent record directly, bypassing mint and therefore the owner index
+fn seed_agent_without_index(env: &Env, client: &AgentNFTClient, agent_id: u64, owner: &Address) {
+    let agent = Agent {
+        id: agent_id,
+        owner: owner.clone(),
+        creator: owner.clone(),
+        name: String::from_str(env, "legacy"),
+        model_hash: String::from_str(env, ""),
+        metadata_cid: String::from_str(env, ""),
+        capabilities: Vec::new(env),
+        evolution_level: 0,
+        created_at: 0,
+        updated_at: 0,
+        nonce: 0,
+        escrow_locked: false,
+        escrow_holder: None,
+    };
+    env.as_contract(&client.address, || {
+        env.storage()
+            .instance()
+            .set(&(Symbol::new(env, "agent"), agent_id), &agent);
+    });
+}
+
+#[test]
+fn test_owner_index_follows_transfers() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+    let recipient = Address::generate(&env);
+
+    assert_eq!(client.get_agents_by_owner(&owner), vec![&env, 1u64]);
+
+    client.transfer_agent(&1, &owner, &owner, &recipient);
+    assert_eq!(client.get_agents_by_owner(&owner).len(), 0);
+    assert_eq!(client.get_agents_by_owner(&recipient), vec![&env, 1u64]);
+}
+
+#[test]
+fn test_creator_survives_transfers() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let first_buyer = Address::generate(&env);
+    let second_buyer = Address::generate(&env);
+
+    client.mint_agent(
+        &2,
+        &admin,
+        &first_buyer,
+        &String::from_str(&env, "ipfs://agent-2"),
+        &0,
+    );
+    assert_eq!(client.get_creator(&2), first_buyer);
+
+    client.transfer_agent(&2, &first_buyer, &first_buyer, &second_buyer);
+    assert_eq!(client.get_agent_owner(&2), second_buyer);
+    assert_eq!(client.get_creator(&2), first_buyer);
+    assert_eq!(client.get_agent(&2).creator, first_buyer);
+
+    assert_eq!(
+        client.try_get_creator(&99),
+        Err(Ok(ContractError::AgentNotFound))
+    );
+}
+
+#[test]
+fn test_backfill_creators_restores_legacy_records() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let legacy_owner = Address::generate(&env);
+    let original_creator = Address::generate(&env);
+
+    // Store agent 7 in the layout used before `Agent` carried a creator
+    seed_agent_without_index(&env, &client, 7, &legacy_owner);
+    env.as_contract(&client.address, || {
+        let key = (Symbol::new(&env, "agent"), 7u64);
+        let agent: Agent = env.storage().instance().get(&key).unwrap();
+        let encoded: Val = agent.into_val(&env);
+        let mut record = Map::<Symbol, Val>::try_from_val(&env, &encoded).unwrap();
+        record.remove(Symbol::new(&env, "creator"));
+        env.storage().instance().set(&key, &record);
+    });
+
+    let creators = vec![&env, (7u64, original_creator.clone())];
+    assert_eq!(
+        client.try_backfill_creators(&Address::generate(&env), &creators),
+        Err(Ok(ContractError::Unauthorized))
+    );
+
+    client.backfill_creators(&admin, &creators);
+    assert_eq!(client.get_creator(&7), original_creator);
+    assert_eq!(client.get_agent(&7).owner, legacy_owner);
+
+    // Re-running leaves an existing creator untouched
+    client.backfill_creators(&admin, &vec![&env, (7u64, legacy_owner.clone())]);
+    assert_eq!(client.get_creator(&7), original_creator);
+
+    assert_eq!(
+        client.try_backfill_creators(&admin, &vec![&env, (99u64, original_creator)]),
+        Err(Ok(ContractError::AgentNotFound))
+    );
+}
+
+#[test]
+fn test_owner_at_follows_transfer_history() {
+    let env = Env::default();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let (client, minter) = setup_agent_nft(&env);
+    let first_buyer = Address::generate(&env);
+    let second_buyer = Address::generate(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.transfer_agent(&1, &minter, &minter, &first_buyer);
+    env.ledger().with_mut(|li| li.timestamp = 300);
+    client.transfer_agent(&1, &first_buyer, &first_buyer, &second_buyer);
+
+    assert_eq!(client.owner_at(&1, &99), None);
+    assert_eq!(client.owner_at(&1, &100), Some(minter.clone()));
+    assert_eq!(client.owner_at(&1, &199), Some(minter));
+    assert_eq!(client.owner_at(&1, &200), Some(first_buyer.clone()));
+    assert_eq!(client.owner_at(&1, &299), Some(first_buyer));
+    assert_eq!(client.owner_at(&1, &300), Some(second_buyer.clone()));
+    assert_eq!(client.owner_at(&1, &10_000), Some(second_buyer));
+    assert_eq!(client.owner_at(&2, &10_000), None);
+}
+
+#[test]
+fn test_owner_at_survives_burn() {
+    let env = Env::default();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let (client, minter) = setup_agent_nft(&env);
+    let buyer = Address::generate(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    client.transfer_agent(&1, &minter, &minter, &buyer);
+    env.ledger().with_mut(|li| li.timestamp = 300);
+    client.burn_agent(&1, &buyer);
+
+    assert_eq!(client.owner_at(&1, &150), Some(minter.clone()));
+    assert_eq!(client.owner_at(&1, &250), Some(buyer.clone()));
+    assert_eq!(client.owner_at(&1, &10_000), Some(buyer.clone()));
+
+    // Re-minting the freed ID continues the same log
+    env.ledger().with_mut(|li| li.timestamp = 400);
+    client.mint_agent(
+        &1,
+        &minter,
+        &minter,
+        &String::from_str(&env, "ipfs://agent-1b"),
+        &0,
+    );
+    assert_eq!(client.owner_at(&1, &250), Some(buyer));
+    assert_eq!(client.owner_at(&1, &400), Some(minter));
+}
+
+#[test]
+fn test_balances_and_pages_follow_transfers() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+    let recipient = Address::generate(&env);
+    let cid = String::from_str(&env, "ipfs://agent");
+    client.mint_agent(&2, &owner, &owner, &cid, &0);
+    client.mint_agent(&3, &owner, &owner, &cid, &0);
+
+    assert_eq!(client.balance_of(&owner), 3);
+    assert_eq!(
+        client.agents_of_owner(&owner, &1, &5),
+        vec![&env, 2u64, 3u64]
+    );
+
+    client.transfer_agent(&2, &owner, &owner, &recipient);
+    assert_eq!(client.balance_of(&owner), 2);
+    assert_eq!(client.balance_of(&recipient), 1);
+    assert_eq!(client.agents_of_owner(&owner, &0, &1), vec![&env, 1u64]);
+    assert_eq!(client.agents_of_owner(&owner, &1, &1), vec![&env, 3u64]);
+    assert_eq!(
+        client.agents_of_owner(&recipient, &0, &10),
+        vec![&env, 2u64]
+    );
+    assert_eq!(client.agents_of_owner(&recipient, &5, &10).len(), 0);
+}
+
+#[test]
+fn test_owner_index_is_bounded() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+
+    let mut full = Vec::new(&env);
+    for agent_id in 0..MAX_AGENTS_PER_OWNER as u64 {
+        full.push_back(1_000 + agent_id);
+    }
+    env.as_contract(&client.address, || {
+        env.storage()
+            .instance()
+            .set(&(Symbol::new(&env, "owner_idx"), owner.clone()), &full);
+    });
+
+    assert_eq!(
+        client.try_mint_agent(
+            &2,
+            &owner,
+            &owner,
+            &String::from_str(&env, "ipfs://agent-2"),
+            &0
+        ),
+        Err(Ok(ContractError::InvalidInput))
+    );
+}
+
+#[test]
+fn test_rebuild_owner_index_recovers_unindexed_agents() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let legacy_owner = Address::generate(&env);
+
+    seed_agent_without_index(&env, &client, 10, &legacy_owner);
+    seed_agent_without_index(&env, &client, 11, &legacy_owner);
+    assert_eq!(client.get_agents_by_owner(&legacy_owner).len(), 0);
+
+    client.rebuild_owner_index(&admin, &vec![&env, 10u64, 11u64, 1u64]);
+    assert_eq!(
+        client.get_agents_by_owner(&legacy_owner),
+        vec![&env, 10u64, 11u64]
+    );
+
+    // Re-running is idempotent
+    client.rebuild_owner_index(&admin, &vec![&env, 10u64, 11u64, 1u64]);
+    assert_eq!(client.get_agents_by_owner(&legacy_owner).len(), 2);
+    assert_eq!(client.get_agents_by_owner(&admin), vec![&env, 1u64]);
+}
+
+/// Fills agent 1 up to `MAX_ATTRIBUTES` with keys `attr_0`, `attr_1`, ...
+fn fill_attributes(env: &Env, client: &AgentNFTClient, owner: &Address) {
+    for i in 0..MAX_ATTRIBUTES {
+        let key = Symbol::new(env, &alloc::format!("attr_{}", i));
+        client.set_attribute(&1, owner, &key, &String::from_str(env, "value"));
+    }
+}
+
+#[test]
+fn test_set_attribute_up_to_cap() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+
+    fill_attributes(&env, &client, &owner);
+    assert_eq!(client.get_attributes(&1).len(), MAX_ATTRIBUTES);
+    assert_eq!(
+        client.get_attribute(&1, &Symbol::new(&env, "attr_0")),
+        Some(String::from_str(&env, "value"))
+    );
+}
+
+#[test]
+fn test_set_attribute_rejects_new_key_over_cap() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+
+    fill_attributes(&env, &client, &owner);
+    assert_eq!(
+        client.try_set_attribute(
+            &1,
+            &owner,
+            &Symbol::new(&env, "one_more"),
+            &String::from_str(&env, "value")
+        ),
+        Err(Ok(ContractError::InvalidInput))
+    );
+}
+
+#[test]
+fn test_set_attribute_overwrite_at_cap() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+
+    fill_attributes(&env, &client, &owner);
+    let key = Symbol::new(&env, "attr_3");
+    client.set_attribute(&1, &owner, &key, &String::from_str(&env, "updated"));
+
+    assert_eq!(client.get_attributes(&1).len(), MAX_ATTRIBUTES);
+    assert_eq!(
+        client.get_attribute(&1, &key),
+        Some(String::from_str(&env, "updated"))
+    );
+
+    // Removing one frees a slot for a new key
+    client.remove_attribute(&1, &owner, &key);
+    client.set_attribute(
+        &1,
+        &owner,
+        &Symbol::new(&env, "one_more"),
+        &String::from_str(&env, "value"),
+    );
+}
+
+#[test]
+fn test_get_agents_returns_positional_results() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    client.mint_agent(
+        &3,
+        &admin,
+        &admin,
+        &String::from_str(&env, "ipfs://agent-3"),
+        &0,
+    );
+
+    let agents = client.get_agents(&vec![&env, 3, 0, 1, 2, 3]);
+    assert_eq!(agents.len(), 5);
+    assert_eq!(agents.get(0).unwrap().unwrap().id, 3);
+    assert!(agents.get(1).unwrap().is_none());
+    assert_eq!(agents.get(2).unwrap().unwrap().id, 1);
+    assert!(agents.get(3).unwrap().is_none());
+    assert_eq!(agents.get(4).unwrap().unwrap().id, 3);
+}
+
+#[test]
+fn test_get_agents_rejects_oversized_batch() {
+    let env = Env::default();
+    let (client, _admin) = setup_agent_nft(&env);
+
+    let mut ids = Vec::new(&env);
+    for id in 0..=MAX_BATCH_SIZE as u64 {
+        ids.push_back(id);
+    }
+    assert_eq!(
+        client.try_get_agents(&ids),
+        Err(Ok(ContractError::InvalidInput))
+    );
+}
+
+#[test]
+fn test_approved_operator_mints_to_users() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let launchpad = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let cid = String::from_str(&env, "ipfs://drop");
+
+    client.set_approval_for_all(&admin, &launchpad, &true);
+    assert!(client.is_approved_for_all(&admin, &launchpad));
+
+    client.mint_agent_for(&launchpad, &2, &alice, &cid, &0);
+    client.mint_agent_for(&launchpad, &3, &bob, &cid, &0);
+    assert_eq!(client.get_agent_owner(&2), alice);
+    assert_eq!(client.get_agent_owner(&3), bob);
+    assert_eq!(client.get_agents_by_owner(&alice), vec![&env, 2]);
+
+    // Operators still cannot reuse an ID
+    assert_eq!(
+        client.try_mint_agent_for(&launchpad, &2, &bob, &cid, &0),
+        Err(Ok(ContractError::DuplicateAgentId))
+    );
+
+    // Revoking the approval stops further mints
+    client.set_approval_for_all(&admin, &launchpad, &false);
+    assert_eq!(
+        client.try_mint_agent_for(&launchpad, &4, &alice, &cid, &0),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_unapproved_operator_cannot_mint_to_users() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let launchpad = Address::generate(&env);
+    let user = Address::generate(&env);
+    let cid = String::from_str(&env, "ipfs://drop");
+
+    // Approval from someone other than the admin does not count
+    client.set_approval_for_all(&user, &launchpad, &true);
+    assert_eq!(
+        client.try_mint_agent_for(&launchpad, &2, &user, &cid, &0),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert!(!client.is_approved_for_all(&admin, &launchpad));
+    assert_eq!(
+        client.try_get_agent(&2),
+        Err(Ok(ContractError::AgentNotFound))
+    );
+}
+
+#[test]
+fn test_approved_operator_transfers_single_agent() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+    let marketplace = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    assert_eq!(
+        client.try_transfer_agent(&1, &marketplace, &owner, &buyer),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert!(!client.can_transfer_agent(&1, &marketplace));
+
+    client.approve(&1, &owner, &marketplace);
+    assert_eq!(client.get_approved(&1), Some(marketplace.clone()));
+    assert!(client.can_transfer_agent(&1, &marketplace));
+
+    client.transfer_agent(&1, &marketplace, &owner, &buyer);
+    assert_eq!(client.get_agent_owner(&1), buyer);
+
+    // The approval does not carry over to the new owner
+    assert_eq!(client.get_approved(&1), None);
+    assert_eq!(
+        client.try_transfer_agent(&1, &marketplace, &buyer, &owner),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_operator_approved_for_all_transfers_owner_agents() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+    let marketplace = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    client.set_approval_for_all(&owner, &marketplace, &true);
+    client.transfer_agent(&1, &marketplace, &owner, &buyer);
+    assert_eq!(client.get_agent_owner(&1), buyer);
+
+    // An owner-wide approval only covers that owner's agents
+    assert_eq!(
+        client.try_transfer_agent(&1, &marketplace, &buyer, &owner),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_approve_rejects_non_owner() {
+    let env = Env::default();
+    let (client, owner) = setup_agent_nft(&env);
+    let stranger = Address::generate(&env);
+
+    assert_eq!(
+        client.try_approve(&1, &stranger, &stranger),
+        Err(Ok(ContractError::NotOwner))
+    );
+    assert_eq!(
+        client.try_approve(&1, &owner, &owner),
+        Err(Ok(ContractError::InvalidInput))
+    );
+    assert_eq!(client.get_approved(&1), None);
+}
+
+#[test]
+fn test_mint_batch_mints_every_agent() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+
+    client.mint_batch(
+        &admin,
+        &vec![&env, alice.clone(), bob.clone(), alice.clone()],
+        &vec![&env, 2u128, 3, 4],
+        &vec![
+            &env,
+            String::from_str(&env, "ipfs://agent-2"),
+            String::from_str(&env, "ipfs://agent-3"),
+            String::from_str(&env, "ipfs://agent-4"),
+        ],
+        &vec![&env, 0u32, 1, 2],
+    );
+
+    assert_eq!(client.get_agents_by_owner(&alice), vec![&env, 2u64, 4]);
+    assert_eq!(client.get_agents_by_owner(&bob), vec![&env, 3u64]);
+    assert_eq!(client.get_agent(&4).evolution_level, 2);
+    assert_eq!(
+        client.get_agent(&3).metadata_cid,
+        String::from_str(&env, "ipfs://agent-3")
+    );
+    assert_eq!(client.total_agents(), 4);
+
+    let minted = env
+        .events()
+        .all()
+        .iter()
+        .filter_map(|(_, _, data)| <(u64, Address, u32)>::try_from_val(&env, &data).ok())
+        .filter(|(agent_id, _, _)| *agent_id > 1)
+        .count();
+    assert_eq!(minted, 3);
+}
+
+#[test]
+fn test_mint_batch_reverts_on_duplicate_id() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let owner = Address::generate(&env);
+    let cid = String::from_str(&env, "ipfs://drop");
+
+    // The last entry repeats an ID from earlier in the batch
+    assert_eq!(
+        client.try_mint_batch(
+            &admin,
+            &vec![&env, owner.clone(), owner.clone(), owner.clone()],
+            &vec![&env, 2u128, 3, 2],
+            &vec![&env, cid.clone(), cid.clone(), cid.clone()],
+            &vec![&env, 0u32, 0, 0],
+        ),
+        Err(Ok(ContractError::DuplicateAgentId))
+    );
+
+    // Nothing from the failed batch was kept
+    assert_eq!(
+        client.try_get_agent(&2),
+        Err(Ok(ContractError::AgentNotFound))
+    );
+    assert_eq!(client.get_agents_by_owner(&owner).len(), 0);
+    assert_eq!(client.total_agents(), 1);
+}
+
+#[test]
+fn test_mint_batch_rejects_bad_shapes_and_minters() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let owner = Address::generate(&env);
+    let cid = String::from_str(&env, "ipfs://drop");
+
+    // Mismatched lengths
+    assert_eq!(
+        client.try_mint_batch(
+            &admin,
+            &vec![&env, owner.clone()],
+            &vec![&env, 2u128, 3],
+            &vec![&env, cid.clone(), cid.clone()],
+            &vec![&env, 0u32, 0],
+        ),
+        Err(Ok(ContractError::InvalidInput))
+    );
+
+    // Empty and oversized batches
+    assert_eq!(
+        client.try_mint_batch(&admin, &vec![&env], &vec![&env], &vec![&env], &vec![&env]),
+        Err(Ok(ContractError::InvalidInput))
+    );
+    let mut owners = Vec::new(&env);
+    let mut ids = Vec::new(&env);
+    let mut cids = Vec::new(&env);
+    let mut levels = Vec::new(&env);
+    for i in 0..=MAX_BATCH_SIZE {
+        owners.push_back(owner.clone());
+        ids.push_back(i as u128 + 2);
+        cids.push_back(cid.clone());
+        levels.push_back(0u32);
+    }
+    assert_eq!(
+        client.try_mint_batch(&admin, &owners, &ids, &cids, &levels),
+        Err(Ok(ContractError::InvalidInput))
+    );
+
+    // Only admins and approved minters may batch-mint
+    assert_eq!(
+        client.try_mint_batch(
+            &owner,
+            &vec![&env, owner.clone()],
+            &vec![&env, 2u128],
+            &vec![&env, cid.clone()],
+            &vec![&env, 0u32],
+        ),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
+
+/// Interleaved explicit-ID (`mint_agent`) and counter-based (`mint_agent_legacy`) mints
+/// share one keyspace; none of them may overwrite another.
+mod mint_collisions {
+    use super::*;
+
+    fn legacy_mint(env: &Env, client: &AgentNFTClient, minter: &Address, name: &str) -> u64 {
+        client.mint_agent_legacy(
+            minter,
+            &String::from_str(env, name),
+            &String::from_str(env, "legacy_hash"),
+            &Vec::new(env),
+        )
+    }
+
+    fn stored_counter(env: &Env, client: &AgentNFTClient) -> u64 {
+        env.as_contract(&client.address, || {
+            env.storage()
+                .instance()
+                .get(&Symbol::new(env, AGENT_COUNTER_KEY))
+                .unwrap()
+        })
+    }
+
+    #[test]
+    fn test_explicit_mint_of_next_id_advances_counter() {
+        let env = Env::default();
+        let (client, admin) = setup_agent_nft(&env);
+        let cid = String::from_str(&env, "ipfs://explicit");
+
+        // Agent 1 from setup was the next counter ID
+        assert_eq!(stored_counter(&env, &client), 1);
+
+        // An ID ahead of the counter leaves it alone
+        client.mint_agent(&3, &admin, &admin, &cid, &0);
+        assert_eq!(stored_counter(&env, &client), 1);
+
+        // Filling the gap advances past it and over the already-taken 3
+        client.mint_agent(&2, &admin, &admin, &cid, &0);
+        assert_eq!(stored_counter(&env, &client), 3);
+        assert_eq!(client.next_agent_id(), 4);
+
+        assert_eq!(legacy_mint(&env, &client, &admin, "next"), 4);
+        assert_eq!(stored_counter(&env, &client), 4);
+    }
+
+    #[test]
+    fn test_legacy_mint_skips_explicit_ids() {
+        let env = Env::default();
+        let (client, admin) = setup_agent_nft(&env);
+        let cid = String::from_str(&env, "ipfs://explicit");
+
+        // Agent 1 is explicit (from setup); reserve 2 and 4 explicitly as well
+        client.mint_agent(&2, &admin, &admin, &cid, &3);
+        client.mint_agent(&4, &admin, &admin, &cid, &5);
+        assert_eq!(client.next_agent_id(), 3);
+
+        assert_eq!(legacy_mint(&env, &client, &admin, "first"), 3);
+        assert_eq!(client.next_agent_id(), 5);
+        assert_eq!(legacy_mint(&env, &client, &admin, "second"), 5);
+        assert_eq!(client.total_agents(), 5);
+        assert_eq!(client.next_agent_id(), 6);
+
+        // Explicit records were not overwritten
+        for (agent_id, level) in [(1u64, 0u32), (2, 3), (4, 5)] {
+            let agent = client.get_agent(&agent_id);
+            assert_eq!(agent.id, agent_id);
+            assert_eq!(agent.evolution_level, level);
+            assert_eq!(agent.model_hash, String::from_str(&env, ""));
+        }
+        for (agent_id, name) in [(3u64, "first"), (5, "second")] {
+            let agent = client.get_agent(&agent_id);
+            assert_eq!(agent.id, agent_id);
+            assert_eq!(agent.name, String::from_str(&env, name));
+        }
+    }
+
+    #[test]
+    fn test_explicit_mint_rejects_legacy_ids() {
+        let env = Env::default();
+        let (client, admin) = setup_agent_nft(&env);
+        let cid = String::from_str(&env, "ipfs://explicit");
+
+        let legacy_id = legacy_mint(&env, &client, &admin, "legacy");
+        assert_eq!(legacy_id, 2);
+        assert_eq!(
+            client.try_mint_agent(&(legacy_id as u128), &admin, &admin, &cid, &9),
+            Err(Ok(ContractError::DuplicateAgentId))
+        );
+        assert_eq!(
+            client.get_agent(&legacy_id).name,
+            String::from_str(&env, "legacy")
+        );
+        assert_eq!(client.total_agents(), 2);
+        assert_eq!(client.next_agent_id(), 3);
+    }
+
+    #[test]
+    fn test_interleaved_mints_keep_owner_index_consistent() {
+        let env = Env::default();
+        let (client, admin) = setup_agent_nft(&env);
+        let cid = String::from_str(&env, "ipfs://explicit");
+
+        let mut expected = vec![&env, 1u64];
+        for round in 0..3u64 {
+            let explicit_id = client.next_agent_id();
+            client.mint_agent(&(explicit_id as u128), &admin, &admin, &cid, &0);
+            expected.push_back(explicit_id);
+            let legacy_id = legacy_mint(&env, &client, &admin, "legacy");
+            assert_eq!(legacy_id, explicit_id + 1, "round {}", round);
+            expected.push_back(legacy_id);
+        }
+
+        assert_eq!(client.get_agents_by_owner(&admin), expected);
+        for agent_id in expected.iter() {
+            assert_eq!(client.get_agent(&agent_id).id, agent_id);
+        }
+    }
+}
+
+#[test]
+fn test_capability_prefix_queries() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let caps = vec![
+        &env,
+        String::from_str(&env, "defi:swap"),
+        String::from_str(&env, "chat"),
+        String::from_str(&env, "defi:lend"),
+    ];
+    let agent_id = client.mint_agent_legacy(
+        &admin,
+        &String::from_str(&env, "trader"),
+        &String::from_str(&env, "hash"),
+        &caps,
+    );
+
+    let defi = String::from_str(&env, "defi:");
+    assert!(client.has_capability_prefix(&agent_id, &defi));
+    assert_eq!(
+        client.get_capabilities_with_prefix(&agent_id, &defi),
+        vec![
+            &env,
+            String::from_str(&env, "defi:swap"),
+            String::from_str(&env, "defi:lend")
+        ]
+    );
+
+    let nft = String::from_str(&env, "nft:");
+    assert!(!client.has_capability_prefix(&agent_id, &nft));
+    assert_eq!(
+        client.get_capabilities_with_prefix(&agent_id, &nft).len(),
+        0
+    );
+
+    // A prefix longer than the capability never matches
+    let long = String::from_str(&env, "chat:extended");
+    assert!(!client.has_capability_prefix(&agent_id, &long));
+}
+
+#[test]
+fn test_duplicate_capabilities_rejected() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let caps = vec![
+        &env,
+        String::from_str(&env, "chat"),
+        String::from_str(&env, "defi:swap"),
+        String::from_str(&env, "chat"),
+    ];
+
+    assert_eq!(
+        client.try_update_agent(&1, &admin, &None, &Some(caps.clone())),
+        Err(Ok(ContractError::InvalidInput))
+    );
+    assert_eq!(
+        client.try_mint_agent_legacy(
+            &admin,
+            &String::from_str(&env, "trader"),
+            &String::from_str(&env, "hash"),
+            &caps,
+        ),
+        Err(Ok(ContractError::InvalidInput))
+    );
+    assert_eq!(client.get_agent(&1).capabilities.len(), 0);
+}
+
+#[test]
+fn test_has_capability_matches_exactly() {
+    let env = Env::default();
+    let (client, admin) = setup_agent_nft(&env);
+    let caps = vec![
+        &env,
+        String::from_str(&env, "chat"),
+        String::from_str(&env, "defi:swap"),
+    ];
+    client.update_agent(&1, &admin, &None, &Some(caps));
+
+    assert!(client.has_capability(&1, &String::from_str(&env, "chat")));
+    assert!(client.has_capability(&1, &String::from_str(&env, "defi:swap")));
+    assert!(!client.has_capability(&1, &String::from_str(&env, "defi:")));
+    assert!(!client.has_capability(&99, &String::from_str(&env, "chat")));
+}
+
+#[test]
+fn test_capability_prefix_on_agent_without_capabilities() {
+    let env = Env::default();
+    let (client, _admin) = setup_agent_nft(&env);
+
+    // Agent 1 is minted without capabilities
+    let prefix = String::from_str(&env, "defi:");
+    assert!(!client.has_capability_prefix(&1, &prefix));
+    assert_eq!(client.get_capabilities_with_prefix(&1, &prefix).len(), 0);
+    assert!(!client.has_capability_prefix(&99, &prefix));
+}
@@ -1,7 +1,59 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, Address, Env, Symbol};
-use stellai_lib::{EvolutionRequest, EvolutionStatus, ADMIN_KEY, REQUEST_COUNTER_KEY};
+mod tests;
+pub mod testutils;
+
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, token, Address, Bytes, BytesN, Env, String, Symbol,
+    Vec,
+};
+use stellai_lib::{
+    encoding::{encode_for_hash, EncodablePart},
+    errors::ContractError,
+    interfaces::{AgentNftClient, OracleDataClient},
+    EvolutionAttestation, EvolutionRequest, EvolutionStatus, ADMIN_KEY, AGENT_NFT_KEY,
+    ATTESTATION_SIGNATURE_SIZE, EVOLUTION_COOLDOWN_KEY, LEVELS_PER_TIER, MAX_AGE_SECONDS,
+    MAX_ATTESTATION_DATA_SIZE, MAX_ATTESTATION_QUORUM, MAX_HISTORY_QUERY_LIMIT, MAX_HISTORY_SIZE,
+    MAX_PENDING_REQUESTS, MAX_STRING_LENGTH, MIN_STAKE_KEY, REQUEST_COUNTER_KEY, STAKE_TOKEN_KEY,
+};
+
+/// First failed precondition of `request_upgrade`
+enum UpgradeBlock {
+    StakeNotPositive,
+    StakeTooLow,
+    NotOwner,
+    OnCooldown,
+    TooManyPending,
+    MaxLevelReached,
+}
+
+impl UpgradeBlock {
+    fn reason(&self, env: &Env) -> Symbol {
+        match self {
+            UpgradeBlock::StakeNotPositive | UpgradeBlock::StakeTooLow => {
+                Symbol::new(env, "stake_too_low")
+            }
+            UpgradeBlock::NotOwner => Symbol::new(env, "not_owner"),
+            UpgradeBlock::OnCooldown => Symbol::new(env, "on_cooldown"),
+            UpgradeBlock::TooManyPending => Symbol::new(env, "too_many_pending"),
+            UpgradeBlock::MaxLevelReached => Symbol::new(env, "max_level"),
+        }
+    }
+
+    /// Abort `request_upgrade`, with the matching `ContractError` where one exists
+    fn raise(&self, env: &Env) -> ! {
+        match self {
+            UpgradeBlock::StakeNotPositive => panic_with_error!(env, ContractError::InvalidAmount),
+            UpgradeBlock::StakeTooLow => panic_with_error!(env, ContractError::StakeBelowMinimum),
+            UpgradeBlock::NotOwner => panic_with_error!(env, ContractError::NotOwner),
+            UpgradeBlock::OnCooldown => panic_with_error!(env, ContractError::OnCooldown),
+            UpgradeBlock::TooManyPending => {
+                panic!("Too many pending upgrade requests for this agent")
+            }
+            UpgradeBlock::MaxLevelReached => panic!("Maximum evolution level reached"),
+        }
+    }
+}
 
 #[contract]
 pub struct Evolution;
@@ -27,15 +79,494 @@ impl Evolution {
             .set(&Symbol::new(&env, REQUEST_COUNTER_KEY), &0u64);
     }
 
-    /// Create an evolution request
-    pub fn create_request(env: Env, agent_id: u64, owner: Address, stake_amount: i128) -> u64 {
-        owner.require_auth();
+    /// Whether the contract has been initialized with an admin
+    pub fn is_initialized(env: Env) -> bool {
+        env.storage().instance().has(&Symbol::new(&env, ADMIN_KEY))
+    }
+
+    /// Verify caller is admin
+    fn verify_admin(env: &Env, caller: &Address) {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(env, ADMIN_KEY))
+            .expect("Admin not set");
+
+        if caller != &admin {
+            panic!("Unauthorized: caller is not admin");
+        }
+    }
+
+    /// Set the token staked on upgrade requests (admin only)
+    pub fn set_stake_token(env: Env, admin: Address, token: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, STAKE_TOKEN_KEY), &token);
+
+        env.events()
+            .publish((Symbol::new(&env, "stake_token_set"),), token);
+    }
+
+    /// Set the AgentNFT contract used to check agent ownership (admin only)
+    pub fn set_agent_nft(env: Env, admin: Address, agent_nft: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, AGENT_NFT_KEY), &agent_nft);
+
+        env.events()
+            .publish((Symbol::new(&env, "agent_nft_set"),), agent_nft);
+    }
+
+    /// Allow upgrades to complete even if the agent changed owner after the request (admin only)
+    pub fn set_allow_owner_change(env: Env, admin: Address, allowed: bool) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "allow_owner_chg"), &allowed);
+
+        env.events()
+            .publish((Symbol::new(&env, "allow_owner_change_set"),), allowed);
+    }
+
+    /// Look up the agent's current owner, if an AgentNFT contract is configured
+    fn current_agent_owner(env: &Env, agent_id: u64) -> Option<Address> {
+        let agent_nft: Address = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(env, AGENT_NFT_KEY))?;
+        Some(AgentNftClient::new(env, &agent_nft).get_agent_owner(&agent_id))
+    }
+
+    /// Check the agent is still owned by whoever requested the upgrade
+    fn owner_unchanged(env: &Env, request: &EvolutionRequest) -> bool {
+        let allowed: bool = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(env, "allow_owner_chg"))
+            .unwrap_or(false);
+        if allowed {
+            return true;
+        }
+
+        match Self::current_agent_owner(env, request.agent_id) {
+            Some(owner) => owner == request.owner,
+            None => true,
+        }
+    }
+
+    /// Set the minimum time between upgrade requests for an agent (admin only, 0 disables)
+    pub fn set_cooldown(env: Env, admin: Address, cooldown_seconds: u64) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if cooldown_seconds > MAX_AGE_SECONDS {
+            panic!("Cooldown exceeds maximum allowed duration");
+        }
+
+        env.storage().instance().set(
+            &Symbol::new(&env, EVOLUTION_COOLDOWN_KEY),
+            &cooldown_seconds,
+        );
+
+        env.events()
+            .publish((Symbol::new(&env, "cooldown_set"),), cooldown_seconds);
+    }
+
+    /// Get the cooldown between upgrade requests
+    pub fn get_cooldown(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, EVOLUTION_COOLDOWN_KEY))
+            .unwrap_or(0)
+    }
+
+    /// Add or remove an agent from the cooldown allow-list (admin only)
+    pub fn set_cooldown_exempt(env: Env, admin: Address, agent_id: u64, exempt: bool) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
 
         if agent_id == 0 {
             panic!("Invalid agent ID");
         }
+
+        let exempt_key = (Symbol::new(&env, "cd_exempt"), agent_id);
+        if exempt {
+            env.storage().instance().set(&exempt_key, &true);
+        } else {
+            env.storage().instance().remove(&exempt_key);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "cooldown_exempt_set"),),
+            (agent_id, exempt),
+        );
+    }
+
+    /// Check if an agent is on the cooldown allow-list
+    pub fn is_cooldown_exempt(env: Env, agent_id: u64) -> bool {
+        let exempt_key = (Symbol::new(&env, "cd_exempt"), agent_id);
+        env.storage().instance().has(&exempt_key)
+    }
+
+    /// Choose when an agent's cooldown starts (admin only): at request time (the
+    /// default, so cancelled or failed requests still count) or only once an
+    /// upgrade completes
+    pub fn set_cooldown_on_complete(env: Env, admin: Address, on_complete: bool) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let mode_key = Symbol::new(&env, "cd_on_complete");
+        if on_complete {
+            env.storage().instance().set(&mode_key, &true);
+        } else {
+            env.storage().instance().remove(&mode_key);
+        }
+
+        env.events()
+            .publish((Symbol::new(&env, "cooldown_mode_set"),), on_complete);
+    }
+
+    /// Check if cooldowns start on completion rather than on request
+    pub fn is_cooldown_on_complete(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .has(&Symbol::new(&env, "cd_on_complete"))
+    }
+
+    /// Seconds until the agent may request another upgrade (0 when not on cooldown)
+    pub fn get_agent_cooldown(env: Env, agent_id: u64) -> u64 {
+        if agent_id == 0 {
+            panic!("Invalid agent ID");
+        }
+        if Self::is_cooldown_exempt(env.clone(), agent_id) {
+            return 0;
+        }
+
+        let (_, cooldown) = Self::agent_tier_params(&env, agent_id);
+        match env
+            .storage()
+            .instance()
+            .get::<_, u64>(&Self::agent_cooldown_key(&env, agent_id))
+        {
+            Some(last_request) => last_request
+                .saturating_add(cooldown)
+                .saturating_sub(env.ledger().timestamp()),
+            None => 0,
+        }
+    }
+
+    /// Check if an agent is still within the cooldown of its last request
+    fn is_agent_on_cooldown(env: &Env, agent_id: u64) -> bool {
+        Self::get_agent_cooldown(env.clone(), agent_id) > 0
+    }
+
+    /// Start the agent's cooldown from the current ledger time; callers check
+    /// `is_cooldown_on_complete` to decide whether this is the trigger point
+    fn update_agent_cooldown(env: &Env, agent_id: u64) {
+        env.storage().instance().set(
+            &Self::agent_cooldown_key(env, agent_id),
+            &env.ledger().timestamp(),
+        );
+    }
+
+    /// Storage key of an agent's last request time; one slot per agent
+    fn agent_cooldown_key(env: &Env, agent_id: u64) -> (Symbol, u64) {
+        (Symbol::new(env, "agent_cd"), agent_id)
+    }
+
+    /// Set the minimum time between completed evolutions of an agent (admin only, 0 disables)
+    pub fn set_min_evolution_interval(env: Env, admin: Address, interval_seconds: u64) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if interval_seconds > MAX_AGE_SECONDS {
+            panic!("Interval exceeds maximum allowed duration");
+        }
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "min_evo_interval"), &interval_seconds);
+
+        env.events().publish(
+            (Symbol::new(&env, "min_evo_interval_set"),),
+            interval_seconds,
+        );
+    }
+
+    /// Get the minimum time between completed evolutions of an agent
+    pub fn get_min_evolution_interval(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "min_evo_interval"))
+            .unwrap_or(0)
+    }
+
+    /// Set the highest evolution level an agent can reach (admin only)
+    pub fn set_max_evolution_level(env: Env, admin: Address, max: u32) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "max_evo_level"), &max);
+
+        env.events()
+            .publish((Symbol::new(&env, "max_evo_level_set"),), max);
+    }
+
+    /// Get the highest evolution level an agent can reach (`u32::MAX` if unset)
+    pub fn get_max_evolution_level(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "max_evo_level"))
+            .unwrap_or(u32::MAX)
+    }
+
+    /// Set the minimum stake for an upgrade request (admin only)
+    pub fn set_min_stake(env: Env, admin: Address, min_stake: i128) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if min_stake <= 0 {
+            panic!("Minimum stake must be positive");
+        }
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, MIN_STAKE_KEY), &min_stake);
+
+        env.events()
+            .publish((Symbol::new(&env, "min_stake_set"),), min_stake);
+    }
+
+    /// Get the minimum stake for an upgrade request
+    pub fn get_min_stake(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, MIN_STAKE_KEY))
+            .unwrap_or(1)
+    }
+
+    /// Set the minimum stake and cooldown for agents in `tier`, i.e. with an evolution
+    /// level in `tier * LEVELS_PER_TIER..(tier + 1) * LEVELS_PER_TIER` (admin only)
+    pub fn set_tier_params(
+        env: Env,
+        admin: Address,
+        tier: u32,
+        min_stake: i128,
+        cooldown_seconds: u64,
+    ) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if min_stake <= 0 {
+            panic!("Minimum stake must be positive");
+        }
+        if cooldown_seconds > MAX_AGE_SECONDS {
+            panic!("Cooldown exceeds maximum allowed duration");
+        }
+
+        let tier_key = (Symbol::new(&env, "tier_params"), tier);
+        env.storage()
+            .instance()
+            .set(&tier_key, &(min_stake, cooldown_seconds));
+
+        env.events().publish(
+            (Symbol::new(&env, "tier_params_set"),),
+            (tier, min_stake, cooldown_seconds),
+        );
+    }
+
+    /// Get `(min_stake, cooldown_seconds)` for a tier, falling back to the global
+    /// `get_min_stake` / `get_cooldown` when the tier has no params of its own
+    pub fn get_tier_params(env: Env, tier: u32) -> (i128, u64) {
+        let tier_key = (Symbol::new(&env, "tier_params"), tier);
+        env.storage().instance().get(&tier_key).unwrap_or_else(|| {
+            (
+                Self::get_min_stake(env.clone()),
+                Self::get_cooldown(env.clone()),
+            )
+        })
+    }
+
+    /// Set the minimum stake for requests staked in `token`, in whole tokens with the
+    /// token's `decimals`; while `token` is the stake token, requests must meet both
+    /// this and the tier minimum (admin only)
+    pub fn set_token_min_stake(
+        env: Env,
+        admin: Address,
+        token: Address,
+        min_stake: i128,
+        decimals: u32,
+    ) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if min_stake <= 0 {
+            panic!("Minimum stake must be positive");
+        }
+        if Self::scale_to_decimals(min_stake, decimals).is_none() {
+            panic!("Minimum stake overflows token precision");
+        }
+
+        let token_key = (Symbol::new(&env, "token_min"), token.clone());
+        env.storage()
+            .instance()
+            .set(&token_key, &(min_stake, decimals));
+
+        env.events().publish(
+            (Symbol::new(&env, "token_min_stake_set"),),
+            (token, min_stake, decimals),
+        );
+    }
+
+    /// Get `(min_stake, decimals)` configured for `token`, if any
+    pub fn get_token_min_stake(env: Env, token: Address) -> Option<(i128, u32)> {
+        let token_key = (Symbol::new(&env, "token_min"), token);
+        env.storage().instance().get(&token_key)
+    }
+
+    /// Whole-token `amount` expressed in base units of a token with `decimals`
+    fn scale_to_decimals(amount: i128, decimals: u32) -> Option<i128> {
+        10i128.checked_pow(decimals)?.checked_mul(amount)
+    }
+
+    /// Register the Oracle contract whose `evolution_cost` feed sets the minimum stake
+    /// (admin only)
+    pub fn set_oracle_contract(env: Env, admin: Address, oracle: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "oracle"), &oracle);
+
+        env.events()
+            .publish((Symbol::new(&env, "oracle_contract_set"),), oracle);
+    }
+
+    /// Get the Oracle contract consulted for the evolution cost
+    pub fn get_oracle_contract(env: Env) -> Option<Address> {
+        env.storage().instance().get(&Symbol::new(&env, "oracle"))
+    }
+
+    /// Smallest stake `request_upgrade` accepts for the agent: the larger of its tier's
+    /// minimum stake and the stake token's own minimum, raised to the oracle's current
+    /// `evolution_cost` when one is reported
+    fn required_stake(env: &Env, agent_id: u64) -> i128 {
+        let token_min = env
+            .storage()
+            .instance()
+            .get::<_, Address>(&Symbol::new(env, STAKE_TOKEN_KEY))
+            .and_then(|token| Self::get_token_min_stake(env.clone(), token))
+            .and_then(|(min_stake, decimals)| Self::scale_to_decimals(min_stake, decimals));
+        let tier_min = Self::agent_tier_params(env, agent_id).0;
+        let min_stake = match token_min {
+            Some(token_min) => tier_min.max(token_min),
+            None => tier_min,
+        };
+        // A deprecated feed or unreachable oracle falls back to the minimum stake
+        let oracle_cost = Self::get_oracle_contract(env.clone()).and_then(|oracle| {
+            OracleDataClient::new(env, &oracle)
+                .try_get_data(&Symbol::new(env, "evolution_cost"))
+                .ok()
+                .and_then(|data| data.ok())
+                .flatten()
+        });
+        match oracle_cost {
+            Some(cost) => min_stake.max(cost.value),
+            None => min_stake,
+        }
+    }
+
+    /// Params for the tier of the agent's current evolution level
+    fn agent_tier_params(env: &Env, agent_id: u64) -> (i128, u64) {
+        let level = Self::get_evolution_level(env.clone(), agent_id);
+        Self::get_tier_params(env.clone(), level / LEVELS_PER_TIER)
+    }
+
+    /// Run the `request_upgrade` preconditions in order, returning the first that fails
+    fn check_upgrade_preconditions(
+        env: &Env,
+        agent_id: u64,
+        owner: &Address,
+        stake_amount: i128,
+    ) -> Result<(), UpgradeBlock> {
         if stake_amount <= 0 {
-            panic!("Stake amount must be positive");
+            return Err(UpgradeBlock::StakeNotPositive);
+        }
+        if stake_amount < Self::required_stake(env, agent_id) {
+            return Err(UpgradeBlock::StakeTooLow);
+        }
+        if let Some(agent_owner) = Self::current_agent_owner(env, agent_id) {
+            if &agent_owner != owner {
+                return Err(UpgradeBlock::NotOwner);
+            }
+        }
+        if Self::is_agent_on_cooldown(env, agent_id) {
+            return Err(UpgradeBlock::OnCooldown);
+        }
+        if Self::count_pending_requests(env.clone(), agent_id) >= MAX_PENDING_REQUESTS {
+            return Err(UpgradeBlock::TooManyPending);
+        }
+        if Self::get_evolution_level(env.clone(), agent_id)
+            >= Self::get_max_evolution_level(env.clone())
+        {
+            return Err(UpgradeBlock::MaxLevelReached);
+        }
+        Ok(())
+    }
+
+    /// Dry-run `request_upgrade`: returns `(true, "ok")` if it would be accepted,
+    /// otherwise `(false, reason)` for the first blocking precondition
+    pub fn can_request_upgrade(
+        env: Env,
+        agent_id: u64,
+        owner: Address,
+        stake_amount: i128,
+    ) -> (bool, Symbol) {
+        if agent_id == 0 {
+            panic!("Invalid agent ID");
+        }
+
+        match Self::check_upgrade_preconditions(&env, agent_id, &owner, stake_amount) {
+            Ok(()) => (true, Symbol::new(&env, "ok")),
+            Err(block) => (false, block.reason(&env)),
+        }
+    }
+
+    /// Request an upgrade for an agent, subject to the per-agent cooldown
+    pub fn request_upgrade(env: Env, agent_id: u64, owner: Address, stake_amount: i128) -> u64 {
+        owner.require_auth();
+
+        if agent_id == 0 {
+            panic!("Invalid agent ID");
+        }
+        if let Err(block) = Self::check_upgrade_preconditions(&env, agent_id, &owner, stake_amount)
+        {
+            block.raise(&env);
+        }
+
+        // Escrow the stake when a stake token is configured. Every precondition has
+        // been checked above, so a rejected request never moves tokens.
+        let stake_token: Option<Address> = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, STAKE_TOKEN_KEY));
+        if let Some(stake_token) = &stake_token {
+            token::Client::new(&env, stake_token).transfer(
+                &owner,
+                &env.current_contract_address(),
+                &stake_amount,
+            );
         }
 
         let counter: u64 = env
@@ -56,11 +587,26 @@ impl Evolution {
         };
 
         // Use tuple as key (prefix, request_id)
-        let request_key = (Symbol::new(&env, "request"), request_id);
+        let request_key = Self::request_key(&env, request_id);
         env.storage().instance().set(&request_key, &request);
         env.storage()
             .instance()
             .set(&Symbol::new(&env, REQUEST_COUNTER_KEY), &request_id);
+        env.storage()
+            .instance()
+            .set(&Self::stake_token_key(&env, request_id), &stake_token);
+        Self::add_pending_request(&env, agent_id, request_id);
+        Self::record_upgrade_history(&env, agent_id, request_id);
+
+        let attempts_key = (Symbol::new(&env, "attempts"), agent_id);
+        let attempts: u32 = env.storage().instance().get(&attempts_key).unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&attempts_key, &attempts.saturating_add(1));
+
+        if !Self::is_cooldown_on_complete(env.clone()) {
+            Self::update_agent_cooldown(&env, agent_id);
+        }
 
         env.events().publish(
             (Symbol::new(&env, "request_created"),),
@@ -70,13 +616,717 @@ impl Evolution {
         request_id
     }
 
+    /// Create an evolution request (alias of `request_upgrade` kept for existing callers)
+    pub fn create_request(env: Env, agent_id: u64, owner: Address, stake_amount: i128) -> u64 {
+        Self::request_upgrade(env, agent_id, owner, stake_amount)
+    }
+
     /// Get an evolution request
     pub fn get_request(env: Env, request_id: u64) -> Option<EvolutionRequest> {
         if request_id == 0 {
             panic!("Invalid request ID");
         }
 
-        let request_key = (Symbol::new(&env, "request"), request_id);
+        let request_key = Self::request_key(&env, request_id);
         env.storage().instance().get(&request_key)
     }
+
+    /// Cancel a pending request and refund its stake immediately (request owner only).
+    /// The request is marked `Failed`; the `request_cancelled` event tells the two apart.
+    pub fn cancel_request(env: Env, owner: Address, request_id: u64) {
+        owner.require_auth();
+
+        if request_id == 0 {
+            panic!("Invalid request ID");
+        }
+
+        let request_key = Self::request_key(&env, request_id);
+        let mut request: EvolutionRequest = env
+            .storage()
+            .instance()
+            .get(&request_key)
+            .expect("Upgrade request not found");
+
+        if request.owner != owner {
+            panic!("Unauthorized: caller is not request owner");
+        }
+        if request.status != EvolutionStatus::Pending {
+            panic!("Request is not in pending state");
+        }
+
+        request.status = EvolutionStatus::Failed;
+        request.completed_at = Some(env.ledger().timestamp());
+        env.storage().instance().set(&request_key, &request);
+        Self::remove_pending_request(&env, request.agent_id, request_id);
+        Self::release_stake(&env, request_id, &owner, request.stake_amount);
+
+        env.events().publish(
+            (Symbol::new(&env, "request_cancelled"),),
+            (request_id, request.agent_id, owner),
+        );
+    }
+
+    /// Transfer an agent together with one of its pending requests. The new owner
+    /// takes over the request, including the right to claim its stake.
+    pub fn transfer_with_request(
+        env: Env,
+        agent_id: u64,
+        from: Address,
+        to: Address,
+        request_id: u64,
+    ) {
+        from.require_auth();
+
+        if agent_id == 0 {
+            panic!("Invalid agent ID");
+        }
+        if request_id == 0 {
+            panic!("Invalid request ID");
+        }
+
+        let request_key = Self::request_key(&env, request_id);
+        let mut request: EvolutionRequest = env
+            .storage()
+            .instance()
+            .get(&request_key)
+            .expect("Upgrade request not found");
+
+        if request.agent_id != agent_id {
+            panic!("Request is not for this agent");
+        }
+        if request.status != EvolutionStatus::Pending {
+            panic!("Request is not in pending state");
+        }
+        if request.owner != from {
+            panic!("Unauthorized: caller is not request owner");
+        }
+
+        let agent_nft: Address = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, AGENT_NFT_KEY))
+            .expect("AgentNFT contract not configured");
+        AgentNftClient::new(&env, &agent_nft).transfer_agent(&agent_id, &from, &from, &to);
+
+        request.owner = to.clone();
+        env.storage().instance().set(&request_key, &request);
+
+        env.events().publish(
+            (Symbol::new(&env, "request_transferred"),),
+            (request_id, agent_id, from, to),
+        );
+    }
+
+    /// Get the IDs of all pending requests for an agent, oldest first
+    pub fn get_pending_request_ids(env: Env, agent_id: u64) -> Vec<u64> {
+        if agent_id == 0 {
+            panic!("Invalid agent ID");
+        }
+
+        let pending_key = (Symbol::new(&env, "pending"), agent_id);
+        env.storage()
+            .instance()
+            .get(&pending_key)
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Get the agent's `limit` most recent upgrade requests, oldest first, in their
+    /// current state (pending, completed or failed; cancelled requests show as failed)
+    pub fn get_upgrade_history(env: Env, agent_id: u64, limit: u32) -> Vec<EvolutionRequest> {
+        if limit > MAX_HISTORY_QUERY_LIMIT {
+            panic!("Limit exceeds maximum allowed (500)");
+        }
+
+        let history = Self::upgrade_history_ids(&env, agent_id);
+        let offset = history.len().saturating_sub(limit);
+        Self::resolve_requests(&env, &history, offset, limit)
+    }
+
+    /// Get a page of the agent's upgrade requests, oldest first
+    pub fn get_upgrade_history_paged(
+        env: Env,
+        agent_id: u64,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<EvolutionRequest> {
+        if limit > MAX_HISTORY_QUERY_LIMIT {
+            panic!("Limit exceeds maximum allowed (500)");
+        }
+
+        let history = Self::upgrade_history_ids(&env, agent_id);
+        Self::resolve_requests(&env, &history, offset, limit)
+    }
+
+    fn upgrade_history_ids(env: &Env, agent_id: u64) -> Vec<u64> {
+        if agent_id == 0 {
+            panic!("Invalid agent ID");
+        }
+
+        env.storage()
+            .instance()
+            .get(&(Symbol::new(env, "upg_hist"), agent_id))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn resolve_requests(
+        env: &Env,
+        request_ids: &Vec<u64>,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<EvolutionRequest> {
+        let mut requests = Vec::new(env);
+        let end = offset.saturating_add(limit).min(request_ids.len());
+        for i in offset..end {
+            let request_id = request_ids.get(i).unwrap();
+            if let Some(request) = env
+                .storage()
+                .instance()
+                .get(&Self::request_key(env, request_id))
+            {
+                requests.push_back(request);
+            }
+        }
+        requests
+    }
+
+    /// Get the IDs of the agent's requests that are still in the `Pending` state,
+    /// oldest first
+    pub fn get_pending_requests(env: Env, agent_id: u64) -> Vec<u64> {
+        let mut pending = Vec::new(&env);
+        for request_id in Self::get_pending_request_ids(env.clone(), agent_id).iter() {
+            if let Some(request) = Self::get_request(env.clone(), request_id) {
+                if request.status == EvolutionStatus::Pending {
+                    pending.push_back(request_id);
+                }
+            }
+        }
+        pending
+    }
+
+    /// Count the agent's requests that are still in the `Pending` state
+    pub fn count_pending_requests(env: Env, agent_id: u64) -> u32 {
+        Self::get_pending_requests(env, agent_id).len()
+    }
+
+    /// Complete a pending upgrade with the new model hash (admin only).
+    /// Returns the agent's new evolution level.
+    pub fn complete_upgrade(
+        env: Env,
+        admin: Address,
+        request_id: u64,
+        new_model_hash: String,
+    ) -> u32 {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if request_id == 0 {
+            panic!("Invalid request ID");
+        }
+        if new_model_hash.len() > MAX_STRING_LENGTH {
+            panic!("Model hash exceeds maximum length");
+        }
+
+        let request_key = Self::request_key(&env, request_id);
+        let request: EvolutionRequest = env
+            .storage()
+            .instance()
+            .get(&request_key)
+            .expect("Upgrade request not found");
+
+        if !Self::owner_unchanged(&env, &request) {
+            let agent_id = request.agent_id;
+            Self::fail_request(&env, request, Symbol::new(&env, "owner_changed"));
+            return Self::get_evolution_level(env, agent_id);
+        }
+
+        let new_level = Self::finalize_upgrade(&env, request, &new_model_hash);
+
+        env.events().publish(
+            (Symbol::new(&env, "EvolutionCompleted"),),
+            (request_id, new_level, env.ledger().timestamp()),
+        );
+
+        new_level
+    }
+
+    /// Register the Ed25519 public key that signs `provider`'s attestations (admin only)
+    pub fn register_oracle_key(
+        env: Env,
+        admin: Address,
+        provider: Address,
+        public_key: BytesN<32>,
+    ) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let key = (Symbol::new(&env, "oracle_key"), provider.clone());
+        env.storage().instance().set(&key, &public_key);
+
+        env.events()
+            .publish((Symbol::new(&env, "oracle_key_registered"),), provider);
+    }
+
+    /// Get the registered attestation public key of an oracle provider
+    pub fn get_oracle_key(env: Env, provider: Address) -> Option<BytesN<32>> {
+        let key = (Symbol::new(&env, "oracle_key"), provider);
+        env.storage().instance().get(&key)
+    }
+
+    /// The message an oracle signs for an attestation: `encode_for_hash` over
+    /// request_id, agent_id, oracle_provider, new_model_hash, attestation_data,
+    /// timestamp and nonce, in that order. The signature itself is not included.
+    pub fn get_attestation_message(env: Env, attestation: EvolutionAttestation) -> Bytes {
+        encode_for_hash(
+            &env,
+            &[
+                EncodablePart::U64(attestation.request_id),
+                EncodablePart::U64(attestation.agent_id),
+                EncodablePart::Address(attestation.oracle_provider),
+                EncodablePart::String(attestation.new_model_hash),
+                EncodablePart::Bytes(attestation.attestation_data),
+                EncodablePart::U64(attestation.timestamp),
+                EncodablePart::U64(attestation.nonce),
+            ],
+        )
+    }
+
+    /// Check the attestation's Ed25519 signature against the provider's registered key.
+    /// A malformed signature panics with "Invalid attestation signature"; a well-formed
+    /// one that does not verify aborts the invocation in the host.
+    fn verify_attestation_signature(env: &Env, attestation: &EvolutionAttestation) {
+        let public_key = Self::get_oracle_key(env.clone(), attestation.oracle_provider.clone())
+            .expect("Oracle key not registered");
+        let signature: BytesN<64> = attestation
+            .signature
+            .clone()
+            .try_into()
+            .unwrap_or_else(|_| panic!("Invalid attestation signature"));
+        let message = Self::get_attestation_message(env.clone(), attestation.clone());
+        env.crypto()
+            .ed25519_verify(&public_key, &message, &signature);
+    }
+
+    /// Set how many distinct oracle providers must attest a request before it
+    /// completes (admin only, defaults to 1)
+    pub fn set_attestation_quorum(env: Env, admin: Address, threshold: u32) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if threshold == 0 || threshold > MAX_ATTESTATION_QUORUM {
+            panic!("Attestation quorum out of bounds");
+        }
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "att_quorum"), &threshold);
+
+        env.events()
+            .publish((Symbol::new(&env, "attestation_quorum_set"),), threshold);
+    }
+
+    /// Get the number of distinct providers required to complete a request
+    pub fn get_attestation_quorum(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "att_quorum"))
+            .unwrap_or(1)
+    }
+
+    /// Get `(received, threshold)`: distinct providers that have attested the request
+    /// so far, and the quorum currently required
+    pub fn get_attestation_progress(env: Env, request_id: u64) -> (u32, u32) {
+        let providers = Self::request_attestors(&env, request_id);
+        (providers.len(), Self::get_attestation_quorum(env))
+    }
+
+    fn request_attestors(env: &Env, request_id: u64) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&(Symbol::new(env, "attestors"), request_id))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Apply an oracle attestation for a pending upgrade. The upgrade completes once
+    /// `get_attestation_quorum` distinct providers have attested the same model hash.
+    pub fn apply_attestation(env: Env, attestation: EvolutionAttestation) {
+        if attestation.request_id == 0 {
+            panic!("Invalid request ID");
+        }
+        if attestation.agent_id == 0 {
+            panic!("Invalid agent ID");
+        }
+        if attestation.new_model_hash.len() > MAX_STRING_LENGTH {
+            panic!("Model hash exceeds maximum length");
+        }
+        if attestation.signature.len() as usize != ATTESTATION_SIGNATURE_SIZE {
+            panic!("Invalid signature size");
+        }
+        if attestation.attestation_data.len() as usize > MAX_ATTESTATION_DATA_SIZE {
+            panic!("Attestation data exceeds maximum size");
+        }
+
+        attestation.oracle_provider.require_auth();
+        Self::verify_attestation_signature(&env, &attestation);
+
+        // Replay protection: attestation nonces must strictly increase per agent
+        let nonce_key = (Symbol::new(&env, "att_nonce"), attestation.agent_id);
+        let stored_nonce: Option<u64> = env.storage().instance().get(&nonce_key);
+        if let Some(prev_nonce) = stored_nonce {
+            if attestation.nonce <= prev_nonce {
+                panic!("Replay protection: invalid or reused nonce");
+            }
+        }
+
+        let request_key = Self::request_key(&env, attestation.request_id);
+        let request: EvolutionRequest = env
+            .storage()
+            .instance()
+            .get(&request_key)
+            .expect("Upgrade request not found");
+
+        if request.agent_id != attestation.agent_id {
+            panic!("Agent ID mismatch in attestation");
+        }
+        if request.status != EvolutionStatus::Pending {
+            panic!("Request is not in pending state");
+        }
+
+        // Every attestation towards the quorum must vouch for the same model hash
+        let hash_key = (Symbol::new(&env, "att_hash"), attestation.request_id);
+        let mut attestors = Self::request_attestors(&env, attestation.request_id);
+        if attestors.contains(&attestation.oracle_provider) {
+            panic!("Provider already attested this request");
+        }
+        if attestors.is_empty() {
+            env.storage()
+                .instance()
+                .set(&hash_key, &attestation.new_model_hash);
+        } else {
+            let agreed_hash: String = env.storage().instance().get(&hash_key).unwrap();
+            if agreed_hash != attestation.new_model_hash {
+                panic!("Attestation model hash mismatch");
+            }
+        }
+        attestors.push_back(attestation.oracle_provider.clone());
+        env.storage().instance().set(
+            &(Symbol::new(&env, "attestors"), attestation.request_id),
+            &attestors,
+        );
+
+        env.storage().instance().set(&nonce_key, &attestation.nonce);
+
+        let threshold = Self::get_attestation_quorum(env.clone());
+        if attestors.len() < threshold {
+            env.events().publish(
+                (Symbol::new(&env, "attestation_recorded"),),
+                (
+                    attestation.request_id,
+                    attestation.oracle_provider,
+                    attestors.len(),
+                    threshold,
+                ),
+            );
+            return;
+        }
+
+        if !Self::owner_unchanged(&env, &request) {
+            Self::fail_request(&env, request, Symbol::new(&env, "owner_changed"));
+            return;
+        }
+
+        let new_level = Self::finalize_upgrade(&env, request, &attestation.new_model_hash);
+
+        env.events().publish(
+            (Symbol::new(&env, "EvolutionCompleted"),),
+            (
+                attestation.request_id,
+                new_level,
+                attestation.oracle_provider,
+                env.ledger().timestamp(),
+            ),
+        );
+    }
+
+    /// Reclaim the stake of a completed or failed request (request owner only)
+    pub fn claim_stake(env: Env, owner: Address, request_id: u64) -> i128 {
+        owner.require_auth();
+
+        if request_id == 0 {
+            panic!("Invalid request ID");
+        }
+
+        let request_key = Self::request_key(&env, request_id);
+        let request: EvolutionRequest = env
+            .storage()
+            .instance()
+            .get(&request_key)
+            .expect("Upgrade request not found");
+
+        if request.owner != owner {
+            panic!("Unauthorized: caller is not request owner");
+        }
+        if request.status != EvolutionStatus::Completed && request.status != EvolutionStatus::Failed
+        {
+            panic!("Stake is not claimable");
+        }
+
+        let claimed_key = (Symbol::new(&env, "stake_claimed"), request_id);
+        if env.storage().instance().has(&claimed_key) {
+            panic!("Stake already claimed");
+        }
+
+        let stake_token =
+            Self::escrowed_stake_token(&env, request_id).expect("Stake token not configured");
+
+        // Mark claimed before transferring out
+        env.storage().instance().set(&claimed_key, &true);
+        token::Client::new(&env, &stake_token).transfer(
+            &env.current_contract_address(),
+            &owner,
+            &request.stake_amount,
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, "stake_claimed"),),
+            (request_id, owner, request.stake_amount),
+        );
+
+        request.stake_amount
+    }
+
+    /// Fail a pending request (admin only). With `slash` the stake goes to the admin
+    /// and can no longer be claimed; otherwise the owner may reclaim it via `claim_stake`.
+    pub fn fail_upgrade(env: Env, admin: Address, request_id: u64, slash: bool) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if request_id == 0 {
+            panic!("Invalid request ID");
+        }
+
+        let request: EvolutionRequest = env
+            .storage()
+            .instance()
+            .get(&Self::request_key(&env, request_id))
+            .expect("Upgrade request not found");
+        let (agent_id, stake_amount) = (request.agent_id, request.stake_amount);
+        Self::fail_request(&env, request, Symbol::new(&env, "admin_failed"));
+
+        if slash {
+            Self::release_stake(&env, request_id, &admin, stake_amount);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "EvolutionFailed"),),
+            (request_id, agent_id, slash),
+        );
+    }
+
+    /// Get how many upgrades have been requested for an agent, whatever their outcome
+    pub fn get_attempt_count(env: Env, agent_id: u64) -> u32 {
+        if agent_id == 0 {
+            panic!("Invalid agent ID");
+        }
+
+        let attempts_key = (Symbol::new(&env, "attempts"), agent_id);
+        env.storage().instance().get(&attempts_key).unwrap_or(0)
+    }
+
+    /// Time a completed or failed request took from creation to resolution (cancelled
+    /// requests count as failed); `None` while it is pending
+    pub fn get_request_duration(env: Env, request_id: u64) -> Option<u64> {
+        let request = Self::get_request(env, request_id)?;
+        match request.status {
+            EvolutionStatus::Completed | EvolutionStatus::Failed => request
+                .completed_at
+                .map(|completed_at| completed_at.saturating_sub(request.created_at)),
+            _ => None,
+        }
+    }
+
+    /// Average duration of an agent's completed upgrades in seconds (0 if none)
+    pub fn get_avg_evolution_duration(env: Env, agent_id: u64) -> u64 {
+        if agent_id == 0 {
+            panic!("Invalid agent ID");
+        }
+
+        let duration_key = (Symbol::new(&env, "evo_duration"), agent_id);
+        let (total, count): (u64, u64) = env
+            .storage()
+            .instance()
+            .get(&duration_key)
+            .unwrap_or((0, 0));
+        total.checked_div(count).unwrap_or(0)
+    }
+
+    /// Get current evolution level of an agent
+    pub fn get_evolution_level(env: Env, agent_id: u64) -> u32 {
+        if agent_id == 0 {
+            panic!("Invalid agent ID");
+        }
+
+        let level_key = (Symbol::new(&env, "evo_level"), agent_id);
+        env.storage().instance().get(&level_key).unwrap_or(0)
+    }
+
+    /// Mark a pending request completed and bump the agent's evolution level.
+    /// Shared by `complete_upgrade` and `apply_attestation`; returns the new level.
+    fn finalize_upgrade(env: &Env, mut request: EvolutionRequest, new_model_hash: &String) -> u32 {
+        if request.status != EvolutionStatus::Pending {
+            panic!("Request is not in pending state");
+        }
+
+        let now = env.ledger().timestamp();
+        let last_evolved_key = (Symbol::new(env, "last_evolved"), request.agent_id);
+        if let Some(last_evolved) = env.storage().instance().get::<_, u64>(&last_evolved_key) {
+            let interval = Self::get_min_evolution_interval(env.clone());
+            if now.saturating_sub(last_evolved) < interval {
+                panic_with_error!(env, ContractError::OnCooldown);
+            }
+        }
+        env.storage().instance().set(&last_evolved_key, &now);
+        if Self::is_cooldown_on_complete(env.clone()) {
+            Self::update_agent_cooldown(env, request.agent_id);
+        }
+
+        let level_key = (Symbol::new(env, "evo_level"), request.agent_id);
+        let old_level: u32 = env.storage().instance().get(&level_key).unwrap_or(0);
+        if old_level >= Self::get_max_evolution_level(env.clone()) {
+            panic!("Maximum evolution level reached");
+        }
+        let new_level = old_level.checked_add(1).expect("Evolution level overflow");
+        env.storage().instance().set(&level_key, &new_level);
+
+        request.status = EvolutionStatus::Completed;
+        request.completed_at = Some(now);
+
+        let duration_key = (Symbol::new(env, "evo_duration"), request.agent_id);
+        let (total, count): (u64, u64) = env
+            .storage()
+            .instance()
+            .get(&duration_key)
+            .unwrap_or((0, 0));
+        let duration = now.saturating_sub(request.created_at);
+        env.storage().instance().set(
+            &duration_key,
+            &(total.saturating_add(duration), count.saturating_add(1)),
+        );
+        let request_key = Self::request_key(env, request.request_id);
+        env.storage().instance().set(&request_key, &request);
+        Self::remove_pending_request(env, request.agent_id, request.request_id);
+
+        env.events().publish(
+            (Symbol::new(env, "level_up"), request.agent_id),
+            (old_level, new_level, new_model_hash.clone()),
+        );
+
+        new_level
+    }
+
+    /// Mark a pending request failed, leaving its stake claimable
+    fn fail_request(env: &Env, mut request: EvolutionRequest, reason: Symbol) {
+        if request.status != EvolutionStatus::Pending {
+            panic!("Request is not in pending state");
+        }
+
+        request.status = EvolutionStatus::Failed;
+        request.completed_at = Some(env.ledger().timestamp());
+        let request_key = Self::request_key(env, request.request_id);
+        env.storage().instance().set(&request_key, &request);
+        Self::remove_pending_request(env, request.agent_id, request.request_id);
+
+        env.events().publish(
+            (Symbol::new(env, "request_failed"),),
+            (request.request_id, request.agent_id, reason),
+        );
+    }
+
+    /// Consume the request's stake lock and pay the escrowed stake (if any was taken)
+    /// to `recipient`; `claim_stake` rejects the request afterwards
+    fn release_stake(env: &Env, request_id: u64, recipient: &Address, amount: i128) {
+        let claimed_key = (Symbol::new(env, "stake_claimed"), request_id);
+        if env.storage().instance().has(&claimed_key) {
+            panic!("Stake already claimed");
+        }
+        env.storage().instance().set(&claimed_key, &true);
+
+        if let Some(stake_token) = Self::escrowed_stake_token(env, request_id) {
+            token::Client::new(env, &stake_token).transfer(
+                &env.current_contract_address(),
+                recipient,
+                &amount,
+            );
+        }
+    }
+
+    /// Token a request's stake was escrowed in, or `None` if no stake was taken.
+    /// Requests created before the token was recorded fall back to the current one.
+    pub fn get_request_stake_token(env: Env, request_id: u64) -> Option<Address> {
+        Self::escrowed_stake_token(&env, request_id)
+    }
+
+    fn escrowed_stake_token(env: &Env, request_id: u64) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get::<_, Option<Address>>(&Self::stake_token_key(env, request_id))
+            .unwrap_or_else(|| {
+                env.storage()
+                    .instance()
+                    .get(&Symbol::new(env, STAKE_TOKEN_KEY))
+            })
+    }
+
+    /// Storage key of the token a request's stake was escrowed in
+    fn stake_token_key(env: &Env, request_id: u64) -> (Symbol, u64) {
+        (Symbol::new(env, "stake_tok"), request_id)
+    }
+
+    /// Storage key of a single request; one slot per request id
+    fn request_key(env: &Env, request_id: u64) -> (Symbol, u64) {
+        (Symbol::new(env, "request"), request_id)
+    }
+
+    /// Append a request to the agent's pending index
+    fn add_pending_request(env: &Env, agent_id: u64, request_id: u64) {
+        let pending_key = (Symbol::new(env, "pending"), agent_id);
+        let mut pending: Vec<u64> = env
+            .storage()
+            .instance()
+            .get(&pending_key)
+            .unwrap_or_else(|| Vec::new(env));
+        pending.push_back(request_id);
+        env.storage().instance().set(&pending_key, &pending);
+    }
+
+    /// Append a request to the agent's upgrade history, evicting the oldest entry
+    /// once MAX_HISTORY_SIZE is reached
+    fn record_upgrade_history(env: &Env, agent_id: u64, request_id: u64) {
+        let mut history = Self::upgrade_history_ids(env, agent_id);
+        if history.len() >= MAX_HISTORY_SIZE {
+            history.pop_front();
+        }
+        history.push_back(request_id);
+        env.storage()
+            .instance()
+            .set(&(Symbol::new(env, "upg_hist"), agent_id), &history);
+    }
+
+    /// Drop a request from the agent's pending index
+    fn remove_pending_request(env: &Env, agent_id: u64, request_id: u64) {
+        let pending_key = (Symbol::new(env, "pending"), agent_id);
+        let mut pending: Vec<u64> = env
+            .storage()
+            .instance()
+            .get(&pending_key)
+            .unwrap_or_else(|| Vec::new(env));
+        if let Some(index) = pending.first_index_of(request_id) {
+            pending.remove(index);
+        }
+
+        if pending.is_empty() {
+            env.storage().instance().remove(&pending_key);
+        } else {
+            env.storage().instance().set(&pending_key, &pending);
+        }
+    }
 }
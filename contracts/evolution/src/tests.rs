@@ -0,0 +1,1417 @@
+#![cfg(test)]
+
+use crate::testutils::TestAgents;
+use crate::{Evolution, EvolutionClient};
+use agent_nft::{AgentNFT, AgentNFTClient};
+use ed25519_dalek::{Signer, SigningKey};
+use marketplace::{Marketplace, MarketplaceClient};
+use oracle::{Oracle, OracleClient};
+use soroban_sdk::testutils::{Address as _, Events, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{Address, Bytes, BytesN, Env, IntoVal, String, Symbol, TryFromVal, Val, Vec};
+use stellai_lib::{
+    errors::ContractError, EvolutionAttestation, EvolutionStatus, LEVELS_PER_TIER,
+    MAX_ATTESTATION_DATA_SIZE, MAX_PENDING_REQUESTS, MAX_STRING_LENGTH,
+};
+
+fn setup_evolution(env: &Env) -> (EvolutionClient<'_>, Address, Address) {
+    let contract_id = env.register_contract(None, Evolution);
+    let client = EvolutionClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    let owner = Address::generate(env);
+
+    env.mock_all_auths();
+    client.init_contract(&admin);
+
+    (client, admin, owner)
+}
+
+/// Registers a fresh provider with the Ed25519 key derived from `seed` and returns
+/// the provider with its signer; use a distinct seed per provider
+fn setup_attestor(
+    env: &Env,
+    client: &EvolutionClient,
+    admin: &Address,
+    seed: u8,
+) -> (Address, SigningKey) {
+    let provider = Address::generate(env);
+    let signer = SigningKey::from_bytes(&[seed; 32]);
+    client.register_oracle_key(
+        admin,
+        &provider,
+        &BytesN::from_array(env, &signer.verifying_key().to_bytes()),
+    );
+    (provider, signer)
+}
+
+/// Builds an attestation signed over the contract's attestation message
+fn attestation(
+    env: &Env,
+    client: &EvolutionClient,
+    attestor: &(Address, SigningKey),
+    request_id: u64,
+    agent_id: u64,
+    nonce: u64,
+) -> EvolutionAttestation {
+    let mut attestation = EvolutionAttestation {
+        request_id,
+        agent_id,
+        oracle_provider: attestor.0.clone(),
+        new_model_hash: String::from_str(env, "attested_hash"),
+        attestation_data: Bytes::from_slice(env, b"attestation"),
+        signature: Bytes::from_slice(env, &[0u8; 64]),
+        timestamp: env.ledger().timestamp(),
+        nonce,
+    };
+    sign_attestation(env, client, &attestor.1, &mut attestation);
+    attestation
+}
+
+/// (Re)signs `attestation` over its current fields
+fn sign_attestation(
+    env: &Env,
+    client: &EvolutionClient,
+    signer: &SigningKey,
+    attestation: &mut EvolutionAttestation,
+) {
+    let message = client.get_attestation_message(attestation);
+    let mut buf = [0u8; 512];
+    let len = message.len() as usize;
+    message.copy_into_slice(&mut buf[..len]);
+    attestation.signature = Bytes::from_array(env, &signer.sign(&buf[..len]).to_bytes());
+}
+
+/// Returns the data payload of the last `level_up` event published for `agent_id`
+fn last_level_up(env: &Env, agent_id: u64) -> Option<(u32, u32, String)> {
+    let expected_topics: soroban_sdk::Vec<Val> =
+        (Symbol::new(env, "level_up"), agent_id).into_val(env);
+    let mut found = None;
+    for (_, topics, data) in env.events().all().iter() {
+        if topics == expected_topics {
+            found = Some(<(u32, u32, String)>::try_from_val(env, &data).unwrap());
+        }
+    }
+    found
+}
+
+#[test]
+fn test_complete_upgrade_emits_level_up() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    let request_id = client.create_request(&1, &owner, &100);
+    let hash = String::from_str(&env, "hash_v1");
+    client.complete_upgrade(&admin, &request_id, &hash);
+
+    assert_eq!(last_level_up(&env, 1), Some((0, 1, hash)));
+    assert_eq!(client.get_evolution_level(&1), 1);
+    assert!(client.get_request(&request_id).unwrap().status == EvolutionStatus::Completed);
+}
+
+#[test]
+fn test_apply_attestation_emits_level_up() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    let first = client.create_request(&7, &owner, &100);
+    client.complete_upgrade(&admin, &first, &String::from_str(&env, "hash_v1"));
+
+    let attestor = setup_attestor(&env, &client, &admin, 7);
+    let second = client.create_request(&7, &owner, &100);
+    client.apply_attestation(&attestation(&env, &client, &attestor, second, 7, 1));
+
+    assert_eq!(
+        last_level_up(&env, 7),
+        Some((1, 2, String::from_str(&env, "attested_hash")))
+    );
+    assert_eq!(client.get_evolution_level(&7), 2);
+}
+
+#[test]
+fn test_max_evolution_level_caps_both_completion_paths() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    assert_eq!(client.get_max_evolution_level(), u32::MAX);
+
+    client.set_max_evolution_level(&admin, &1);
+    assert_eq!(client.get_max_evolution_level(), 1);
+
+    let first = client.create_request(&1, &owner, &100);
+    let second = client.create_request(&1, &owner, &100);
+    client.complete_upgrade(&admin, &first, &String::from_str(&env, "hash_v1"));
+    assert_eq!(client.get_evolution_level(&1), 1);
+
+    // Requests queued before the agent hit the cap are still rejected on completion
+    assert!(client
+        .try_complete_upgrade(&admin, &second, &String::from_str(&env, "hash_v2"))
+        .is_err());
+
+    let attestor = setup_attestor(&env, &client, &admin, 7);
+    assert!(client
+        .try_apply_attestation(&attestation(&env, &client, &attestor, second, 1, 1))
+        .is_err());
+    assert_eq!(client.get_evolution_level(&1), 1);
+    assert!(client.get_request(&second).unwrap().status == EvolutionStatus::Pending);
+}
+
+#[test]
+#[should_panic(expected = "Maximum evolution level reached")]
+fn test_complete_upgrade_rejected_at_max_level() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    let request_id = client.create_request(&1, &owner, &100);
+    client.set_max_evolution_level(&admin, &0);
+    client.complete_upgrade(&admin, &request_id, &String::from_str(&env, "hash_v1"));
+}
+
+#[test]
+#[should_panic(expected = "Request is not in pending state")]
+fn test_complete_upgrade_twice_rejected() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    let request_id = client.create_request(&1, &owner, &100);
+    let hash = String::from_str(&env, "hash_v1");
+    client.complete_upgrade(&admin, &request_id, &hash);
+    client.complete_upgrade(&admin, &request_id, &hash);
+}
+
+#[test]
+fn test_pending_request_ids_track_cancel_and_completion() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    assert_eq!(client.get_pending_request_ids(&1).len(), 0);
+
+    let first = client.create_request(&1, &owner, &100);
+    let second = client.create_request(&1, &owner, &100);
+    let third = client.create_request(&1, &owner, &100);
+    let other_agent = client.create_request(&2, &owner, &100);
+
+    assert_eq!(
+        client.get_pending_request_ids(&1),
+        soroban_sdk::vec![&env, first, second, third]
+    );
+
+    client.cancel_request(&owner, &second);
+    assert_eq!(
+        client.get_pending_request_ids(&1),
+        soroban_sdk::vec![&env, first, third]
+    );
+    assert!(client.get_request(&second).unwrap().status == EvolutionStatus::Failed);
+
+    client.complete_upgrade(&admin, &first, &String::from_str(&env, "hash_v1"));
+    assert_eq!(
+        client.get_pending_request_ids(&1),
+        soroban_sdk::vec![&env, third]
+    );
+    assert_eq!(
+        client.get_pending_request_ids(&2),
+        soroban_sdk::vec![&env, other_agent]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized: caller is not request owner")]
+fn test_cancel_request_rejects_non_owner() {
+    let env = Env::default();
+    let (client, _admin, owner) = setup_evolution(&env);
+
+    let request_id = client.create_request(&1, &owner, &100);
+    client.cancel_request(&Address::generate(&env), &request_id);
+}
+
+#[test]
+fn test_cooldown_exempt_agent_can_request_back_to_back() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    client.set_cooldown(&admin, &3600);
+    client.set_cooldown_exempt(&admin, &1, &true);
+    assert!(client.is_cooldown_exempt(&1));
+
+    client.request_upgrade(&1, &owner, &100);
+    client.request_upgrade(&1, &owner, &100);
+    assert_eq!(client.get_pending_request_ids(&1).len(), 2);
+
+    // Non-exempt agent is still cooldown-limited
+    client.request_upgrade(&2, &owner, &100);
+    assert!(client.try_request_upgrade(&2, &owner, &100).is_err());
+
+    env.ledger().with_mut(|li| li.timestamp += 3600);
+    client.request_upgrade(&2, &owner, &100);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #20)")]
+fn test_removing_cooldown_exemption_restores_cooldown() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    client.set_cooldown(&admin, &3600);
+    client.set_cooldown_exempt(&admin, &1, &true);
+    client.request_upgrade(&1, &owner, &100);
+
+    client.set_cooldown_exempt(&admin, &1, &false);
+    client.request_upgrade(&1, &owner, &100);
+}
+
+#[test]
+fn test_on_request_cooldown_survives_cancel() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    client.set_cooldown(&admin, &3600);
+    assert!(!client.is_cooldown_on_complete());
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    client.cancel_request(&owner, &request_id);
+
+    assert_eq!(client.get_agent_cooldown(&1), 3600);
+    assert!(client.try_request_upgrade(&1, &owner, &100).is_err());
+}
+
+#[test]
+fn test_on_complete_cooldown_starts_only_after_completion() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    client.set_cooldown(&admin, &3600);
+    client.set_cooldown_on_complete(&admin, &true);
+    assert!(client.is_cooldown_on_complete());
+
+    // A cancelled request does not burn the cooldown
+    let cancelled = client.request_upgrade(&1, &owner, &100);
+    client.cancel_request(&owner, &cancelled);
+    assert_eq!(client.get_agent_cooldown(&1), 0);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    assert_eq!(client.get_agent_cooldown(&1), 0);
+
+    env.ledger().with_mut(|li| li.timestamp += 100);
+    client.complete_upgrade(&admin, &request_id, &String::from_str(&env, "hash_v1"));
+    assert_eq!(client.get_agent_cooldown(&1), 3600);
+    assert!(client.try_request_upgrade(&1, &owner, &100).is_err());
+
+    // Switching back to on-request leaves the recorded cooldown in place
+    client.set_cooldown_on_complete(&admin, &false);
+    assert!(!client.is_cooldown_on_complete());
+    assert_eq!(client.get_agent_cooldown(&1), 3600);
+}
+
+#[test]
+fn test_complete_upgrade_returns_new_level() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    let first = client.request_upgrade(&1, &owner, &100);
+    let second = client.request_upgrade(&1, &owner, &100);
+
+    let level = client.complete_upgrade(&admin, &first, &String::from_str(&env, "hash_v1"));
+    assert_eq!(level, 1);
+    assert_eq!(level, client.get_evolution_level(&1));
+
+    let level = client.complete_upgrade(&admin, &second, &String::from_str(&env, "hash_v2"));
+    assert_eq!(level, 2);
+    assert_eq!(level, client.get_evolution_level(&1));
+}
+
+/// Wires an AgentNFT contract holding agent 1 for `owner` and a stake token funding `owner`
+fn setup_agent_and_stake<'a>(
+    env: &Env,
+    client: &EvolutionClient,
+    admin: &Address,
+    owner: &Address,
+) -> (AgentNFTClient<'a>, TokenClient<'a>) {
+    let nft_id = env.register_contract(None, AgentNFT);
+    let nft = AgentNFTClient::new(env, &nft_id);
+    let nft_admin = Address::generate(env);
+    nft.init_contract(&nft_admin);
+    TestAgents::mint(env, &nft_id, &nft_admin, 1, owner);
+
+    let token = env.register_stellar_asset_contract_v2(admin.clone());
+    StellarAssetClient::new(env, &token.address()).mint(owner, &1_000);
+
+    client.set_agent_nft(admin, &nft_id);
+    client.set_stake_token(admin, &token.address());
+
+    (nft, TokenClient::new(env, &token.address()))
+}
+
+#[test]
+fn test_two_agents_evolve_independently() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let other_owner = Address::generate(&env);
+    client.set_cooldown(&admin, &3600);
+
+    let nft_id = env.register_contract(None, AgentNFT);
+    let nft = AgentNFTClient::new(&env, &nft_id);
+    let nft_admin = Address::generate(&env);
+    nft.init_contract(&nft_admin);
+    client.set_agent_nft(&admin, &nft_id);
+
+    let token = env.register_stellar_asset_contract_v2(admin.clone());
+    StellarAssetClient::new(&env, &token.address()).mint(&owner, &1_000);
+    StellarAssetClient::new(&env, &token.address()).mint(&other_owner, &1_000);
+    client.set_stake_token(&admin, &token.address());
+    let token = TokenClient::new(&env, &token.address());
+
+    TestAgents::mint(&env, &nft_id, &nft_admin, 1, &owner);
+    let second_request = TestAgents::mint_and_request(
+        &env,
+        &nft_id,
+        &client.address,
+        &nft_admin,
+        2,
+        &other_owner,
+        100,
+    );
+
+    let level =
+        TestAgents::evolve_in_place(&env, &client.address, &admin, 1, &owner, 100, "hash_a1");
+    assert_eq!(level, 1);
+
+    // Agent 1 is now on cooldown; agent 2's pending request is untouched
+    assert!(client.get_agent_cooldown(&1) > 0);
+    assert_eq!(client.get_pending_request_ids(&2).len(), 1);
+    client.complete_upgrade(&admin, &second_request, &String::from_str(&env, "hash_b1"));
+
+    env.ledger().with_mut(|li| li.timestamp += 3600);
+    let level =
+        TestAgents::evolve_in_place(&env, &client.address, &admin, 1, &owner, 100, "hash_a2");
+    assert_eq!(level, 2);
+
+    assert_eq!(client.get_evolution_level(&1), 2);
+    assert_eq!(client.get_evolution_level(&2), 1);
+    assert_eq!(client.get_upgrade_history(&1, &10).len(), 2);
+    assert_eq!(client.get_upgrade_history(&2, &10).len(), 1);
+    assert_eq!(nft.get_agent_owner(&1), owner);
+    assert_eq!(nft.get_agent_owner(&2), other_owner);
+    assert_eq!(token.balance(&owner), 800);
+    assert_eq!(token.balance(&other_owner), 900);
+}
+
+#[test]
+fn test_completion_blocked_after_agent_sold_mid_flight() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let (nft, token) = setup_agent_and_stake(&env, &client, &admin, &owner);
+    let buyer = Address::generate(&env);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    assert_eq!(token.balance(&owner), 900);
+
+    nft.transfer_agent(&1, &owner, &owner, &buyer);
+
+    let level = client.complete_upgrade(&admin, &request_id, &String::from_str(&env, "hash_v1"));
+    assert_eq!(level, 0);
+    assert_eq!(client.get_evolution_level(&1), 0);
+    assert!(client.get_request(&request_id).unwrap().status == EvolutionStatus::Failed);
+    assert_eq!(client.get_pending_request_ids(&1).len(), 0);
+
+    assert_eq!(client.claim_stake(&owner, &request_id), 100);
+    assert_eq!(token.balance(&owner), 1_000);
+}
+
+#[test]
+fn test_completion_allowed_after_owner_change_when_flag_set() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let (nft, _token) = setup_agent_and_stake(&env, &client, &admin, &owner);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    nft.transfer_agent(&1, &owner, &owner, &Address::generate(&env));
+
+    client.set_allow_owner_change(&admin, &true);
+    let expected_topics: Vec<Val> = (Symbol::new(&env, "allow_owner_change_set"),).into_val(&env);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, expected_topics);
+    assert!(bool::try_from_val(&env, &data).unwrap());
+
+    let level = client.complete_upgrade(&admin, &request_id, &String::from_str(&env, "hash_v1"));
+    assert_eq!(level, 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #10)")]
+fn test_request_upgrade_requires_current_agent_owner() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    setup_agent_and_stake(&env, &client, &admin, &owner);
+
+    client.request_upgrade(&1, &Address::generate(&env), &100);
+}
+
+#[test]
+#[should_panic(expected = "Stake already claimed")]
+fn test_stake_cannot_be_claimed_twice() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    setup_agent_and_stake(&env, &client, &admin, &owner);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    client.complete_upgrade(&admin, &request_id, &String::from_str(&env, "hash_v1"));
+
+    client.claim_stake(&owner, &request_id);
+    client.claim_stake(&owner, &request_id);
+}
+
+#[test]
+fn test_can_request_upgrade_reports_each_reason() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    setup_agent_and_stake(&env, &client, &admin, &owner);
+    let ok = (true, Symbol::new(&env, "ok"));
+
+    client.set_min_stake(&admin, &50);
+    assert_eq!(client.can_request_upgrade(&1, &owner, &100), ok);
+    assert_eq!(
+        client.can_request_upgrade(&1, &owner, &49),
+        (false, Symbol::new(&env, "stake_too_low"))
+    );
+    assert_eq!(
+        client.can_request_upgrade(&1, &Address::generate(&env), &100),
+        (false, Symbol::new(&env, "not_owner"))
+    );
+
+    client.set_cooldown(&admin, &3600);
+    client.request_upgrade(&1, &owner, &100);
+    assert_eq!(
+        client.can_request_upgrade(&1, &owner, &100),
+        (false, Symbol::new(&env, "on_cooldown"))
+    );
+
+    client.set_cooldown_exempt(&admin, &1, &true);
+    for _ in 1..5 {
+        assert_eq!(client.can_request_upgrade(&1, &owner, &100), ok);
+        client.request_upgrade(&1, &owner, &100);
+    }
+    assert_eq!(
+        client.can_request_upgrade(&1, &owner, &100),
+        (false, Symbol::new(&env, "too_many_pending"))
+    );
+}
+
+#[test]
+fn test_request_upgrade_rejected_at_max_level() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    client.set_max_evolution_level(&admin, &0);
+    assert_eq!(
+        client.can_request_upgrade(&1, &owner, &100),
+        (false, Symbol::new(&env, "max_level"))
+    );
+    assert!(client.try_request_upgrade(&1, &owner, &100).is_err());
+    assert_eq!(client.count_pending_requests(&1), 0);
+
+    client.set_max_evolution_level(&admin, &1);
+    assert_eq!(
+        client.can_request_upgrade(&1, &owner, &100),
+        (true, Symbol::new(&env, "ok"))
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #24)")]
+fn test_request_upgrade_rejects_stake_below_minimum() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    client.set_min_stake(&admin, &50);
+    client.request_upgrade(&1, &owner, &49);
+}
+
+#[test]
+fn test_transfer_with_request_hands_over_completion_and_stake() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let (nft, token) = setup_agent_and_stake(&env, &client, &admin, &owner);
+    let buyer = Address::generate(&env);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    client.transfer_with_request(&1, &owner, &buyer, &request_id);
+
+    assert_eq!(nft.get_agent_owner(&1), buyer);
+    assert_eq!(client.get_request(&request_id).unwrap().owner, buyer);
+
+    // The owner-change guard sees the request's new owner, so completion goes through
+    let level = client.complete_upgrade(&admin, &request_id, &String::from_str(&env, "hash_v1"));
+    assert_eq!(level, 1);
+
+    assert!(client.try_claim_stake(&owner, &request_id).is_err());
+    assert_eq!(client.claim_stake(&buyer, &request_id), 100);
+    assert_eq!(token.balance(&buyer), 100);
+    assert_eq!(token.balance(&owner), 900);
+}
+
+#[test]
+#[should_panic(expected = "Request is not for this agent")]
+fn test_transfer_with_request_rejects_other_agents_request() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    setup_agent_and_stake(&env, &client, &admin, &owner);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    client.transfer_with_request(&2, &owner, &Address::generate(&env), &request_id);
+}
+
+#[test]
+#[should_panic(expected = "Request is not in pending state")]
+fn test_transfer_with_request_rejects_finished_request() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    setup_agent_and_stake(&env, &client, &admin, &owner);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    client.complete_upgrade(&admin, &request_id, &String::from_str(&env, "hash_v1"));
+    client.transfer_with_request(&1, &owner, &Address::generate(&env), &request_id);
+}
+
+#[test]
+fn test_min_evolution_interval_spaces_completions() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let hash = String::from_str(&env, "hash");
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    client.set_min_evolution_interval(&admin, &3_600);
+    let attestor = setup_attestor(&env, &client, &admin, 7);
+    let first = client.request_upgrade(&1, &owner, &100);
+    let second = client.request_upgrade(&1, &owner, &100);
+    assert_eq!(client.complete_upgrade(&admin, &first, &hash), 1);
+
+    env.ledger().with_mut(|li| li.timestamp = 4_599);
+    assert_eq!(
+        client.try_complete_upgrade(&admin, &second, &hash).err(),
+        Some(Ok(ContractError::OnCooldown.into()))
+    );
+    assert!(
+        client
+            .try_apply_attestation(&attestation(&env, &client, &attestor, second, 1, 1))
+            .err()
+            == Some(Ok(ContractError::OnCooldown.into()))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 4_600);
+    assert_eq!(client.complete_upgrade(&admin, &second, &hash), 2);
+}
+
+#[test]
+fn test_min_evolution_interval_is_per_agent() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let hash = String::from_str(&env, "hash");
+
+    client.set_min_evolution_interval(&admin, &3_600);
+    let first = client.request_upgrade(&1, &owner, &100);
+    let other = client.request_upgrade(&2, &owner, &100);
+    client.complete_upgrade(&admin, &first, &hash);
+    assert_eq!(client.complete_upgrade(&admin, &other, &hash), 1);
+}
+
+#[test]
+fn test_agent_cooldowns_are_independent() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.set_cooldown(&admin, &3600);
+
+    client.request_upgrade(&1, &owner, &100);
+    assert_eq!(client.get_agent_cooldown(&1), 3600);
+    assert_eq!(client.get_agent_cooldown(&2), 0);
+
+    // Agent 1's cooldown does not block agent 2
+    env.ledger().with_mut(|li| li.timestamp = 1_600);
+    client.request_upgrade(&2, &owner, &100);
+    assert_eq!(client.get_agent_cooldown(&1), 3000);
+    assert_eq!(client.get_agent_cooldown(&2), 3600);
+    assert!(client.try_request_upgrade(&1, &owner, &100).is_err());
+
+    env.ledger().with_mut(|li| li.timestamp = 4_600);
+    assert_eq!(client.get_agent_cooldown(&1), 0);
+    client.request_upgrade(&1, &owner, &100);
+}
+
+#[test]
+fn test_requests_are_stored_independently() {
+    let env = Env::default();
+    let (client, _admin, owner) = setup_evolution(&env);
+    let other_owner = Address::generate(&env);
+
+    let first = client.request_upgrade(&1, &owner, &100);
+    let second = client.request_upgrade(&2, &other_owner, &250);
+    assert_ne!(first, second);
+
+    let first_request = client.get_request(&first).unwrap();
+    assert_eq!(first_request.agent_id, 1);
+    assert_eq!(first_request.owner, owner);
+    assert_eq!(first_request.stake_amount, 100);
+
+    let second_request = client.get_request(&second).unwrap();
+    assert_eq!(second_request.agent_id, 2);
+    assert_eq!(second_request.owner, other_owner);
+    assert_eq!(second_request.stake_amount, 250);
+
+    // Finishing one request leaves the other untouched
+    client.cancel_request(&owner, &first);
+    assert!(client.get_request(&first).unwrap().status == EvolutionStatus::Failed);
+    assert!(client.get_request(&second).unwrap().status == EvolutionStatus::Pending);
+}
+
+#[test]
+fn test_pending_count_drops_on_completion_and_attestation() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    let first = client.create_request(&1, &owner, &100);
+    let second = client.create_request(&1, &owner, &100);
+    let third = client.create_request(&1, &owner, &100);
+    assert_eq!(client.count_pending_requests(&1), 3);
+    assert_eq!(
+        client.get_pending_requests(&1),
+        Vec::from_array(&env, [first, second, third])
+    );
+
+    client.complete_upgrade(&admin, &first, &String::from_str(&env, "hash_v1"));
+    assert_eq!(client.count_pending_requests(&1), 2);
+
+    let attestor = setup_attestor(&env, &client, &admin, 7);
+    client.apply_attestation(&attestation(&env, &client, &attestor, second, 1, 1));
+    assert_eq!(client.count_pending_requests(&1), 1);
+    assert!(!client.get_pending_request_ids(&1).contains(second));
+    assert_eq!(
+        client.get_pending_requests(&1),
+        Vec::from_array(&env, [third])
+    );
+    assert_eq!(client.count_pending_requests(&2), 0);
+}
+
+#[test]
+#[should_panic(expected = "Too many pending upgrade requests for this agent")]
+fn test_request_upgrade_rejects_beyond_pending_limit() {
+    let env = Env::default();
+    let (client, _admin, owner) = setup_evolution(&env);
+
+    for _ in 0..MAX_PENDING_REQUESTS {
+        client.request_upgrade(&1, &owner, &100);
+    }
+    client.request_upgrade(&1, &owner, &100);
+}
+
+#[test]
+fn test_upgrade_history_keeps_finished_requests() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    let completed = client.request_upgrade(&1, &owner, &100);
+    let cancelled = client.request_upgrade(&1, &owner, &100);
+    let pending = client.request_upgrade(&1, &owner, &100);
+    client.request_upgrade(&2, &owner, &100);
+
+    client.complete_upgrade(&admin, &completed, &String::from_str(&env, "hash_v1"));
+    client.cancel_request(&owner, &cancelled);
+
+    let history = client.get_upgrade_history(&1, &10);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(0).unwrap().request_id, completed);
+    assert!(history.get(0).unwrap().status == EvolutionStatus::Completed);
+    assert!(history.get(1).unwrap().status == EvolutionStatus::Failed);
+    assert!(history.get(2).unwrap().status == EvolutionStatus::Pending);
+
+    // The limit keeps the most recent entries
+    let recent = client.get_upgrade_history(&1, &1);
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent.get(0).unwrap().request_id, pending);
+
+    let page = client.get_upgrade_history_paged(&1, &1, &5);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(0).unwrap().request_id, cancelled);
+    assert_eq!(client.get_upgrade_history_paged(&1, &3, &5).len(), 0);
+    assert_eq!(client.get_upgrade_history(&3, &10).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Limit exceeds maximum allowed (500)")]
+fn test_upgrade_history_rejects_oversized_limit() {
+    let env = Env::default();
+    let (client, _admin, _owner) = setup_evolution(&env);
+    client.get_upgrade_history(&1, &501);
+}
+
+#[test]
+fn test_fail_upgrade_without_slash_returns_stake() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let (_nft, token) = setup_agent_and_stake(&env, &client, &admin, &owner);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    client.fail_upgrade(&admin, &request_id, &false);
+
+    let request = client.get_request(&request_id).unwrap();
+    assert!(request.status == EvolutionStatus::Failed);
+    assert!(request.completed_at.is_some());
+    assert_eq!(client.get_pending_request_ids(&1).len(), 0);
+
+    assert_eq!(client.claim_stake(&owner, &request_id), 100);
+    assert_eq!(token.balance(&owner), 1_000);
+}
+
+#[test]
+fn test_fail_upgrade_with_slash_consumes_stake() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let (_nft, token) = setup_agent_and_stake(&env, &client, &admin, &owner);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    client.fail_upgrade(&admin, &request_id, &true);
+
+    let expected_topics: soroban_sdk::Vec<Val> =
+        (Symbol::new(&env, "EvolutionFailed"),).into_val(&env);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, expected_topics);
+    assert_eq!(
+        <(u64, u64, bool)>::try_from_val(&env, &data).unwrap(),
+        (request_id, 1, true)
+    );
+
+    assert_eq!(token.balance(&admin), 100);
+    assert_eq!(token.balance(&owner), 900);
+    assert!(client.try_claim_stake(&owner, &request_id).is_err());
+}
+
+#[test]
+#[should_panic(expected = "Request is not in pending state")]
+fn test_fail_upgrade_rejects_completed_request() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    client.complete_upgrade(&admin, &request_id, &String::from_str(&env, "hash_v1"));
+    client.fail_upgrade(&admin, &request_id, &true);
+}
+
+#[test]
+fn test_request_duration_and_average() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    let first = client.create_request(&1, &owner, &100);
+    assert_eq!(client.get_request_duration(&first), None);
+    env.ledger().with_mut(|li| li.timestamp = 1_100);
+    client.complete_upgrade(&admin, &first, &String::from_str(&env, "hash_v1"));
+    assert_eq!(client.get_request_duration(&first), Some(100));
+
+    let second = client.create_request(&1, &owner, &100);
+    env.ledger().with_mut(|li| li.timestamp = 1_400);
+    client.complete_upgrade(&admin, &second, &String::from_str(&env, "hash_v2"));
+    assert_eq!(client.get_avg_evolution_duration(&1), 200);
+
+    // Failed requests report a duration but do not move the completion average
+    let failed = client.create_request(&1, &owner, &100);
+    env.ledger().with_mut(|li| li.timestamp = 2_400);
+    client.fail_upgrade(&admin, &failed, &false);
+    assert_eq!(client.get_request_duration(&failed), Some(1_000));
+    assert_eq!(client.get_avg_evolution_duration(&1), 200);
+
+    assert_eq!(client.get_avg_evolution_duration(&2), 0);
+    assert_eq!(client.get_request_duration(&99), None);
+}
+
+#[test]
+fn test_apply_attestation_rejects_tampered_signature() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let attestor = setup_attestor(&env, &client, &admin, 7);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    let mut signed = attestation(&env, &client, &attestor, request_id, 1, 1);
+    signed.new_model_hash = String::from_str(&env, "swapped_hash");
+    assert!(client.try_apply_attestation(&signed).is_err());
+
+    // Signing as another provider fails against that provider's registered key
+    let (other_provider, _) = setup_attestor(&env, &client, &admin, 8);
+    let mut wrong_provider = attestation(&env, &client, &attestor, request_id, 1, 1);
+    wrong_provider.oracle_provider = other_provider;
+    sign_attestation(&env, &client, &attestor.1, &mut wrong_provider);
+    assert!(client.try_apply_attestation(&wrong_provider).is_err());
+
+    assert!(client.get_request(&request_id).unwrap().status == EvolutionStatus::Pending);
+    client.apply_attestation(&attestation(&env, &client, &attestor, request_id, 1, 1));
+    assert_eq!(client.get_evolution_level(&1), 1);
+}
+
+#[test]
+#[should_panic]
+fn test_apply_attestation_traps_on_invalid_signature() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let attestor = setup_attestor(&env, &client, &admin, 7);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    let mut signed = attestation(&env, &client, &attestor, request_id, 1, 1);
+    signed.nonce += 1;
+    client.apply_attestation(&signed);
+}
+
+#[test]
+#[should_panic(expected = "Oracle key not registered")]
+fn test_apply_attestation_requires_registered_key() {
+    let env = Env::default();
+    let (client, _admin, owner) = setup_evolution(&env);
+    let attestor = (Address::generate(&env), SigningKey::from_bytes(&[9u8; 32]));
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    client.apply_attestation(&attestation(&env, &client, &attestor, request_id, 1, 1));
+}
+
+#[test]
+fn test_attempt_count_includes_unsuccessful_requests() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    assert_eq!(client.get_attempt_count(&1), 0);
+
+    let cancelled = client.request_upgrade(&1, &owner, &100);
+    let failed = client.request_upgrade(&1, &owner, &100);
+    let completed = client.request_upgrade(&1, &owner, &100);
+    client.request_upgrade(&2, &owner, &100);
+
+    client.cancel_request(&owner, &cancelled);
+    client.fail_upgrade(&admin, &failed, &false);
+    assert_eq!(client.get_attempt_count(&1), 3);
+    assert_eq!(client.get_evolution_level(&1), 0);
+
+    client.complete_upgrade(&admin, &completed, &String::from_str(&env, "hash_v1"));
+    assert_eq!(client.get_attempt_count(&1), 3);
+    assert_eq!(client.get_evolution_level(&1), 1);
+    assert_eq!(client.get_attempt_count(&2), 1);
+}
+
+#[test]
+fn test_tier_params_follow_evolution_level() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    client.set_min_stake(&admin, &50);
+    client.set_cooldown(&admin, &60);
+
+    // Tiers without params fall back to the global settings
+    assert_eq!(client.get_tier_params(&1), (50, 60));
+    client.set_tier_params(&admin, &1, &500, &0);
+    assert_eq!(client.get_tier_params(&1), (500, 0));
+
+    // Level 0 is tier 0: global params apply
+    client.set_cooldown_exempt(&admin, &1, &true);
+    for i in 0..LEVELS_PER_TIER {
+        let request_id = client.request_upgrade(&1, &owner, &50);
+        let hash = String::from_str(&env, "hash");
+        assert_eq!(client.complete_upgrade(&admin, &request_id, &hash), i + 1);
+    }
+    client.set_cooldown_exempt(&admin, &1, &false);
+
+    // Level 10 is tier 1: the larger stake is required and the cooldown is lifted
+    assert_eq!(
+        client.can_request_upgrade(&1, &owner, &499),
+        (false, Symbol::new(&env, "stake_too_low"))
+    );
+    client.request_upgrade(&1, &owner, &500);
+    client.request_upgrade(&1, &owner, &500);
+
+    // A tier 0 agent is unaffected
+    client.request_upgrade(&2, &owner, &50);
+    assert!(client.try_request_upgrade(&2, &owner, &50).is_err());
+}
+
+#[test]
+fn test_token_min_stake_respects_decimals() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let seven_decimals = Address::generate(&env);
+    let two_decimals = Address::generate(&env);
+    client.set_min_stake(&admin, &50);
+
+    client.set_token_min_stake(&admin, &seven_decimals, &1, &7);
+    client.set_token_min_stake(&admin, &two_decimals, &1, &2);
+    assert_eq!(client.get_token_min_stake(&two_decimals), Some((1, 2)));
+
+    client.set_stake_token(&admin, &seven_decimals);
+    assert_eq!(
+        client.can_request_upgrade(&1, &owner, &9_999_999),
+        (false, Symbol::new(&env, "stake_too_low"))
+    );
+    assert!(client.can_request_upgrade(&1, &owner, &10_000_000).0);
+
+    // One whole two-decimal token is 100 base units, above the global minimum of 50
+    client.set_stake_token(&admin, &two_decimals);
+    assert_eq!(
+        client.can_request_upgrade(&1, &owner, &99),
+        (false, Symbol::new(&env, "stake_too_low"))
+    );
+    assert!(client.can_request_upgrade(&1, &owner, &100).0);
+
+    // A token minimum never lowers the tier/global minimum
+    client.set_min_stake(&admin, &150);
+    assert_eq!(
+        client.can_request_upgrade(&1, &owner, &100),
+        (false, Symbol::new(&env, "stake_too_low"))
+    );
+    assert!(client.can_request_upgrade(&1, &owner, &150).0);
+
+    // Tokens without their own minimum keep the global one
+    client.set_stake_token(&admin, &Address::generate(&env));
+    assert!(client.can_request_upgrade(&1, &owner, &150).0);
+}
+
+#[test]
+#[should_panic(expected = "Minimum stake overflows token precision")]
+fn test_token_min_stake_rejects_overflowing_decimals() {
+    let env = Env::default();
+    let (client, admin, _owner) = setup_evolution(&env);
+
+    client.set_token_min_stake(&admin, &Address::generate(&env), &1, &39);
+}
+
+#[test]
+fn test_stake_refunded_in_the_token_it_was_escrowed_in() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let (_nft, old_token) = setup_agent_and_stake(&env, &client, &admin, &owner);
+
+    let cancelled = client.request_upgrade(&1, &owner, &100);
+    let failed = client.request_upgrade(&1, &owner, &100);
+    assert_eq!(
+        client.get_request_stake_token(&cancelled),
+        Some(old_token.address.clone())
+    );
+
+    // A later request's stake sits in the new token
+    let new_token = env.register_stellar_asset_contract_v2(admin.clone());
+    StellarAssetClient::new(&env, &new_token.address()).mint(&owner, &100);
+    client.set_stake_token(&admin, &new_token.address());
+    let new_token = TokenClient::new(&env, &new_token.address());
+    client.request_upgrade(&1, &owner, &100);
+
+    client.cancel_request(&owner, &cancelled);
+    client.fail_upgrade(&admin, &failed, &false);
+    client.claim_stake(&owner, &failed);
+
+    assert_eq!(old_token.balance(&owner), 1_000);
+    assert_eq!(new_token.balance(&owner), 0);
+    assert_eq!(new_token.balance(&client.address), 100);
+}
+
+#[test]
+fn test_rejected_request_moves_no_tokens() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let (_nft, token) = setup_agent_and_stake(&env, &client, &admin, &owner);
+    client.set_cooldown(&admin, &3600);
+
+    client.request_upgrade(&1, &owner, &100);
+    assert_eq!(token.balance(&owner), 900);
+
+    // Blocked by the cooldown: the stake stays with the owner
+    assert!(client.try_request_upgrade(&1, &owner, &100).is_err());
+    assert_eq!(token.balance(&owner), 900);
+    assert_eq!(token.balance(&client.address), 100);
+    assert_eq!(client.get_pending_request_ids(&1).len(), 1);
+}
+
+#[test]
+fn test_cancel_request_refunds_stake_once() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let (_nft, token) = setup_agent_and_stake(&env, &client, &admin, &owner);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    assert_eq!(token.balance(&owner), 900);
+
+    client.cancel_request(&owner, &request_id);
+    assert_eq!(token.balance(&owner), 1_000);
+    assert_eq!(token.balance(&client.address), 0);
+    assert_eq!(client.get_pending_request_ids(&1).len(), 0);
+
+    // The refund consumed the stake lock
+    assert!(client.try_claim_stake(&owner, &request_id).is_err());
+    assert!(client.try_cancel_request(&owner, &request_id).is_err());
+    assert_eq!(token.balance(&owner), 1_000);
+}
+
+#[test]
+fn test_attestation_quorum_completes_after_distinct_providers() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let first = setup_attestor(&env, &client, &admin, 7);
+    let second = setup_attestor(&env, &client, &admin, 8);
+    client.set_attestation_quorum(&admin, &2);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    assert_eq!(client.get_attestation_progress(&request_id), (0, 2));
+
+    client.apply_attestation(&attestation(&env, &client, &first, request_id, 1, 1));
+    assert_eq!(client.get_attestation_progress(&request_id), (1, 2));
+    assert_eq!(client.get_evolution_level(&1), 0);
+    assert!(client.get_request(&request_id).unwrap().status == EvolutionStatus::Pending);
+
+    // The same provider cannot count twice
+    assert!(client
+        .try_apply_attestation(&attestation(&env, &client, &first, request_id, 1, 2))
+        .is_err());
+
+    client.apply_attestation(&attestation(&env, &client, &second, request_id, 1, 2));
+    assert_eq!(client.get_attestation_progress(&request_id), (2, 2));
+    assert_eq!(client.get_evolution_level(&1), 1);
+    assert!(client.get_request(&request_id).unwrap().status == EvolutionStatus::Completed);
+}
+
+#[test]
+#[should_panic(expected = "Attestation model hash mismatch")]
+fn test_attestation_quorum_rejects_conflicting_model_hash() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let first = setup_attestor(&env, &client, &admin, 7);
+    let second = setup_attestor(&env, &client, &admin, 8);
+    client.set_attestation_quorum(&admin, &2);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    client.apply_attestation(&attestation(&env, &client, &first, request_id, 1, 1));
+
+    let mut conflicting = attestation(&env, &client, &second, request_id, 1, 2);
+    conflicting.new_model_hash = String::from_str(&env, "other_hash");
+    sign_attestation(&env, &client, &second.1, &mut conflicting);
+    client.apply_attestation(&conflicting);
+}
+
+#[test]
+#[should_panic(expected = "Attestation quorum out of bounds")]
+fn test_attestation_quorum_rejects_zero() {
+    let env = Env::default();
+    let (client, admin, _owner) = setup_evolution(&env);
+    client.set_attestation_quorum(&admin, &0);
+}
+
+#[test]
+fn test_request_upgrade_enforces_oracle_evolution_cost() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let oracle = OracleClient::new(&env, &env.register_contract(None, Oracle));
+    let provider = Address::generate(&env);
+    oracle.init_contract(&admin);
+    oracle.register_provider(&admin, &provider);
+
+    client.set_min_stake(&admin, &50);
+    client.set_oracle_contract(&admin, &oracle.address);
+    assert_eq!(client.get_oracle_contract(), Some(oracle.address.clone()));
+
+    // No cost reported yet: the minimum stake applies
+    client.request_upgrade(&1, &owner, &50);
+
+    oracle.submit_data(&provider, &Symbol::new(&env, "evolution_cost"), &300);
+    assert_eq!(
+        client.can_request_upgrade(&2, &owner, &299),
+        (false, Symbol::new(&env, "stake_too_low"))
+    );
+    client.request_upgrade(&2, &owner, &300);
+
+    // A cost below the minimum stake does not lower it
+    oracle.submit_data(&provider, &Symbol::new(&env, "evolution_cost"), &10);
+    assert_eq!(
+        client.can_request_upgrade(&3, &owner, &49),
+        (false, Symbol::new(&env, "stake_too_low"))
+    );
+    client.request_upgrade(&3, &owner, &50);
+}
+
+#[test]
+fn test_deprecated_or_missing_cost_feed_falls_back_to_min_stake() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    let oracle = OracleClient::new(&env, &env.register_contract(None, Oracle));
+    let provider = Address::generate(&env);
+    let cost_key = Symbol::new(&env, "evolution_cost");
+    oracle.init_contract(&admin);
+    oracle.register_provider(&admin, &provider);
+    oracle.submit_data(&provider, &cost_key, &300);
+
+    client.set_min_stake(&admin, &50);
+    client.set_oracle_contract(&admin, &oracle.address);
+    assert!(!client.can_request_upgrade(&1, &owner, &50).0);
+
+    oracle.deprecate_key(&admin, &cost_key);
+    assert!(client.can_request_upgrade(&1, &owner, &50).0);
+    client.request_upgrade(&1, &owner, &50);
+
+    // An address that is not an oracle at all behaves the same
+    client.set_oracle_contract(&admin, &Address::generate(&env));
+    assert!(client.can_request_upgrade(&2, &owner, &50).0);
+    client.request_upgrade(&2, &owner, &50);
+}
+
+#[test]
+fn test_new_agent_starts_at_level_zero_with_empty_history() {
+    let env = Env::default();
+    let (client, _admin, _owner) = setup_evolution(&env);
+
+    assert_eq!(client.get_evolution_level(&1), 0);
+    assert_eq!(client.get_agent_cooldown(&1), 0);
+    assert_eq!(client.get_upgrade_history(&1, &10).len(), 0);
+}
+
+#[test]
+fn test_model_hash_at_maximum_length_accepted() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    let hash = String::from_bytes(&env, &[b'a'; MAX_STRING_LENGTH as usize]);
+    assert_eq!(client.complete_upgrade(&admin, &request_id, &hash), 1);
+}
+
+#[test]
+#[should_panic(expected = "Model hash exceeds maximum length")]
+fn test_oversized_model_hash_rejected() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    let hash = String::from_bytes(&env, &[b'a'; MAX_STRING_LENGTH as usize + 1]);
+    client.complete_upgrade(&admin, &request_id, &hash);
+}
+
+#[test]
+#[should_panic(expected = "Stake is not claimable")]
+fn test_claim_stake_rejects_pending_request() {
+    let env = Env::default();
+    let (client, admin, owner) = setup_evolution(&env);
+    setup_agent_and_stake(&env, &client, &admin, &owner);
+
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    client.claim_stake(&owner, &request_id);
+}
+
+/// Agent 1 minted through `TestAgents`, a registered attestor and one pending request
+fn setup_attested_request<'a>(
+    env: &'a Env,
+) -> (
+    EvolutionClient<'a>,
+    Address,
+    Address,
+    (Address, SigningKey),
+    u64,
+) {
+    let (client, admin, owner) = setup_evolution(env);
+    setup_agent_and_stake(env, &client, &admin, &owner);
+    let attestor = setup_attestor(env, &client, &admin, 7);
+    let request_id = client.request_upgrade(&1, &owner, &100);
+    (client, admin, owner, attestor, request_id)
+}
+
+#[test]
+fn test_replay_protection_rejects_reused_nonce() {
+    let env = Env::default();
+    let (client, _admin, owner, attestor, first) = setup_attested_request(&env);
+    let second = client.request_upgrade(&1, &owner, &100);
+
+    client.apply_attestation(&attestation(&env, &client, &attestor, first, 1, 1));
+    assert_eq!(client.get_evolution_level(&1), 1);
+
+    // The same nonce cannot be used again, even for another pending request
+    assert!(client
+        .try_apply_attestation(&attestation(&env, &client, &attestor, second, 1, 1))
+        .is_err());
+    assert_eq!(client.get_evolution_level(&1), 1);
+    assert!(client.get_request(&second).unwrap().status == EvolutionStatus::Pending);
+}
+
+#[test]
+fn test_replay_protection_allows_higher_nonce() {
+    let env = Env::default();
+    let (client, _admin, owner, attestor, first) = setup_attested_request(&env);
+    let second = client.request_upgrade(&1, &owner, &100);
+
+    client.apply_attestation(&attestation(&env, &client, &attestor, first, 1, 1));
+    client.apply_attestation(&attestation(&env, &client, &attestor, second, 1, 2));
+    assert_eq!(client.get_evolution_level(&1), 2);
+}
+
+#[test]
+#[should_panic(expected = "Upgrade request not found")]
+fn test_attestation_for_unknown_request_rejected() {
+    let env = Env::default();
+    let (client, _admin, _owner, attestor, _request_id) = setup_attested_request(&env);
+
+    client.apply_attestation(&attestation(&env, &client, &attestor, 999, 1, 1));
+}
+
+#[test]
+#[should_panic(expected = "Agent ID mismatch in attestation")]
+fn test_attestation_agent_mismatch_rejected() {
+    let env = Env::default();
+    let (client, admin, owner, attestor, request_id) = setup_attested_request(&env);
+    let nft_id = env.register_contract(None, AgentNFT);
+    AgentNFTClient::new(&env, &nft_id).init_contract(&admin);
+    TestAgents::mint(&env, &nft_id, &admin, 2, &owner);
+
+    client.apply_attestation(&attestation(&env, &client, &attestor, request_id, 2, 1));
+}
+
+#[test]
+#[should_panic(expected = "Request is not in pending state")]
+fn test_attestation_for_completed_request_rejected() {
+    let env = Env::default();
+    let (client, admin, _owner, attestor, request_id) = setup_attested_request(&env);
+    client.complete_upgrade(&admin, &request_id, &String::from_str(&env, "hash_v1"));
+
+    client.apply_attestation(&attestation(&env, &client, &attestor, request_id, 1, 1));
+}
+
+#[test]
+#[should_panic(expected = "Attestation data exceeds maximum size")]
+fn test_attestation_oversized_data_rejected() {
+    let env = Env::default();
+    let (client, _admin, _owner, attestor, request_id) = setup_attested_request(&env);
+
+    let mut oversized = attestation(&env, &client, &attestor, request_id, 1, 1);
+    oversized.attestation_data = Bytes::from_slice(&env, &[0u8; MAX_ATTESTATION_DATA_SIZE + 1]);
+    client.apply_attestation(&oversized);
+}
+
+#[test]
+#[should_panic(expected = "Invalid signature size")]
+fn test_attestation_bad_signature_size_rejected() {
+    let env = Env::default();
+    let (client, _admin, _owner, attestor, request_id) = setup_attested_request(&env);
+
+    let mut short = attestation(&env, &client, &attestor, request_id, 1, 1);
+    short.signature = Bytes::from_slice(&env, &[0u8; 32]);
+    client.apply_attestation(&short);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized: caller is not admin")]
+fn test_complete_upgrade_rejects_non_admin() {
+    let env = Env::default();
+    let (client, _admin, _owner, _attestor, request_id) = setup_attested_request(&env);
+
+    client.complete_upgrade(
+        &Address::generate(&env),
+        &request_id,
+        &String::from_str(&env, "hash_v1"),
+    );
+}
+
+/// Mint -> list -> buy -> hand over -> stake -> evolve, across the AgentNFT,
+/// Marketplace, Oracle and Evolution contracts
+#[test]
+fn test_full_flow_mint_list_buy_stake_evolve() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_evolution(&env);
+    let buyer = Address::generate(&env);
+
+    let nft_id = env.register_contract(None, AgentNFT);
+    let nft = AgentNFTClient::new(&env, &nft_id);
+    let nft_admin = Address::generate(&env);
+    nft.init_contract(&nft_admin);
+    client.set_agent_nft(&admin, &nft_id);
+
+    let token = env.register_stellar_asset_contract_v2(admin.clone());
+    StellarAssetClient::new(&env, &token.address()).mint(&buyer, &10_000);
+    let token = TokenClient::new(&env, &token.address());
+    client.set_stake_token(&admin, &token.address);
+
+    let market = MarketplaceClient::new(&env, &env.register_contract(None, Marketplace));
+    market.init_contract(&admin);
+    market.set_payment_token(&admin, &token.address);
+
+    let oracle = OracleClient::new(&env, &env.register_contract(None, Oracle));
+    let provider = Address::generate(&env);
+    oracle.init_contract(&admin);
+    oracle.register_provider(&admin, &provider);
+    oracle.submit_data(&provider, &Symbol::new(&env, "evolution_cost"), &500);
+    client.set_oracle_contract(&admin, &oracle.address);
+
+    // Mint
+    TestAgents::mint(&env, &nft_id, &nft_admin, 1, &seller);
+    assert_eq!(nft.get_agent(&1).evolution_level, 0);
+
+    // List, with the marketplace approved to deliver the agent
+    let listing_id = market.create_listing(&1, &seller, &0, &1_000);
+    nft.approve(&1, &seller, &market.address);
+
+    // Buy, then hand the agent over
+    market.buy_agent(&listing_id, &buyer, &1_000);
+    assert!(!market.get_listing(&listing_id).unwrap().active);
+    assert_eq!(market.get_proceeds_balance(&seller), 1_000);
+    nft.transfer_agent(&1, &market.address, &seller, &buyer);
+    assert_eq!(nft.get_agent_owner(&1), buyer);
+
+    // The seller can no longer stake on the agent, and the oracle cost applies
+    assert_eq!(
+        client.can_request_upgrade(&1, &seller, &500),
+        (false, Symbol::new(&env, "not_owner"))
+    );
+    assert_eq!(
+        client.can_request_upgrade(&1, &buyer, &499),
+        (false, Symbol::new(&env, "stake_too_low"))
+    );
+
+    // Stake and evolve
+    let request_id = client.request_upgrade(&1, &buyer, &500);
+    assert!(client.get_request(&request_id).unwrap().status == EvolutionStatus::Pending);
+    assert_eq!(token.balance(&buyer), 10_000 - 1_000 - 500);
+
+    let level = client.complete_upgrade(&admin, &request_id, &String::from_str(&env, "hash_v1"));
+    assert_eq!(level, 1);
+    let request = client.get_request(&request_id).unwrap();
+    assert!(request.status == EvolutionStatus::Completed);
+    assert!(request.completed_at.is_some());
+
+    assert_eq!(client.claim_stake(&buyer, &request_id), 500);
+    assert_eq!(token.balance(&buyer), 10_000 - 1_000);
+}
+
+#[test]
+fn test_parallel_listings_deliver_only_sold_agents() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_evolution(&env);
+    let buyer = Address::generate(&env);
+
+    let nft_id = env.register_contract(None, AgentNFT);
+    let nft = AgentNFTClient::new(&env, &nft_id);
+    let nft_admin = Address::generate(&env);
+    nft.init_contract(&nft_admin);
+    client.set_agent_nft(&admin, &nft_id);
+
+    let token = env.register_stellar_asset_contract_v2(admin.clone());
+    StellarAssetClient::new(&env, &token.address()).mint(&buyer, &10_000);
+    let market = MarketplaceClient::new(&env, &env.register_contract(None, Marketplace));
+    market.init_contract(&admin);
+    market.set_payment_token(&admin, &token.address());
+
+    let mut listings = soroban_sdk::Vec::<u64>::new(&env);
+    for agent_id in 1..=3u64 {
+        TestAgents::mint(&env, &nft_id, &nft_admin, agent_id, &seller);
+        listings.push_back(market.create_listing(&agent_id, &seller, &0, &1_000));
+    }
+    assert_eq!(listings, soroban_sdk::vec![&env, 1u64, 2, 3]);
+    nft.set_approval_for_all(&seller, &market.address, &true);
+
+    for agent_id in 1..=2u64 {
+        market.buy_agent(&listings.get_unchecked(agent_id as u32 - 1), &buyer, &1_000);
+        nft.transfer_agent(&agent_id, &market.address, &seller, &buyer);
+    }
+
+    assert!(market.get_listing(&3).unwrap().active);
+    assert_eq!(
+        nft.get_agents_by_owner(&buyer),
+        soroban_sdk::vec![&env, 1u64, 2]
+    );
+    assert_eq!(nft.get_agent_owner(&3), seller);
+
+    // Only the new owner may evolve a delivered agent
+    assert!(client.can_request_upgrade(&1, &buyer, &100).0);
+    assert!(!client.can_request_upgrade(&3, &buyer, &100).0);
+}
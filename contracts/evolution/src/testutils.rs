@@ -0,0 +1,55 @@
+#![cfg(any(test, feature = "testutils"))]
+
+use soroban_sdk::{Address, Env, String};
+use stellai_lib::interfaces::AgentNftClient;
+
+use crate::EvolutionClient;
+
+/// Sets up evolution scenarios through the public AgentNFT and Evolution entry points
+/// only, so tests exercise the contracts' real storage layout
+pub struct TestAgents;
+
+impl TestAgents {
+    /// Mint `agent_id` on `agent_nft` to `owner`, authorized by `minter` (admin or
+    /// approved minter)
+    pub fn mint(env: &Env, agent_nft: &Address, minter: &Address, agent_id: u64, owner: &Address) {
+        AgentNftClient::new(env, agent_nft).mint_agent(
+            &(agent_id as u128),
+            minter,
+            owner,
+            &String::from_str(env, "ipfs://test-agent"),
+            &0,
+        );
+    }
+
+    /// Mint `agent_id` to `owner` and open an upgrade request for it; returns the
+    /// request ID
+    pub fn mint_and_request(
+        env: &Env,
+        agent_nft: &Address,
+        evolution: &Address,
+        minter: &Address,
+        agent_id: u64,
+        owner: &Address,
+        stake_amount: i128,
+    ) -> u64 {
+        Self::mint(env, agent_nft, minter, agent_id, owner);
+        EvolutionClient::new(env, evolution).request_upgrade(&agent_id, owner, &stake_amount)
+    }
+
+    /// Request and complete one upgrade of an existing agent in a single step;
+    /// returns the agent's new evolution level
+    pub fn evolve_in_place(
+        env: &Env,
+        evolution: &Address,
+        admin: &Address,
+        agent_id: u64,
+        owner: &Address,
+        stake_amount: i128,
+        new_model_hash: &str,
+    ) -> u32 {
+        let client = EvolutionClient::new(env, evolution);
+        let request_id = client.request_upgrade(&agent_id, owner, &stake_amount);
+        client.complete_upgrade(admin, &request_id, &String::from_str(env, new_model_hash))
+    }
+}
@@ -1,11 +1,18 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, Address, Bytes, Env, String, Symbol, Vec,
+    contract, contractimpl, contracttype, panic_with_error, symbol_short, Address, Bytes, Env,
+    String, Symbol, Vec,
 };
 use stellai_lib::{
-    ADMIN_KEY, DEFAULT_RATE_LIMIT_OPERATIONS, DEFAULT_RATE_LIMIT_WINDOW_SECONDS, EXEC_CTR_KEY,
-    MAX_DATA_SIZE, MAX_HISTORY_QUERY_LIMIT, MAX_HISTORY_SIZE, MAX_STRING_LENGTH,
+    encoding::{encode_for_hash, EncodablePart},
+    errors::ContractError,
+    interfaces::{AgentNftClient, OracleDataClient},
+    ActionRecord, ActionRecordPage, RateLimit, ADMIN_KEY, DEFAULT_RATE_LIMIT_OPERATIONS,
+    DEFAULT_RATE_LIMIT_WINDOW_SECONDS, EXEC_CTR_KEY, MAX_AGE_SECONDS, MAX_BATCH_SIZE,
+    MAX_DATA_SIZE, MAX_EXECUTORS_PER_AGENT, MAX_HISTORY_QUERY_LIMIT, MAX_HISTORY_SIZE,
+    MAX_IDEMPOTENCY_WINDOW_SECONDS, MAX_RATE_LIMIT_OPERATIONS, MAX_RETRY_DELAY_SECONDS,
+    MAX_RULES_PER_AGENT, MAX_STRING_LENGTH,
 };
 
 // Data structures
@@ -18,19 +25,89 @@ pub struct RuleKey {
 
 #[derive(Clone)]
 #[contracttype]
-pub struct ActionRecord {
+pub struct RateLimitData {
+    pub last_reset: u64,
+    pub count: u32,
+}
+
+/// Record of a completed execution, including the hash of its inputs
+/// (see `compute_execution_hash`)
+#[derive(Clone)]
+#[contracttype]
+pub struct ExecutionReceipt {
     pub execution_id: u64,
+    pub agent_id: u64,
     pub action: String,
     pub executor: Address,
+    pub nonce: u64,
     pub timestamp: u64,
+    pub execution_hash: Bytes,
+    pub status: ExecutionStatus,
+    pub retry_after: Option<u64>, // set when a failed execution may be retried
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[contracttype]
+#[repr(u32)]
+pub enum ExecutionStatus {
+    Pending = 0,
+    Succeeded = 1,
+    Failed = 2,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct LastExecution {
     pub nonce: u64,
+    pub execution_id: u64,
+    pub executor: Address,
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[contracttype]
+#[repr(u32)]
+pub enum ReplayMode {
+    Nonce = 0,
+    LedgerSequence = 1,
+}
+
+/// How an oracle value is compared against the threshold in `execute_action_if`
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[contracttype]
+#[repr(u32)]
+pub enum Comparator {
+    GreaterThan = 0,
+    GreaterOrEqual = 1,
+    LessThan = 2,
+    LessOrEqual = 3,
+    Equal = 4,
+}
+
+impl Comparator {
+    fn holds(self, value: i128, threshold: i128) -> bool {
+        match self {
+            Comparator::GreaterThan => value > threshold,
+            Comparator::GreaterOrEqual => value >= threshold,
+            Comparator::LessThan => value < threshold,
+            Comparator::LessOrEqual => value <= threshold,
+            Comparator::Equal => value == threshold,
+        }
+    }
 }
 
+/// Snapshot of every admin-set parameter (see `get_config`)
 #[derive(Clone)]
 #[contracttype]
-pub struct RateLimitData {
-    pub last_reset: u64,
-    pub count: u32,
+pub struct ContractConfig {
+    pub admin: Address,
+    pub paused: bool,
+    pub strict_mode: bool,
+    pub history_cap: u32,
+    pub idempotency_window: u64,
+    pub oracle: Option<Address>,
+    pub agent_nft: Option<Address>,
+    pub batch_summary_only: bool,
 }
 
 #[contract]
@@ -47,8 +124,17 @@ impl ExecutionHub {
         admin.require_auth();
         env.storage().instance().set(&ADMIN_KEY, &admin);
         env.storage().instance().set(&EXEC_CTR_KEY, &0u64);
+        env.storage()
+            .instance()
+            .set(&symbol_short!("hist_cap"), &MAX_HISTORY_SIZE);
+
+        env.events()
+            .publish((symbol_short!("init"), admin.clone()), admin);
+    }
 
-        env.events().publish((symbol_short!("init"),), admin);
+    /// Whether the contract has been initialized with an admin
+    pub fn is_initialized(env: Env) -> bool {
+        env.storage().instance().has(&ADMIN_KEY)
     }
 
     // Get current execution counter
@@ -72,6 +158,7 @@ impl ExecutionHub {
         rule_name: String,
         rule_data: Bytes,
     ) {
+        Self::require_not_paused(&env);
         owner.require_auth();
 
         Self::validate_agent_id(agent_id);
@@ -84,15 +171,27 @@ impl ExecutionHub {
         };
         let timestamp = env.ledger().timestamp();
 
+        if !env.storage().instance().has(&rule_key) {
+            let mut names = Self::read_rule_index(&env, agent_id);
+            if names.len() >= MAX_RULES_PER_AGENT {
+                panic!("Maximum number of rules per agent reached");
+            }
+            names.push_back(rule_name.clone());
+            env.storage()
+                .instance()
+                .set(&(symbol_short!("rules"), agent_id), &names);
+        }
+
         env.storage().instance().set(&rule_key, &rule_data);
         env.events().publish(
-            (symbol_short!("rule_reg"),),
+            (symbol_short!("rule_reg"), agent_id),
             (agent_id, rule_name, owner, timestamp),
         );
     }
 
     // Revoke existing rule
     pub fn revoke_rule(env: Env, agent_id: u64, owner: Address, rule_name: String) {
+        Self::require_not_paused(&env);
         owner.require_auth();
         Self::validate_agent_id(agent_id);
 
@@ -100,10 +199,53 @@ impl ExecutionHub {
             agent_id,
             rule_name: rule_name.clone(),
         };
+        if !env.storage().instance().has(&rule_key) {
+            return;
+        }
         env.storage().instance().remove(&rule_key);
 
-        env.events()
-            .publish((symbol_short!("rule_rev"),), (agent_id, rule_name, owner));
+        let mut names = Self::read_rule_index(&env, agent_id);
+        if let Some(index) = names.first_index_of(&rule_name) {
+            names.remove(index);
+        }
+        let index_key = (symbol_short!("rules"), agent_id);
+        if names.is_empty() {
+            env.storage().instance().remove(&index_key);
+        } else {
+            env.storage().instance().set(&index_key, &names);
+        }
+
+        env.events().publish(
+            (symbol_short!("rule_rev"), agent_id),
+            (agent_id, rule_name, owner),
+        );
+    }
+
+    // List rule names registered for an agent, in registration order
+    pub fn list_rules(env: Env, agent_id: u64, offset: u32, limit: u32) -> Vec<String> {
+        Self::validate_agent_id(agent_id);
+        if limit > MAX_HISTORY_QUERY_LIMIT {
+            panic!("Limit exceeds maximum allowed (500)");
+        }
+
+        let names = Self::read_rule_index(&env, agent_id);
+        let start = offset.min(names.len());
+        let end = offset.saturating_add(limit).min(names.len());
+        names.slice(start..end)
+    }
+
+    // Get the number of rules registered for an agent
+    pub fn rule_count(env: Env, agent_id: u64) -> u32 {
+        Self::validate_agent_id(agent_id);
+        Self::read_rule_index(&env, agent_id).len()
+    }
+
+    // Helper: rule names registered for an agent
+    fn read_rule_index(env: &Env, agent_id: u64) -> Vec<String> {
+        env.storage()
+            .instance()
+            .get(&(symbol_short!("rules"), agent_id))
+            .unwrap_or_else(|| Vec::new(env))
     }
 
     // Get rule data
@@ -126,375 +268,2657 @@ impl ExecutionHub {
         nonce: u64,
     ) -> u64 {
         executor.require_auth();
+        Self::execute_action_impl(&env, agent_id, executor, action, parameters, nonce, true)
+            .unwrap_or_else(|| panic!("Rate limit exceeded"))
+    }
 
-        Self::validate_agent_id(agent_id);
-        Self::validate_string_length(&action, "Action name");
-        Self::validate_data_size(&parameters, "Parameters");
+    // Execute action, returning None instead of failing when the agent is rate limited.
+    // A failed invocation discards its events, so this is the variant that lets the
+    // `rate_limited` event reach monitoring.
+    pub fn execute_action_checked(
+        env: Env,
+        agent_id: u64,
+        executor: Address,
+        action: String,
+        parameters: Bytes,
+        nonce: u64,
+    ) -> Option<u64> {
+        executor.require_auth();
+        Self::execute_action_impl(&env, agent_id, executor, action, parameters, nonce, true)
+    }
 
-        // Replay protection
-        let stored_nonce = Self::get_action_nonce(&env, agent_id);
-        if nonce <= stored_nonce {
-            panic!("Invalid nonce: replay protection triggered");
+    // Execute action only if the oracle value for `oracle_key` satisfies the condition.
+    // Returns None (and executes nothing) when the condition does not hold; stale or
+    // missing oracle data is rejected.
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_action_if(
+        env: Env,
+        agent_id: u64,
+        executor: Address,
+        action: String,
+        parameters: Bytes,
+        nonce: u64,
+        oracle_key: Symbol,
+        comparator: Comparator,
+        threshold: i128,
+        max_age_seconds: u64,
+    ) -> Option<u64> {
+        Self::require_not_paused(&env);
+        if max_age_seconds > MAX_AGE_SECONDS {
+            panic!("Max age exceeds maximum allowed");
         }
 
-        // Rate limiting
-        Self::check_rate_limit(
-            &env,
-            agent_id,
-            DEFAULT_RATE_LIMIT_OPERATIONS,
-            DEFAULT_RATE_LIMIT_WINDOW_SECONDS,
-        );
+        let oracle = Self::get_oracle(env.clone()).expect("Oracle not set");
+        let data = OracleDataClient::new(&env, &oracle)
+            .get_data(&oracle_key)
+            .expect("No oracle data for key");
+        let age = env.ledger().timestamp().saturating_sub(data.timestamp);
+        if age > max_age_seconds {
+            panic!("Oracle data is stale");
+        }
 
-        let execution_id = Self::next_execution_id(&env);
-        Self::set_action_nonce(&env, agent_id, nonce);
-        Self::record_action_in_history(&env, agent_id, execution_id, &action, &executor, nonce);
+        if !comparator.holds(data.value, threshold) {
+            return None;
+        }
 
-        let timestamp = env.ledger().timestamp();
-        env.events().publish(
-            (symbol_short!("act_exec"),),
-            (execution_id, agent_id, action, executor, timestamp, nonce),
-        );
+        executor.require_auth();
+        let execution_id =
+            Self::execute_action_impl(&env, agent_id, executor, action, parameters, nonce, true)
+                .unwrap_or_else(|| panic!("Rate limit exceeded"));
+        Some(execution_id)
+    }
+
+    // Set the oracle contract used for conditional execution (admin only)
+    pub fn set_oracle(env: Env, admin: Address, oracle: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
 
-        execution_id
+        env.storage()
+            .instance()
+            .set(&symbol_short!("oracle"), &oracle);
+        env.events()
+            .publish((symbol_short!("oracle"), admin.clone()), (admin, oracle));
     }
 
-    // Get execution history
-    pub fn get_history(env: Env, agent_id: u64, limit: u32) -> Vec<ActionRecord> {
+    // Get the oracle contract used for conditional execution
+    pub fn get_oracle(env: Env) -> Option<Address> {
+        env.storage().instance().get(&symbol_short!("oracle"))
+    }
+
+    // Helper: shared body of the execute entry points; callers authorize `executor`
+    fn execute_action_impl(
+        env: &Env,
+        agent_id: u64,
+        executor: Address,
+        action: String,
+        parameters: Bytes,
+        nonce: u64,
+        emit_event: bool,
+    ) -> Option<u64> {
+        let env = env.clone();
+        Self::require_not_paused(&env);
+
         Self::validate_agent_id(agent_id);
+        Self::validate_string_length(&action, "Action name");
+        Self::validate_data_size(&parameters, "Parameters");
 
-        if limit > MAX_HISTORY_QUERY_LIMIT {
-            panic!("Limit exceeds maximum allowed (500)");
+        if !Self::is_authorized_executor(env.clone(), agent_id, executor.clone()) {
+            panic!("Executor not authorized for agent");
+        }
+        if Self::is_strict_mode(env.clone()) {
+            Self::require_agent_exists(&env, agent_id);
         }
 
-        let history_key = symbol_short!("hist");
-        let agent_key = (history_key, agent_id);
-        let history: Vec<ActionRecord> = env
-            .storage()
-            .instance()
-            .get(&agent_key)
-            .unwrap_or_else(|| Vec::new(&env));
-
-        let mut result = Vec::new(&env);
-        let start_idx = if history.len() > limit {
-            history.len() - limit
+        // Replay protection
+        let replay_mode = Self::get_replay_mode(env.clone(), agent_id);
+        if replay_mode == ReplayMode::LedgerSequence {
+            Self::check_ledger_sequence(&env, agent_id, nonce);
         } else {
-            0
-        };
-
-        for i in start_idx..history.len() {
-            if let Some(item) = history.get(i) {
-                result.push_back(item);
+            let stored_nonce = Self::get_action_nonce(env.clone(), agent_id);
+            if nonce == stored_nonce {
+                if let Some(execution_id) =
+                    Self::idempotent_execution_id(&env, agent_id, &executor, nonce)
+                {
+                    return Some(execution_id);
+                }
+            }
+            if nonce <= stored_nonce {
+                panic!("Invalid nonce: replay protection triggered");
             }
         }
 
-        result
-    }
+        // Rate limiting
+        if !Self::check_rate_limit(&env, agent_id) {
+            return None;
+        }
 
-    // Get total action count
-    pub fn get_action_count(env: Env, agent_id: u64) -> u32 {
-        Self::validate_agent_id(agent_id);
-        let history_key = symbol_short!("hist");
-        let agent_key = (history_key, agent_id);
-        let history: Vec<ActionRecord> = env
-            .storage()
-            .instance()
-            .get(&agent_key)
-            .unwrap_or_else(|| Vec::new(&env));
-        history.len()
-    }
+        let execution_id = Self::next_execution_id(&env);
+        if replay_mode == ReplayMode::LedgerSequence {
+            let seq_key = (symbol_short!("last_seq"), agent_id);
+            env.storage().instance().set(&seq_key, &nonce);
+        } else {
+            Self::set_action_nonce(&env, agent_id, nonce);
+        }
+        Self::record_action_in_history(&env, agent_id, execution_id, &action, &executor, nonce);
 
-    // Get admin address
-    pub fn get_admin(env: Env) -> Address {
+        let count_key = (symbol_short!("exec_cnt"), agent_id, executor.clone());
+        let count: u64 = env.storage().instance().get(&count_key).unwrap_or(0);
+        let count = count
+            .checked_add(1)
+            .expect("Executor action count overflow");
+        env.storage().instance().set(&count_key, &count);
+
+        let timestamp = env.ledger().timestamp();
+        let execution_hash =
+            Self::compute_execution_hash(env.clone(), agent_id, action.clone(), parameters, nonce);
+        let receipt = ExecutionReceipt {
+            execution_id,
+            agent_id,
+            action: action.clone(),
+            executor: executor.clone(),
+            nonce,
+            timestamp,
+            execution_hash,
+            status: ExecutionStatus::Pending,
+            retry_after: None,
+        };
         env.storage()
             .instance()
-            .get(&ADMIN_KEY)
-            .expect("Admin not set")
-    }
+            .set(&(symbol_short!("receipt"), execution_id), &receipt);
+        let mut receipt_ids = Self::read_receipt_index(&env, agent_id);
+        receipt_ids.push_back(execution_id);
+        env.storage()
+            .instance()
+            .set(&(symbol_short!("rcpt_ids"), agent_id), &receipt_ids);
+
+        let last_exec_key = (symbol_short!("last_exec"), agent_id);
+        env.storage().instance().set(
+            &last_exec_key,
+            &LastExecution {
+                nonce,
+                execution_id,
+                executor: executor.clone(),
+                timestamp,
+            },
+        );
 
-    // Transfer admin rights
-    pub fn transfer_admin(env: Env, current_admin: Address, new_admin: Address) {
-        current_admin.require_auth();
-        Self::verify_admin(&env, &current_admin);
+        if emit_event {
+            env.events().publish(
+                (symbol_short!("act_exec"), agent_id),
+                (execution_id, agent_id, action, executor, timestamp, nonce),
+            );
+        }
 
-        env.storage().instance().set(&ADMIN_KEY, &new_admin);
-        env.events()
-            .publish((symbol_short!("adm_xfer"),), (current_admin, new_admin));
+        Some(execution_id)
     }
 
-    // Helper: verify admin
-    fn verify_admin(env: &Env, caller: &Address) {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&ADMIN_KEY)
-            .expect("Admin not set");
-        if caller != &admin {
-            panic!("Unauthorized: caller is not admin");
+    // Execute several actions for one agent, the i-th with nonce `start_nonce + i`.
+    // Emits one `batch_exec` summary event (count, first and last execution ID); the
+    // per-action events are dropped while batch summary-only mode is enabled.
+    // The whole batch reverts if any action fails or is rate limited.
+    pub fn execute_batch(
+        env: Env,
+        agent_id: u64,
+        executor: Address,
+        actions: Vec<String>,
+        parameters: Vec<Bytes>,
+        start_nonce: u64,
+    ) -> Vec<u64> {
+        if actions.is_empty() {
+            panic!("Batch cannot be empty");
         }
-    }
+        if actions.len() > MAX_BATCH_SIZE {
+            panic!("Batch size exceeds maximum allowed");
+        }
+        if actions.len() != parameters.len() {
+            panic!("Actions and parameters length mismatch");
+        }
+        executor.require_auth();
 
-    // Helper: validate agent ID
-    fn validate_agent_id(agent_id: u64) {
-        if agent_id == 0 {
-            panic!("Invalid agent ID: must be non-zero");
+        let emit_items = !Self::is_batch_summary_only(env.clone());
+        let mut execution_ids = Vec::new(&env);
+        let mut nonce = start_nonce;
+        for (action, params) in actions.iter().zip(parameters.iter()) {
+            let execution_id = Self::execute_action_impl(
+                &env,
+                agent_id,
+                executor.clone(),
+                action,
+                params,
+                nonce,
+                emit_items,
+            )
+            .unwrap_or_else(|| panic!("Rate limit exceeded"));
+            execution_ids.push_back(execution_id);
+            nonce = nonce.checked_add(1).expect("Nonce overflow");
         }
+
+        let first = execution_ids.first().unwrap();
+        let last = execution_ids.last().unwrap();
+        env.events().publish(
+            (Symbol::new(&env, "batch_exec"), agent_id),
+            (execution_ids.len(), first, last),
+        );
+
+        execution_ids
     }
 
-    // Helper: validate string length
-    fn validate_string_length(s: &String, _field_name: &str) {
-        if s.len() > MAX_STRING_LENGTH {
-            panic!("String exceeds maximum length");
-        }
+    // Emit only the summary event for batches, not one event per action (admin only)
+    pub fn set_batch_summary_only(env: Env, admin: Address, summary_only: bool) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&symbol_short!("batch_sum"), &summary_only);
+        env.events().publish(
+            (symbol_short!("batch_sum"), admin.clone()),
+            (admin, summary_only),
+        );
     }
 
-    // Helper: validate data size
-    fn validate_data_size(data: &Bytes, _field_name: &str) {
-        if data.len() > MAX_DATA_SIZE {
-            panic!("Data exceeds maximum size");
-        }
+    // Check whether batches emit only their summary event
+    pub fn is_batch_summary_only(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("batch_sum"))
+            .unwrap_or(false)
     }
 
-    // Helper: get nonce
-    fn get_action_nonce(env: &Env, agent_id: u64) -> u64 {
-        let nonce_key = symbol_short!("nonce");
-        let agent_nonce_key = (nonce_key, agent_id);
-        env.storage().instance().get(&agent_nonce_key).unwrap_or(0)
+    // Get the receipt of a completed execution (public agents only, see
+    // `get_execution_receipt_as`)
+    pub fn get_execution_receipt(env: Env, execution_id: u64) -> Option<ExecutionReceipt> {
+        let receipt = Self::read_receipt(&env, execution_id)?;
+        Self::require_public_history(&env, receipt.agent_id);
+        Some(receipt)
     }
 
-    // Helper: set nonce
-    fn set_action_nonce(env: &Env, agent_id: u64, nonce: u64) {
-        let nonce_key = symbol_short!("nonce");
-        let agent_nonce_key = (nonce_key, agent_id);
-        env.storage().instance().set(&agent_nonce_key, &nonce);
+    // Get the receipt of an execution as the agent owner or a registered executor
+    pub fn get_execution_receipt_as(
+        env: Env,
+        execution_id: u64,
+        caller: Address,
+    ) -> Option<ExecutionReceipt> {
+        caller.require_auth();
+        let receipt = Self::read_receipt(&env, execution_id)?;
+        Self::require_history_reader(&env, receipt.agent_id, &caller);
+        Some(receipt)
     }
 
-    // Helper: record action in history
-    fn record_action_in_history(
-        env: &Env,
-        agent_id: u64,
+    // Helper: load a receipt without any privacy check
+    fn read_receipt(env: &Env, execution_id: u64) -> Option<ExecutionReceipt> {
+        env.storage()
+            .instance()
+            .get(&(symbol_short!("receipt"), execution_id))
+    }
+
+    // Record the outcome of an execution (its executor only). A failed execution can
+    // carry a hint that it may be retried `retry_delay_seconds` from now.
+    pub fn finalize_execution(
+        env: Env,
         execution_id: u64,
-        action: &String,
-        executor: &Address,
-        nonce: u64,
+        executor: Address,
+        succeeded: bool,
+        retry_delay_seconds: Option<u64>,
     ) {
-        let history_key = symbol_short!("hist");
-        let agent_key = (history_key, agent_id);
+        executor.require_auth();
 
-        let mut history: Vec<ActionRecord> = env
+        let receipt_key = (symbol_short!("receipt"), execution_id);
+        let mut receipt: ExecutionReceipt = env
             .storage()
             .instance()
-            .get(&agent_key)
-            .unwrap_or_else(|| Vec::new(env));
+            .get(&receipt_key)
+            .expect("Execution receipt not found");
 
-        if history.len() >= MAX_HISTORY_SIZE {
-            panic!("Action history limit exceeded");
+        if receipt.executor != executor {
+            panic!("Unauthorized: caller is not the executor");
+        }
+        if receipt.status != ExecutionStatus::Pending {
+            panic!("Execution already finalized");
         }
 
-        let timestamp = env.ledger().timestamp();
-        let record = ActionRecord {
-            execution_id,
-            action: action.clone(),
-            executor: executor.clone(),
-            timestamp,
-            nonce,
+        receipt.retry_after = match retry_delay_seconds {
+            None => None,
+            Some(_) if succeeded => panic!("Retry hint only applies to failed executions"),
+            Some(delay) if delay > MAX_RETRY_DELAY_SECONDS => {
+                panic!("Retry delay exceeds maximum allowed")
+            }
+            Some(delay) => Some(env.ledger().timestamp().saturating_add(delay)),
+        };
+        receipt.status = if succeeded {
+            ExecutionStatus::Succeeded
+        } else {
+            ExecutionStatus::Failed
         };
+        env.storage().instance().set(&receipt_key, &receipt);
 
-        history.push_back(record);
-        env.storage().instance().set(&agent_key, &history);
+        env.events().publish(
+            (symbol_short!("exec_fin"), receipt.agent_id),
+            (execution_id, receipt.status as u32, receipt.retry_after),
+        );
     }
 
-    // Helper: check rate limit
-    fn check_rate_limit(env: &Env, agent_id: u64, max_operations: u32, window_seconds: u64) {
+    // Get the agent's most recent receipts for `action`, oldest first (public agents
+    // only). `limit` is clamped to MAX_HISTORY_QUERY_LIMIT; pruned receipts are skipped.
+    pub fn get_agent_receipts_by_action(
+        env: Env,
+        agent_id: u64,
+        action: String,
+        limit: u32,
+    ) -> Vec<ExecutionReceipt> {
+        Self::validate_agent_id(agent_id);
+        Self::validate_string_length(&action, "Action name");
+        Self::require_public_history(&env, agent_id);
+
+        let limit = limit.min(MAX_HISTORY_QUERY_LIMIT);
+        let history: Vec<ActionRecord> = env
+            .storage()
+            .instance()
+            .get(&Self::history_key(agent_id))
+            .unwrap_or_else(|| Vec::new(&env));
+
+        let mut receipts = Vec::new(&env);
+        for record in history.iter().rev() {
+            if receipts.len() >= limit {
+                break;
+            }
+            if record.action != action {
+                continue;
+            }
+            if let Some(receipt) = Self::read_receipt(&env, record.execution_id) {
+                receipts.push_front(receipt);
+            }
+        }
+        receipts
+    }
+
+    // Delete an agent's receipts created before `cutoff_timestamp`; the action history
+    // is left untouched (admin only). Returns the number of receipts removed.
+    pub fn prune_receipts_before(
+        env: Env,
+        admin: Address,
+        agent_id: u64,
+        cutoff_timestamp: u64,
+    ) -> u32 {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+        Self::validate_agent_id(agent_id);
+
+        // Receipt IDs are appended in execution order, so the stale ones form a prefix.
+        // IDs whose receipt is already gone are dropped from the index but not counted.
+        let mut receipt_ids = Self::read_receipt_index(&env, agent_id);
+        let mut removed = 0u32;
+        while let Some(execution_id) = receipt_ids.first() {
+            let receipt_key = (symbol_short!("receipt"), execution_id);
+            if let Some(receipt) = env
+                .storage()
+                .instance()
+                .get::<_, ExecutionReceipt>(&receipt_key)
+            {
+                if receipt.timestamp >= cutoff_timestamp {
+                    break;
+                }
+                env.storage().instance().remove(&receipt_key);
+                removed += 1;
+            }
+            receipt_ids.pop_front();
+        }
+
+        let index_key = (symbol_short!("rcpt_ids"), agent_id);
+        if receipt_ids.is_empty() {
+            env.storage().instance().remove(&index_key);
+        } else {
+            env.storage().instance().set(&index_key, &receipt_ids);
+        }
+
+        env.events()
+            .publish((symbol_short!("rcpt_prn"), agent_id), (agent_id, removed));
+        removed
+    }
+
+    // Canonical hash of an execution's inputs:
+    //
+    //     sha256(encode_for_hash([U64(agent_id), String(action), Bytes(parameters), U64(nonce)]))
+    //
+    // where each field is written as tag (1 byte) || length (u32 BE) || payload, with
+    // integers big-endian and the action as raw UTF-8 (see `stellai_lib::encoding`).
+    pub fn compute_execution_hash(
+        env: Env,
+        agent_id: u64,
+        action: String,
+        parameters: Bytes,
+        nonce: u64,
+    ) -> Bytes {
+        let preimage = encode_for_hash(
+            &env,
+            &[
+                EncodablePart::U64(agent_id),
+                EncodablePart::String(action),
+                EncodablePart::Bytes(parameters),
+                EncodablePart::U64(nonce),
+            ],
+        );
+        env.crypto().sha256(&preimage).into()
+    }
+
+    // Check a receipt's execution hash; false if the execution does not exist
+    // (public agents only, see `verify_execution_hash_as`)
+    pub fn verify_execution_hash(env: Env, execution_id: u64, expected_hash: Bytes) -> bool {
+        match Self::get_execution_receipt(env, execution_id) {
+            Some(receipt) => receipt.execution_hash == expected_hash,
+            None => false,
+        }
+    }
+
+    // Check a receipt's execution hash as the agent owner or a registered executor
+    pub fn verify_execution_hash_as(
+        env: Env,
+        execution_id: u64,
+        caller: Address,
+        expected_hash: Bytes,
+    ) -> bool {
+        match Self::get_execution_receipt_as(env, execution_id, caller) {
+            Some(receipt) => receipt.execution_hash == expected_hash,
+            None => false,
+        }
+    }
+
+    // Get execution history (public agents only, see `get_history_as`)
+    pub fn get_history(env: Env, agent_id: u64, limit: u32) -> Vec<ActionRecord> {
+        Self::validate_agent_id(agent_id);
+        Self::require_public_history(&env, agent_id);
+        Self::read_history(&env, agent_id, limit)
+    }
+
+    // Get execution history as the agent owner or a registered executor
+    pub fn get_history_as(
+        env: Env,
+        agent_id: u64,
+        caller: Address,
+        limit: u32,
+    ) -> Vec<ActionRecord> {
+        caller.require_auth();
+        Self::validate_agent_id(agent_id);
+        Self::require_history_reader(&env, agent_id, &caller);
+        Self::read_history(&env, agent_id, limit)
+    }
+
+    // Get a page of execution history in chronological order, with the total record count
+    pub fn get_history_page(env: Env, agent_id: u64, offset: u32, limit: u32) -> ActionRecordPage {
+        Self::validate_agent_id(agent_id);
+        Self::require_public_history(&env, agent_id);
+
+        if limit > MAX_HISTORY_QUERY_LIMIT {
+            panic!("Limit exceeds maximum allowed (500)");
+        }
+
+        let history: Vec<ActionRecord> = env
+            .storage()
+            .instance()
+            .get(&Self::history_key(agent_id))
+            .unwrap_or_else(|| Vec::new(&env));
+
+        let total = history.len();
+        let start = offset.min(total);
+        let end = offset.saturating_add(limit).min(total);
+
+        ActionRecordPage {
+            items: history.slice(start..end),
+            total,
+            offset,
+        }
+    }
+
+    // Get history records from `offset` onward, oldest first. Unlike `get_history_page`,
+    // `limit` is clamped to MAX_HISTORY_QUERY_LIMIT rather than rejected.
+    pub fn get_history_paged(
+        env: Env,
+        agent_id: u64,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<ActionRecord> {
+        let limit = limit.min(MAX_HISTORY_QUERY_LIMIT);
+        Self::get_history_page(env, agent_id, offset, limit).items
+    }
+
+    // Get total action count (public agents only, see `get_action_count_as`)
+    pub fn get_action_count(env: Env, agent_id: u64) -> u32 {
+        Self::validate_agent_id(agent_id);
+        Self::require_public_history(&env, agent_id);
+        Self::history_len(&env, agent_id)
+    }
+
+    // Get total action count as the agent owner or a registered executor
+    pub fn get_action_count_as(env: Env, agent_id: u64, caller: Address) -> u32 {
+        caller.require_auth();
+        Self::validate_agent_id(agent_id);
+        Self::require_history_reader(&env, agent_id, &caller);
+        Self::history_len(&env, agent_id)
+    }
+
+    // Register the owner of an agent (admin only)
+    pub fn set_agent_owner(env: Env, admin: Address, agent_id: u64, owner: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+        Self::validate_agent_id(agent_id);
+
+        let owner_key = (symbol_short!("owner"), agent_id);
+        env.storage().instance().set(&owner_key, &owner);
+        env.events()
+            .publish((symbol_short!("owner_set"), agent_id), (agent_id, owner));
+    }
+
+    // Get the registered owner of an agent
+    pub fn get_agent_owner(env: Env, agent_id: u64) -> Option<Address> {
+        let owner_key = (symbol_short!("owner"), agent_id);
+        env.storage().instance().get(&owner_key)
+    }
+
+    // Get the last nonce used by an agent (the next execution must use a higher one)
+    pub fn get_action_nonce(env: Env, agent_id: u64) -> u64 {
+        let nonce_key = symbol_short!("nonce");
+        let agent_nonce_key = (nonce_key, agent_id);
+        env.storage().instance().get(&agent_nonce_key).unwrap_or(0)
+    }
+
+    // Skip an agent's nonce forward, e.g. after a signer lost track of it (agent owner only)
+    pub fn bump_nonce(env: Env, agent_id: u64, owner: Address, new_nonce: u64) {
+        owner.require_auth();
+        Self::validate_agent_id(agent_id);
+        Self::verify_agent_owner(&env, agent_id, &owner);
+
+        let stored_nonce = Self::get_action_nonce(env.clone(), agent_id);
+        if new_nonce <= stored_nonce {
+            panic!("New nonce must be greater than current nonce");
+        }
+
+        Self::set_action_nonce(&env, agent_id, new_nonce);
+        env.events().publish(
+            (symbol_short!("nonce_bmp"), agent_id),
+            (agent_id, stored_nonce, new_nonce),
+        );
+    }
+
+    // Register an executor for an agent (agent owner only)
+    pub fn register_agent_executor(env: Env, agent_id: u64, owner: Address, executor: Address) {
+        owner.require_auth();
+        Self::validate_agent_id(agent_id);
+        Self::verify_agent_owner(&env, agent_id, &owner);
+
+        let mut executors = Self::get_agent_executors(env.clone(), agent_id);
+        if executors.contains(&executor) {
+            panic!("Executor already registered");
+        }
+        if executors.len() >= MAX_EXECUTORS_PER_AGENT {
+            panic!("Maximum number of executors reached");
+        }
+
+        executors.push_back(executor.clone());
+        env.storage()
+            .instance()
+            .set(&(symbol_short!("executors"), agent_id), &executors);
+        env.events()
+            .publish((symbol_short!("exec_reg"), agent_id), (agent_id, executor));
+    }
+
+    // Remove a registered executor (agent owner only). Removing the last one
+    // reopens the agent to any executor.
+    pub fn revoke_agent_executor(env: Env, agent_id: u64, owner: Address, executor: Address) {
+        owner.require_auth();
+        Self::validate_agent_id(agent_id);
+        Self::verify_agent_owner(&env, agent_id, &owner);
+
+        let mut executors = Self::get_agent_executors(env.clone(), agent_id);
+        let index = executors
+            .first_index_of(&executor)
+            .unwrap_or_else(|| panic!("Executor not registered"));
+        executors.remove(index);
+
+        let executors_key = (symbol_short!("executors"), agent_id);
+        if executors.is_empty() {
+            env.storage().instance().remove(&executors_key);
+        } else {
+            env.storage().instance().set(&executors_key, &executors);
+        }
+        env.events()
+            .publish((symbol_short!("exec_rev"), agent_id), (agent_id, executor));
+    }
+
+    // Get the executors registered for an agent
+    pub fn get_agent_executors(env: Env, agent_id: u64) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&(symbol_short!("executors"), agent_id))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    // Get how many actions `executor` has performed for an agent
+    pub fn get_executor_action_count(env: Env, agent_id: u64, executor: Address) -> u64 {
+        let count_key = (symbol_short!("exec_cnt"), agent_id, executor);
+        env.storage().instance().get(&count_key).unwrap_or(0)
+    }
+
+    // Let `delegate` act as an executor for the agent until `expires_at` (agent owner only)
+    pub fn delegate_executor(
+        env: Env,
+        agent_id: u64,
+        owner: Address,
+        delegate: Address,
+        expires_at: u64,
+    ) {
+        owner.require_auth();
+        Self::validate_agent_id(agent_id);
+        Self::verify_agent_owner(&env, agent_id, &owner);
+
+        if expires_at <= env.ledger().timestamp() {
+            panic!("Delegation expiry must be in the future");
+        }
+
+        let delegation_key = (symbol_short!("delegate"), agent_id, delegate.clone());
+        env.storage().instance().set(&delegation_key, &expires_at);
+        env.events().publish(
+            (symbol_short!("delegate"), agent_id),
+            (agent_id, delegate, expires_at),
+        );
+    }
+
+    // Get the expiry of a delegation, if one was granted
+    pub fn get_delegation(env: Env, agent_id: u64, delegate: Address) -> Option<u64> {
+        let delegation_key = (symbol_short!("delegate"), agent_id, delegate);
+        env.storage().instance().get(&delegation_key)
+    }
+
+    // Check whether `executor` may act for the agent: the owner, a registered executor
+    // (any address while none are registered), or a holder of an unexpired delegation
+    pub fn is_authorized_executor(env: Env, agent_id: u64, executor: Address) -> bool {
+        if Self::get_agent_owner(env.clone(), agent_id) == Some(executor.clone()) {
+            return true;
+        }
+
+        let executors = Self::get_agent_executors(env.clone(), agent_id);
+        if executors.is_empty() || executors.contains(&executor) {
+            return true;
+        }
+
+        match Self::get_delegation(env.clone(), agent_id, executor) {
+            Some(expires_at) => env.ledger().timestamp() < expires_at,
+            None => false,
+        }
+    }
+
+    // Make an agent's history private or public (admin or agent owner)
+    pub fn set_history_private(env: Env, agent_id: u64, caller: Address, private: bool) {
+        caller.require_auth();
+        Self::validate_agent_id(agent_id);
+
+        let is_admin = env
+            .storage()
+            .instance()
+            .get::<_, Address>(&ADMIN_KEY)
+            .map(|admin| admin == caller)
+            .unwrap_or(false);
+        if !is_admin {
+            Self::verify_agent_owner(&env, agent_id, &caller);
+        }
+
+        let private_key = (symbol_short!("hist_priv"), agent_id);
+        if private {
+            env.storage().instance().set(&private_key, &true);
+        } else {
+            env.storage().instance().remove(&private_key);
+        }
+        env.events()
+            .publish((symbol_short!("hist_priv"), agent_id), (agent_id, private));
+    }
+
+    // Check if an agent's history is private
+    pub fn is_history_private(env: Env, agent_id: u64) -> bool {
+        let private_key = (symbol_short!("hist_priv"), agent_id);
+        env.storage().instance().has(&private_key)
+    }
+
+    // Set idempotency window for retried nonces (admin only, 0 disables)
+    pub fn set_idempotency_window(env: Env, admin: Address, window_seconds: u64) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if window_seconds > MAX_IDEMPOTENCY_WINDOW_SECONDS {
+            panic!("Idempotency window exceeds maximum allowed");
+        }
+
+        env.storage()
+            .instance()
+            .set(&symbol_short!("idem_win"), &window_seconds);
+        env.events().publish(
+            (symbol_short!("idem_win"), admin.clone()),
+            (admin, window_seconds),
+        );
+    }
+
+    // Get idempotency window (0 means strict replay protection)
+    pub fn get_idempotency_window(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("idem_win"))
+            .unwrap_or(0)
+    }
+
+    // Set replay protection mode for an agent (admin only)
+    pub fn set_replay_mode(env: Env, admin: Address, agent_id: u64, mode: ReplayMode) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+        Self::validate_agent_id(agent_id);
+
+        let mode_key = (symbol_short!("replay"), agent_id);
+        env.storage().instance().set(&mode_key, &mode);
+        env.events()
+            .publish((symbol_short!("replay"), agent_id), (agent_id, mode as u32));
+    }
+
+    // Get replay protection mode for an agent (nonce mode by default)
+    pub fn get_replay_mode(env: Env, agent_id: u64) -> ReplayMode {
+        let mode_key = (symbol_short!("replay"), agent_id);
+        env.storage()
+            .instance()
+            .get(&mode_key)
+            .unwrap_or(ReplayMode::Nonce)
+    }
+
+    // Set the AgentNFT contract used by strict mode (admin only)
+    pub fn set_agent_nft(env: Env, admin: Address, agent_nft: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&symbol_short!("agent_nft"), &agent_nft);
+        env.events().publish(
+            (symbol_short!("agent_nft"), admin.clone()),
+            (admin, agent_nft),
+        );
+    }
+
+    // Require executed agents to exist in the AgentNFT contract (admin only).
+    // Off by default so the hub can run decoupled from AgentNFT.
+    pub fn set_strict_mode(env: Env, admin: Address, strict: bool) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if strict && !env.storage().instance().has(&symbol_short!("agent_nft")) {
+            panic!("AgentNFT contract not configured");
+        }
+
+        env.storage()
+            .instance()
+            .set(&symbol_short!("strict"), &strict);
+        env.events()
+            .publish((symbol_short!("strict"), admin.clone()), (admin, strict));
+    }
+
+    // Check whether strict mode is enabled
+    pub fn is_strict_mode(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("strict"))
+            .unwrap_or(false)
+    }
+
+    // Pause or resume executions and rule changes; reads stay available (admin only)
+    pub fn set_paused(env: Env, admin: Address, paused: bool) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&symbol_short!("paused"), &paused);
+        env.events()
+            .publish((symbol_short!("paused"), admin.clone()), (admin, paused));
+    }
+
+    // Check whether the contract is paused
+    pub fn is_paused(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("paused"))
+            .unwrap_or(false)
+    }
+
+    // Set the per-agent history cap; executions are rejected once it is reached (admin only)
+    pub fn set_history_cap(env: Env, admin: Address, cap: u32) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if cap == 0 || cap > MAX_HISTORY_SIZE {
+            panic!("History cap out of bounds");
+        }
+
+        env.storage()
+            .instance()
+            .set(&symbol_short!("hist_cap"), &cap);
+        env.events()
+            .publish((symbol_short!("hist_cap"), admin.clone()), (admin, cap));
+    }
+
+    // Get the per-agent history cap
+    pub fn get_history_cap(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&symbol_short!("hist_cap"))
+            .unwrap_or(MAX_HISTORY_SIZE)
+    }
+
+    // Override the rate limit for an agent (admin only)
+    pub fn set_agent_rate_limit(
+        env: Env,
+        admin: Address,
+        agent_id: u64,
+        max_operations: u32,
+        window_seconds: u64,
+    ) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+        Self::validate_agent_id(agent_id);
+
+        if window_seconds == 0 {
+            panic!("Rate limit window must be positive");
+        }
+        if max_operations == 0 || max_operations > MAX_RATE_LIMIT_OPERATIONS {
+            panic!("Rate limit operations out of bounds");
+        }
+
+        let limit_key = (symbol_short!("agent_rl"), agent_id);
+        env.storage().instance().set(
+            &limit_key,
+            &RateLimit {
+                window_seconds,
+                max_operations,
+            },
+        );
+        env.events().publish(
+            (symbol_short!("agent_rl"), agent_id),
+            (agent_id, max_operations, window_seconds),
+        );
+    }
+
+    // Get the rate limit for an agent (the defaults unless overridden)
+    pub fn get_agent_rate_limit(env: Env, agent_id: u64) -> RateLimit {
+        let limit_key = (symbol_short!("agent_rl"), agent_id);
+        env.storage()
+            .instance()
+            .get(&limit_key)
+            .unwrap_or(RateLimit {
+                window_seconds: DEFAULT_RATE_LIMIT_WINDOW_SECONDS,
+                max_operations: DEFAULT_RATE_LIMIT_OPERATIONS,
+            })
+    }
+
+    // Get all admin-set parameters in one call, for diagnostics
+    pub fn get_config(env: Env) -> ContractConfig {
+        ContractConfig {
+            admin: Self::get_admin(env.clone()),
+            paused: Self::is_paused(env.clone()),
+            strict_mode: Self::is_strict_mode(env.clone()),
+            history_cap: Self::get_history_cap(env.clone()),
+            idempotency_window: Self::get_idempotency_window(env.clone()),
+            oracle: Self::get_oracle(env.clone()),
+            agent_nft: env.storage().instance().get(&symbol_short!("agent_nft")),
+            batch_summary_only: Self::is_batch_summary_only(env.clone()),
+        }
+    }
+
+    // Get admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&ADMIN_KEY)
+            .expect("Admin not set")
+    }
+
+    // Transfer admin rights
+    pub fn transfer_admin(env: Env, current_admin: Address, new_admin: Address) {
+        current_admin.require_auth();
+        Self::verify_admin(&env, &current_admin);
+
+        env.storage().instance().set(&ADMIN_KEY, &new_admin);
+        env.events().publish(
+            (symbol_short!("adm_xfer"), current_admin.clone()),
+            (current_admin, new_admin),
+        );
+    }
+
+    // Helper: verify admin
+    fn verify_admin(env: &Env, caller: &Address) {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&ADMIN_KEY)
+            .expect("Admin not set");
+        if caller != &admin {
+            panic!("Unauthorized: caller is not admin");
+        }
+    }
+
+    // Helper: verify caller is the registered agent owner
+    fn verify_agent_owner(env: &Env, agent_id: u64, caller: &Address) {
+        let owner = Self::get_agent_owner(env.clone(), agent_id).expect("Agent owner not set");
+        if caller != &owner {
+            panic!("Unauthorized: caller is not agent owner");
+        }
+    }
+
+    // Helper: reject agents unknown to the AgentNFT contract
+    fn require_agent_exists(env: &Env, agent_id: u64) {
+        let agent_nft: Address = env
+            .storage()
+            .instance()
+            .get(&symbol_short!("agent_nft"))
+            .expect("AgentNFT contract not configured");
+        if AgentNftClient::new(env, &agent_nft)
+            .try_get_agent(&agent_id)
+            .is_err()
+        {
+            panic_with_error!(env, ContractError::AgentNotFound);
+        }
+    }
+
+    // Helper: reject state changes while paused
+    fn require_not_paused(env: &Env) {
+        if Self::is_paused(env.clone()) {
+            panic!("Contract is paused");
+        }
+    }
+
+    // Helper: reject anonymous history reads for private agents
+    fn require_public_history(env: &Env, agent_id: u64) {
+        if Self::is_history_private(env.clone(), agent_id) {
+            panic!("History is private");
+        }
+    }
+
+    // Helper: private history is readable by the agent owner and registered executors
+    fn require_history_reader(env: &Env, agent_id: u64, caller: &Address) {
+        if !Self::is_history_private(env.clone(), agent_id) {
+            return;
+        }
+
+        let is_owner = Self::get_agent_owner(env.clone(), agent_id)
+            .map(|owner| &owner == caller)
+            .unwrap_or(false);
+        if !is_owner && !Self::get_agent_executors(env.clone(), agent_id).contains(caller) {
+            panic!("Unauthorized: history is private");
+        }
+    }
+
+    // Helper: validate agent ID
+    fn validate_agent_id(agent_id: u64) {
+        if agent_id == 0 {
+            panic!("Invalid agent ID: must be non-zero");
+        }
+    }
+
+    // Helper: validate string length
+    fn validate_string_length(s: &String, _field_name: &str) {
+        if s.len() > MAX_STRING_LENGTH {
+            panic!("String exceeds maximum length");
+        }
+    }
+
+    // Helper: validate data size
+    fn validate_data_size(data: &Bytes, _field_name: &str) {
+        if data.len() > MAX_DATA_SIZE {
+            panic!("Data exceeds maximum size");
+        }
+    }
+
+    // Helper: prior execution ID for a nonce retried by the same executor while still
+    // inside the idempotency window
+    fn idempotent_execution_id(
+        env: &Env,
+        agent_id: u64,
+        executor: &Address,
+        nonce: u64,
+    ) -> Option<u64> {
+        let window = Self::get_idempotency_window(env.clone());
+        if window == 0 {
+            return None;
+        }
+
+        let last_exec_key = (symbol_short!("last_exec"), agent_id);
+        let last: LastExecution = env.storage().instance().get(&last_exec_key)?;
+        if last.nonce != nonce || &last.executor != executor {
+            return None;
+        }
+
+        let elapsed = env.ledger().timestamp().saturating_sub(last.timestamp);
+        if elapsed <= window {
+            Some(last.execution_id)
+        } else {
+            None
+        }
+    }
+
+    // Helper: in ledger-sequence mode the action references a ledger sequence that must
+    // not be in the future and must be strictly greater than the last accepted one
+    fn check_ledger_sequence(env: &Env, agent_id: u64, sequence: u64) {
+        if sequence > env.ledger().sequence() as u64 {
+            panic!("Invalid ledger sequence: sequence is in the future");
+        }
+
+        let seq_key = (symbol_short!("last_seq"), agent_id);
+        let last_sequence: u64 = env.storage().instance().get(&seq_key).unwrap_or(0);
+        if sequence <= last_sequence {
+            panic!("Invalid ledger sequence: replay protection triggered");
+        }
+    }
+
+    // Helper: set nonce
+    fn set_action_nonce(env: &Env, agent_id: u64, nonce: u64) {
+        let nonce_key = symbol_short!("nonce");
+        let agent_nonce_key = (nonce_key, agent_id);
+        env.storage().instance().set(&agent_nonce_key, &nonce);
+    }
+
+    // Helper: execution IDs of an agent's stored receipts, oldest first
+    fn read_receipt_index(env: &Env, agent_id: u64) -> Vec<u64> {
+        env.storage()
+            .instance()
+            .get(&(symbol_short!("rcpt_ids"), agent_id))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    // Helper: storage key for an agent's action history
+    fn history_key(agent_id: u64) -> (Symbol, u64) {
+        (symbol_short!("hist"), agent_id)
+    }
+
+    // Helper: last `limit` history records in chronological order
+    fn read_history(env: &Env, agent_id: u64, limit: u32) -> Vec<ActionRecord> {
+        if limit > MAX_HISTORY_QUERY_LIMIT {
+            panic!("Limit exceeds maximum allowed (500)");
+        }
+
+        let history: Vec<ActionRecord> = env
+            .storage()
+            .instance()
+            .get(&Self::history_key(agent_id))
+            .unwrap_or_else(|| Vec::new(env));
+
+        let mut result = Vec::new(env);
+        let start_idx = if history.len() > limit {
+            history.len() - limit
+        } else {
+            0
+        };
+
+        for i in start_idx..history.len() {
+            if let Some(item) = history.get(i) {
+                result.push_back(item);
+            }
+        }
+
+        result
+    }
+
+    // Helper: number of history records for an agent
+    fn history_len(env: &Env, agent_id: u64) -> u32 {
+        let history: Vec<ActionRecord> = env
+            .storage()
+            .instance()
+            .get(&Self::history_key(agent_id))
+            .unwrap_or_else(|| Vec::new(env));
+        history.len()
+    }
+
+    // Helper: record action in history
+    fn record_action_in_history(
+        env: &Env,
+        agent_id: u64,
+        execution_id: u64,
+        action: &String,
+        executor: &Address,
+        nonce: u64,
+    ) {
+        let agent_key = Self::history_key(agent_id);
+
+        let mut history: Vec<ActionRecord> = env
+            .storage()
+            .instance()
+            .get(&agent_key)
+            .unwrap_or_else(|| Vec::new(env));
+
+        if history.len() >= Self::get_history_cap(env.clone()) {
+            panic!("Action history limit exceeded");
+        }
+
+        let timestamp = env.ledger().timestamp();
+        let record = ActionRecord {
+            execution_id,
+            action: action.clone(),
+            executor: executor.clone(),
+            timestamp,
+            nonce,
+        };
+
+        history.push_back(record);
+        env.storage().instance().set(&agent_key, &history);
+    }
+
+    // Helper: check and consume rate limit; emits `rate_limited` and returns false when exceeded
+    fn check_rate_limit(env: &Env, agent_id: u64) -> bool {
+        let RateLimit {
+            window_seconds,
+            max_operations,
+        } = Self::get_agent_rate_limit(env.clone(), agent_id);
         let now = env.ledger().timestamp();
         let limit_key = symbol_short!("ratelim");
         let agent_limit_key = (limit_key, agent_id);
 
-        let rate_data: Option<RateLimitData> = env.storage().instance().get(&agent_limit_key);
-        let (last_reset, count) = match rate_data {
-            Some(data) => (data.last_reset, data.count),
-            None => (now, 0),
-        };
+        let rate_data: Option<RateLimitData> = env.storage().instance().get(&agent_limit_key);
+        let (last_reset, count) = match rate_data {
+            Some(data) => (data.last_reset, data.count),
+            None => (now, 0),
+        };
+
+        let elapsed = now.saturating_sub(last_reset);
+
+        let (new_reset, new_count) = if elapsed > window_seconds {
+            (now, 1)
+        } else if count < max_operations {
+            (last_reset, count + 1)
+        } else {
+            env.events().publish(
+                (Symbol::new(env, "rate_limited"), agent_id),
+                (agent_id, now),
+            );
+            return false;
+        };
+
+        let new_rate_data = RateLimitData {
+            last_reset: new_reset,
+            count: new_count,
+        };
+
+        env.storage()
+            .instance()
+            .set(&agent_limit_key, &new_rate_data);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate alloc;
+
+    use super::*;
+    use soroban_sdk::{
+        testutils::{Address as _, Events, Ledger},
+        Env, IntoVal,
+    };
+
+    #[test]
+    fn test_initialization() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+
+        env.mock_all_auths();
+        assert!(!client.is_initialized());
+        client.initialize(&admin);
+
+        assert!(client.is_initialized());
+        assert_eq!(client.get_admin(), admin);
+        assert_eq!(client.get_execution_counter(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract already initialized")]
+    fn test_double_initialization() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.initialize(&admin);
+    }
+
+    #[test]
+    fn test_execution_counter_increment() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+
+        let action = String::from_str(&env, "test_action");
+        let params = Bytes::from_array(&env, &[1, 2, 3]);
+
+        let exec_id_1 = client.execute_action(&1, &executor, &action, &params, &1);
+        assert_eq!(exec_id_1, 1);
+        assert_eq!(client.get_execution_counter(), 1);
+
+        let exec_id_2 = client.execute_action(&1, &executor, &action, &params, &2);
+        assert_eq!(exec_id_2, 2);
+        assert_eq!(client.get_execution_counter(), 2);
+    }
+
+    #[test]
+    fn test_register_and_get_rule() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+
+        let rule_name = String::from_str(&env, "my_rule");
+        let rule_data = Bytes::from_array(&env, &[10, 20, 30]);
+
+        client.register_rule(&1, &owner, &rule_name, &rule_data);
+
+        let retrieved = client.get_rule(&1, &rule_name);
+        assert!(retrieved.is_some());
+        assert_eq!(retrieved.unwrap(), rule_data);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid nonce")]
+    fn test_replay_protection() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+
+        client.execute_action(&1, &executor, &action, &params, &1);
+        client.execute_action(&1, &executor, &action, &params, &1);
+    }
+
+    #[test]
+    fn test_get_history() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+
+        let action = String::from_str(&env, "test_action");
+        let params = Bytes::from_array(&env, &[1]);
+
+        client.execute_action(&1, &executor, &action, &params, &1);
+        client.execute_action(&1, &executor, &action, &params, &2);
+
+        let history = client.get_history(&1, &10);
+        assert_eq!(history.len(), 2);
+        assert_eq!(client.get_action_count(&1), 2);
+    }
+
+    #[test]
+    fn test_admin_transfer() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin1 = Address::generate(&env);
+        let admin2 = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin1);
+
+        client.transfer_admin(&admin1, &admin2);
+        assert_eq!(client.get_admin(), admin2);
+    }
+
+    #[test]
+    fn test_rate_limiting() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+
+        for i in 1..=10 {
+            client.execute_action(&1, &executor, &action, &params, &i);
+        }
+
+        let result = client.execute_action(&1, &executor, &action, &params, &11);
+        assert!(result > 0);
+    }
+    #[test]
+    #[should_panic(expected = "Invalid nonce")]
+    fn test_equal_nonce_rejected_in_strict_mode() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        assert_eq!(client.get_idempotency_window(), 0);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+
+        client.execute_action(&1, &executor, &action, &params, &5);
+        client.execute_action(&1, &executor, &action, &params, &5);
+    }
+
+    #[test]
+    fn test_idempotent_retry_within_window() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_idempotency_window(&admin, &30);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+
+        let exec_id = client.execute_action(&1, &executor, &action, &params, &1);
+        env.ledger().with_mut(|li| li.timestamp += 10);
+        let retried_id = client.execute_action(&1, &executor, &action, &params, &1);
+
+        assert_eq!(retried_id, exec_id);
+        assert_eq!(client.get_execution_counter(), 1);
+        assert_eq!(client.get_action_count(&1), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid nonce")]
+    fn test_idempotent_retry_rejected_for_other_executor() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+        let other_executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_idempotency_window(&admin, &30);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+
+        client.execute_action(&1, &executor, &action, &params, &1);
+        client.execute_action(&1, &other_executor, &action, &params, &1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid nonce")]
+    fn test_idempotent_retry_rejected_after_window() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_idempotency_window(&admin, &30);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+
+        client.execute_action(&1, &executor, &action, &params, &1);
+        env.ledger().with_mut(|li| li.timestamp += 31);
+        client.execute_action(&1, &executor, &action, &params, &1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid nonce")]
+    fn test_stale_nonce_rejected_with_window() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_idempotency_window(&admin, &30);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+
+        client.execute_action(&1, &executor, &action, &params, &1);
+        client.execute_action(&1, &executor, &action, &params, &2);
+        client.execute_action(&1, &executor, &action, &params, &1);
+    }
+
+    #[test]
+    fn test_ledger_sequence_mode_accepts_increasing_sequence() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_replay_mode(&admin, &1, &ReplayMode::LedgerSequence);
+        assert!(client.get_replay_mode(&1) == ReplayMode::LedgerSequence);
+        assert!(client.get_replay_mode(&2) == ReplayMode::Nonce);
+
+        env.ledger().with_mut(|li| li.sequence_number = 100);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+
+        client.execute_action(&1, &executor, &action, &params, &50);
+        client.execute_action(&1, &executor, &action, &params, &100);
+        assert_eq!(client.get_action_count(&1), 2);
+
+        // Agent 2 stays in nonce mode and is unaffected
+        client.execute_action(&2, &executor, &action, &params, &1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid ledger sequence")]
+    fn test_ledger_sequence_mode_rejects_out_of_order() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_replay_mode(&admin, &1, &ReplayMode::LedgerSequence);
+
+        env.ledger().with_mut(|li| li.sequence_number = 100);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+
+        client.execute_action(&1, &executor, &action, &params, &60);
+        client.execute_action(&1, &executor, &action, &params, &40);
+    }
+
+    #[test]
+    #[should_panic(expected = "sequence is in the future")]
+    fn test_ledger_sequence_mode_rejects_future_sequence() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_replay_mode(&admin, &1, &ReplayMode::LedgerSequence);
+
+        env.ledger().with_mut(|li| li.sequence_number = 100);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+
+        client.execute_action(&1, &executor, &action, &params, &101);
+    }
+
+    #[test]
+    fn test_agent_histories_are_independent() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+
+        let action = String::from_str(&env, "agent_one_action");
+        let params = Bytes::from_array(&env, &[1]);
+
+        client.execute_action(&1, &executor, &action, &params, &1);
+        client.execute_action(&1, &executor, &action, &params, &2);
+
+        assert_eq!(client.get_action_count(&1), 2);
+        assert_eq!(client.get_action_count(&2), 0);
+        assert_eq!(client.get_history(&2, &10).len(), 0);
+
+        let other_action = String::from_str(&env, "agent_two_action");
+        client.execute_action(&2, &executor, &other_action, &params, &1);
+
+        let history_one = client.get_history(&1, &10);
+        let history_two = client.get_history(&2, &10);
+        assert_eq!(history_one.len(), 2);
+        assert_eq!(history_two.len(), 1);
+        assert_eq!(history_two.get(0).unwrap().action, other_action);
+        for record in history_one.iter() {
+            assert_eq!(record.action, action);
+        }
+    }
+
+    #[test]
+    fn test_history_page_total_is_independent_of_window() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+        for nonce in 1..=5 {
+            client.execute_action(&1, &executor, &action, &params, &nonce);
+        }
+
+        let page = client.get_history_page(&1, &1, &2);
+        assert_eq!(page.total, 5);
+        assert_eq!(page.offset, 1);
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.items.get(0).unwrap().nonce, 2);
+        assert_eq!(page.items.get(1).unwrap().nonce, 3);
+
+        let past_end = client.get_history_page(&1, &10, &2);
+        assert_eq!(past_end.total, 5);
+        assert_eq!(past_end.items.len(), 0);
+    }
+
+    #[test]
+    fn test_private_history_blocks_third_party_but_allows_owner() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let executor = Address::generate(&env);
+        let stranger = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_agent_owner(&admin, &1, &owner);
+        client.register_agent_executor(&1, &owner, &executor);
+
+        let action = String::from_str(&env, "sensitive");
+        let params = Bytes::from_array(&env, &[1]);
+        client.execute_action(&1, &executor, &action, &params, &1);
+
+        client.set_history_private(&1, &owner, &true);
+        assert!(client.is_history_private(&1));
+
+        assert_eq!(client.get_history_as(&1, &owner, &10).len(), 1);
+        assert_eq!(client.get_action_count_as(&1, &executor), 1);
+        assert!(client.try_get_history_as(&1, &stranger, &10).is_err());
+        assert!(client.try_get_action_count_as(&1, &stranger).is_err());
+        assert!(client.try_get_history(&1, &10).is_err());
+        assert!(client.try_get_action_count(&1).is_err());
+    }
+
+    #[test]
+    fn test_private_history_hides_receipts_from_third_parties() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let executor = Address::generate(&env);
+        let stranger = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_agent_owner(&admin, &1, &owner);
+        client.register_agent_executor(&1, &owner, &executor);
+
+        let action = String::from_str(&env, "sensitive");
+        let params = Bytes::from_array(&env, &[1]);
+        let execution_id = client.execute_action(&1, &executor, &action, &params, &1);
+        let hash = client
+            .get_execution_receipt(&execution_id)
+            .unwrap()
+            .execution_hash;
+
+        client.set_history_private(&1, &owner, &true);
+
+        assert!(client.try_get_execution_receipt(&execution_id).is_err());
+        assert!(client
+            .try_verify_execution_hash(&execution_id, &hash)
+            .is_err());
+        assert!(client
+            .try_get_execution_receipt_as(&execution_id, &stranger)
+            .is_err());
+        assert!(client
+            .try_verify_execution_hash_as(&execution_id, &stranger, &hash)
+            .is_err());
+
+        assert!(client
+            .get_execution_receipt_as(&execution_id, &owner)
+            .is_some());
+        assert!(client.verify_execution_hash_as(&execution_id, &executor, &hash));
+
+        // Unknown executions stay indistinguishable from mismatches
+        assert!(!client.verify_execution_hash(&99, &hash));
+    }
+
+    #[test]
+    fn test_public_history_unchanged() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let executor = Address::generate(&env);
+        let stranger = Address::generate(&env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_agent_owner(&admin, &1, &owner);
+
+        let action = String::from_str(&env, "public");
+        let params = Bytes::from_array(&env, &[1]);
+        client.execute_action(&1, &executor, &action, &params, &1);
+
+        assert!(!client.is_history_private(&1));
+        assert_eq!(client.get_history(&1, &10).len(), 1);
+        assert_eq!(client.get_action_count(&1), 1);
+        assert_eq!(client.get_history_as(&1, &stranger, &10).len(), 1);
+
+        // Toggling back to public restores anonymous reads
+        client.set_history_private(&1, &admin, &true);
+        client.set_history_private(&1, &owner, &false);
+        assert_eq!(client.get_action_count(&1), 1);
+    }
+
+    #[test]
+    fn test_rate_limit_emits_event_on_checked_path() {
+        let env = Env::default();
+        let contract_id = env.register_contract(None, ExecutionHub);
+        let client = ExecutionHubClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+
+        env.mock_all_auths();
+        env.budget().reset_unlimited();
+        client.initialize(&admin);
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+        let action = String::from_str(&env, "test");
+        let params = Bytes::from_array(&env, &[1]);
+        for nonce in 1..=DEFAULT_RATE_LIMIT_OPERATIONS as u64 {
+            client.execute_action(&1, &executor, &action, &params, &nonce);
+        }
+
+        let nonce = DEFAULT_RATE_LIMIT_OPERATIONS as u64 + 1;
+        let result = client.execute_action_checked(&1, &executor, &action, &params, &nonce);
+        assert_eq!(result, None);
+
+        let expected = (
+            contract_id.clone(),
+            (Symbol::new(&env, "rate_limited"), 1u64).into_val(&env),
+            (1u64, 1_000u64).into_val(&env),
+        );
+        assert!(env.events().all().contains(&expected));
+
+        // The panicking variant is unchanged
+        assert!(client
+            .try_execute_action(&1, &executor, &action, &params, &nonce)
+            .is_err());
+    }
+
+    #[test]
+    fn test_list_rules_tracks_register_and_revoke() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let owner = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&Address::generate(&env));
+
+        let data = Bytes::from_array(&env, &[1]);
+        for name in ["alpha", "beta", "gamma"] {
+            client.register_rule(&1, &owner, &String::from_str(&env, name), &data);
+        }
+        // Re-registering overwrites without duplicating the index entry
+        client.register_rule(&1, &owner, &String::from_str(&env, "beta"), &data);
+        client.register_rule(&2, &owner, &String::from_str(&env, "other"), &data);
+        assert_eq!(client.rule_count(&1), 3);
+
+        client.revoke_rule(&1, &owner, &String::from_str(&env, "beta"));
+        client.revoke_rule(&1, &owner, &String::from_str(&env, "missing"));
+        assert_eq!(client.rule_count(&1), 2);
+
+        let names = client.list_rules(&1, &0, &10);
+        assert_eq!(names.len(), 2);
+        assert_eq!(names.get(0).unwrap(), String::from_str(&env, "alpha"));
+        assert_eq!(names.get(1).unwrap(), String::from_str(&env, "gamma"));
+        assert_eq!(client.list_rules(&1, &1, &10).len(), 1);
+        assert_eq!(client.list_rules(&1, &5, &10).len(), 0);
+        assert_eq!(client.rule_count(&2), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Maximum number of rules per agent reached")]
+    fn test_rule_index_is_bounded() {
+        let env = Env::default();
+        env.budget().reset_unlimited();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let owner = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&Address::generate(&env));
+
+        let data = Bytes::new(&env);
+        for i in 0..=MAX_RULES_PER_AGENT {
+            let name = String::from_str(&env, &alloc::format!("rule_{}", i));
+            client.register_rule(&1, &owner, &name, &data);
+        }
+    }
+
+    #[test]
+    fn test_agent_rate_limit_override() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+
+        let default = client.get_agent_rate_limit(&1);
+        assert_eq!(default.max_operations, DEFAULT_RATE_LIMIT_OPERATIONS);
+        assert_eq!(default.window_seconds, DEFAULT_RATE_LIMIT_WINDOW_SECONDS);
+
+        client.set_agent_rate_limit(&admin, &1, &2, &30);
+        let limit = client.get_agent_rate_limit(&1);
+        assert_eq!((limit.max_operations, limit.window_seconds), (2, 30));
+
+        let action = String::from_str(&env, "act");
+        let params = Bytes::new(&env);
+        client.execute_action(&1, &executor, &action, &params, &1);
+        client.execute_action(&1, &executor, &action, &params, &2);
+        assert!(client
+            .try_execute_action(&1, &executor, &action, &params, &3)
+            .is_err());
+
+        // Other agents keep the defaults
+        client.execute_action(&2, &executor, &action, &params, &1);
+        client.execute_action(&2, &executor, &action, &params, &2);
+        client.execute_action(&2, &executor, &action, &params, &3);
+
+        env.ledger().with_mut(|li| li.timestamp += 31);
+        client.execute_action(&1, &executor, &action, &params, &3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rate limit operations out of bounds")]
+    fn test_agent_rate_limit_rejects_zero_operations() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+
+        client.set_agent_rate_limit(&admin, &1, &0, &60);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rate limit window must be positive")]
+    fn test_agent_rate_limit_rejects_zero_window() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
 
-        let elapsed = now.saturating_sub(last_reset);
+        client.set_agent_rate_limit(&admin, &1, &10, &0);
+    }
 
-        let (new_reset, new_count) = if elapsed > window_seconds {
-            (now, 1)
-        } else if count < max_operations {
-            (last_reset, count + 1)
-        } else {
-            panic!("Rate limit exceeded");
-        };
+    #[test]
+    fn test_history_paged_walks_forward_and_clamps() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&Address::generate(&env));
+
+        let params = Bytes::new(&env);
+        for nonce in 1..=5u64 {
+            client.execute_action(
+                &1,
+                &executor,
+                &String::from_str(&env, "act"),
+                &params,
+                &nonce,
+            );
+        }
 
-        let new_rate_data = RateLimitData {
-            last_reset: new_reset,
-            count: new_count,
-        };
+        let page = client.get_history_paged(&1, &1, &2);
+        assert_eq!(page.len(), 2);
+        assert_eq!(page.get(0).unwrap().nonce, 2);
+        assert_eq!(page.get(1).unwrap().nonce, 3);
 
-        env.storage()
-            .instance()
-            .set(&agent_limit_key, &new_rate_data);
+        assert_eq!(client.get_history_paged(&1, &3, &u32::MAX).len(), 2);
+        assert_eq!(client.get_history_paged(&1, &5, &10).len(), 0);
+        assert_eq!(client.get_history_paged(&1, &u32::MAX, &10).len(), 0);
+        assert_eq!(client.get_history_paged(&2, &0, &10).len(), 0);
     }
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-    use soroban_sdk::{testutils::Address as _, Env};
 
     #[test]
-    fn test_initialization() {
+    fn test_bump_nonce_skips_forward() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, ExecutionHub);
-        let client = ExecutionHubClient::new(&env, &contract_id);
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_agent_owner(&admin, &1, &owner);
+
+        let action = String::from_str(&env, "act");
+        let params = Bytes::new(&env);
+        assert_eq!(client.get_action_nonce(&1), 0);
+        client.execute_action(&1, &executor, &action, &params, &3);
+        assert_eq!(client.get_action_nonce(&1), 3);
+
+        client.bump_nonce(&1, &owner, &100);
+        assert_eq!(client.get_action_nonce(&1), 100);
+        let expected = (
+            client.address.clone(),
+            (symbol_short!("nonce_bmp"), 1u64).into_val(&env),
+            (1u64, 3u64, 100u64).into_val(&env),
+        );
+        assert!(env.events().all().contains(&expected));
+        assert!(client
+            .try_execute_action(&1, &executor, &action, &params, &50)
+            .is_err());
+        client.execute_action(&1, &executor, &action, &params, &101);
+    }
 
+    #[test]
+    #[should_panic(expected = "New nonce must be greater than current nonce")]
+    fn test_bump_nonce_rejects_rewind() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
         let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_agent_owner(&admin, &1, &owner);
 
+        client.bump_nonce(&1, &owner, &10);
+        client.bump_nonce(&1, &owner, &10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorized: caller is not agent owner")]
+    fn test_bump_nonce_requires_agent_owner() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
         env.mock_all_auths();
         client.initialize(&admin);
+        client.set_agent_owner(&admin, &1, &Address::generate(&env));
 
-        assert_eq!(client.get_admin(), admin);
-        assert_eq!(client.get_execution_counter(), 0);
+        client.bump_nonce(&1, &Address::generate(&env), &10);
     }
 
     #[test]
-    #[should_panic(expected = "Contract already initialized")]
-    fn test_double_initialization() {
+    fn test_event_topics_carry_agent_id() {
         let env = Env::default();
         let contract_id = env.register_contract(None, ExecutionHub);
         let client = ExecutionHubClient::new(&env, &contract_id);
-
         let admin = Address::generate(&env);
-
+        let owner = Address::generate(&env);
         env.mock_all_auths();
+
         client.initialize(&admin);
-        client.initialize(&admin);
+        let topics = env.events().all().last().unwrap().1;
+        assert_eq!(topics.len(), 2);
+        assert_eq!(
+            topics,
+            (symbol_short!("init"), admin.clone()).into_val(&env)
+        );
+
+        let rule_name = String::from_str(&env, "rule");
+        client.register_rule(&7, &owner, &rule_name, &Bytes::new(&env));
+        let topics = env.events().all().last().unwrap().1;
+        assert_eq!(topics, (symbol_short!("rule_reg"), 7u64).into_val(&env));
+
+        client.execute_action(
+            &7,
+            &owner,
+            &String::from_str(&env, "act"),
+            &Bytes::new(&env),
+            &1,
+        );
+        let (_, topics, data) = env.events().all().last().unwrap();
+        assert_eq!(topics.len(), 2);
+        assert_eq!(topics, (symbol_short!("act_exec"), 7u64).into_val(&env));
+        // The payload still leads with the execution and agent IDs
+        let (execution_id, agent_id, _, _, _, nonce): (u64, u64, String, Address, u64, u64) =
+            data.into_val(&env);
+        assert_eq!((execution_id, agent_id, nonce), (1, 7, 1));
+
+        client.revoke_rule(&7, &owner, &rule_name);
+        let topics = env.events().all().last().unwrap().1;
+        assert_eq!(topics, (symbol_short!("rule_rev"), 7u64).into_val(&env));
     }
 
     #[test]
-    fn test_execution_counter_increment() {
+    fn test_execution_hash_round_trip() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, ExecutionHub);
-        let client = ExecutionHubClient::new(&env, &contract_id);
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&Address::generate(&env));
+
+        let action = String::from_str(&env, "trade");
+        let params = Bytes::from_array(&env, &[9, 8, 7]);
+        let execution_id = client.execute_action(&1, &executor, &action, &params, &4);
+
+        let expected = client.compute_execution_hash(&1, &action, &params, &4);
+        assert_eq!(expected.len(), 32);
+        let receipt = client.get_execution_receipt(&execution_id).unwrap();
+        assert_eq!(receipt.execution_hash, expected);
+        assert_eq!(receipt.executor, executor);
+        assert!(client.verify_execution_hash(&execution_id, &expected));
+
+        let other = client.compute_execution_hash(&1, &action, &params, &5);
+        assert_ne!(other, expected);
+        assert!(!client.verify_execution_hash(&execution_id, &other));
+        assert!(!client.verify_execution_hash(&99, &expected));
+    }
 
+    #[test]
+    fn test_history_cap_can_be_lowered_and_raised() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
         let admin = Address::generate(&env);
         let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+        assert_eq!(client.get_history_cap(), MAX_HISTORY_SIZE);
+
+        let action = String::from_str(&env, "act");
+        let params = Bytes::new(&env);
+        client.set_history_cap(&admin, &2);
+        client.execute_action(&1, &executor, &action, &params, &1);
+        client.execute_action(&1, &executor, &action, &params, &2);
+        assert!(client
+            .try_execute_action(&1, &executor, &action, &params, &3)
+            .is_err());
+
+        client.set_history_cap(&admin, &3);
+        client.execute_action(&1, &executor, &action, &params, &3);
+        assert_eq!(client.get_action_count(&1), 3);
+    }
 
+    #[test]
+    #[should_panic(expected = "History cap out of bounds")]
+    fn test_history_cap_above_maximum_rejected() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
         env.mock_all_auths();
         client.initialize(&admin);
 
-        let action = String::from_str(&env, "test_action");
-        let params = Bytes::from_array(&env, &[1, 2, 3]);
+        client.set_history_cap(&admin, &(MAX_HISTORY_SIZE + 1));
+    }
 
-        let exec_id_1 = client.execute_action(&1, &executor, &action, &params, &1);
-        assert_eq!(exec_id_1, 1);
-        assert_eq!(client.get_execution_counter(), 1);
+    #[test]
+    fn test_pause_blocks_writes_but_not_reads() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
 
-        let exec_id_2 = client.execute_action(&1, &executor, &action, &params, &2);
-        assert_eq!(exec_id_2, 2);
-        assert_eq!(client.get_execution_counter(), 2);
+        let action = String::from_str(&env, "act");
+        let rule_name = String::from_str(&env, "rule");
+        let params = Bytes::new(&env);
+        let execution_id = client.execute_action(&1, &owner, &action, &params, &1);
+        client.register_rule(&1, &owner, &rule_name, &params);
+
+        client.set_paused(&admin, &true);
+        assert!(client.is_paused());
+        assert!(client
+            .try_execute_action(&1, &owner, &action, &params, &2)
+            .is_err());
+        assert!(client
+            .try_register_rule(&1, &owner, &String::from_str(&env, "other"), &params)
+            .is_err());
+        assert!(client.try_revoke_rule(&1, &owner, &rule_name).is_err());
+
+        assert_eq!(client.get_history(&1, &10).len(), 1);
+        assert!(client.get_execution_receipt(&execution_id).is_some());
+        assert!(client.get_rule(&1, &rule_name).is_some());
+
+        client.set_paused(&admin, &false);
+        assert!(!client.is_paused());
+        client.execute_action(&1, &owner, &action, &params, &2);
     }
 
     #[test]
-    fn test_register_and_get_rule() {
+    #[should_panic(expected = "Contract is paused")]
+    fn test_execute_action_rejected_while_paused() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, ExecutionHub);
-        let client = ExecutionHubClient::new(&env, &contract_id);
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_paused(&admin, &true);
+
+        client.execute_action(
+            &1,
+            &Address::generate(&env),
+            &String::from_str(&env, "act"),
+            &Bytes::new(&env),
+            &1,
+        );
+    }
 
+    #[test]
+    fn test_is_authorized_executor_cases() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
         let admin = Address::generate(&env);
         let owner = Address::generate(&env);
-
+        let listed = Address::generate(&env);
+        let delegate = Address::generate(&env);
+        let stranger = Address::generate(&env);
         env.mock_all_auths();
         client.initialize(&admin);
+        client.set_agent_owner(&admin, &1, &owner);
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
 
-        let rule_name = String::from_str(&env, "my_rule");
-        let rule_data = Bytes::from_array(&env, &[10, 20, 30]);
+        // No allow-list yet: the agent is open
+        assert!(client.is_authorized_executor(&1, &stranger));
 
-        client.register_rule(&1, &owner, &rule_name, &rule_data);
+        client.register_agent_executor(&1, &owner, &listed);
+        client.delegate_executor(&1, &owner, &delegate, &2_000);
 
-        let retrieved = client.get_rule(&1, &rule_name);
-        assert!(retrieved.is_some());
-        assert_eq!(retrieved.unwrap(), rule_data);
+        assert!(client.is_authorized_executor(&1, &owner));
+        assert!(client.is_authorized_executor(&1, &listed));
+        assert!(client.is_authorized_executor(&1, &delegate));
+        assert!(!client.is_authorized_executor(&1, &stranger));
+
+        env.ledger().with_mut(|li| li.timestamp = 2_000);
+        assert_eq!(client.get_delegation(&1, &delegate), Some(2_000));
+        assert!(!client.is_authorized_executor(&1, &delegate));
+        assert!(client.is_authorized_executor(&1, &listed));
     }
 
     #[test]
-    #[should_panic(expected = "Invalid nonce")]
-    fn test_replay_protection() {
+    fn test_executor_action_counts_are_independent() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, ExecutionHub);
-        let client = ExecutionHubClient::new(&env, &contract_id);
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&Address::generate(&env));
+
+        let action = String::from_str(&env, "act");
+        let params = Bytes::new(&env);
+        client.execute_action(&1, &first, &action, &params, &1);
+        client.execute_action(&1, &first, &action, &params, &2);
+        client.execute_action(&1, &second, &action, &params, &3);
+        client.execute_action(&2, &first, &action, &params, &1);
+
+        assert_eq!(client.get_executor_action_count(&1, &first), 2);
+        assert_eq!(client.get_executor_action_count(&1, &second), 1);
+        assert_eq!(client.get_executor_action_count(&2, &first), 1);
+        assert_eq!(client.get_executor_action_count(&2, &second), 0);
+    }
 
+    #[test]
+    fn test_prune_receipts_before_keeps_history() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
         let admin = Address::generate(&env);
         let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+
+        let action = String::from_str(&env, "act");
+        let params = Bytes::new(&env);
+        for (nonce, timestamp) in [(1u64, 100u64), (2, 200), (3, 300)] {
+            env.ledger().with_mut(|li| li.timestamp = timestamp);
+            client.execute_action(&1, &executor, &action, &params, &nonce);
+        }
+        client.execute_action(&2, &executor, &action, &params, &1);
+
+        assert_eq!(client.prune_receipts_before(&admin, &1, &300), 2);
+        assert!(client.get_execution_receipt(&1).is_none());
+        assert!(client.get_execution_receipt(&2).is_none());
+        assert!(client.get_execution_receipt(&3).is_some());
+        assert!(client.get_execution_receipt(&4).is_some());
+        assert_eq!(client.get_history(&1, &10).len(), 3);
+
+        assert_eq!(client.prune_receipts_before(&admin, &1, &300), 0);
+        assert_eq!(client.prune_receipts_before(&admin, &1, &u64::MAX), 1);
+        assert!(client.get_execution_receipt(&4).is_some());
+    }
 
+    #[test]
+    fn test_prune_receipts_before_skips_missing_receipts() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
         env.mock_all_auths();
         client.initialize(&admin);
 
-        let action = String::from_str(&env, "test");
-        let params = Bytes::from_array(&env, &[1]);
+        let action = String::from_str(&env, "act");
+        let params = Bytes::new(&env);
+        for (nonce, timestamp) in [(1u64, 100u64), (2, 200), (3, 300)] {
+            env.ledger().with_mut(|li| li.timestamp = timestamp);
+            client.execute_action(&1, &executor, &action, &params, &nonce);
+        }
 
-        client.execute_action(&1, &executor, &action, &params, &1);
-        client.execute_action(&1, &executor, &action, &params, &1);
+        // Leave a gap in the receipt index
+        env.as_contract(&client.address, || {
+            env.storage()
+                .instance()
+                .remove(&(symbol_short!("receipt"), 2u64));
+        });
+
+        assert_eq!(client.prune_receipts_before(&admin, &1, &u64::MAX), 2);
+        assert_eq!(client.prune_receipts_before(&admin, &1, &u64::MAX), 0);
     }
 
     #[test]
-    fn test_get_history() {
+    fn test_execute_action_enforces_executor_set() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, ExecutionHub);
-        let client = ExecutionHubClient::new(&env, &contract_id);
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let executor = Address::generate(&env);
+        let stranger = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_agent_owner(&admin, &5, &owner);
+
+        let action = String::from_str(&env, "act");
+        let params = Bytes::new(&env);
+        // Empty executor set: open to anyone
+        client.execute_action(&5, &stranger, &action, &params, &1);
+
+        client.register_agent_executor(&5, &owner, &executor);
+        client.execute_action(&5, &executor, &action, &params, &2);
+        client.execute_action(&5, &owner, &action, &params, &3);
+        assert!(client
+            .try_execute_action(&5, &stranger, &action, &params, &4)
+            .is_err());
+
+        client.revoke_agent_executor(&5, &owner, &executor);
+        assert_eq!(client.get_agent_executors(&5).len(), 0);
+        client.execute_action(&5, &stranger, &action, &params, &4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Executor not authorized for agent")]
+    fn test_unregistered_executor_rejected() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_agent_owner(&admin, &5, &owner);
+        client.register_agent_executor(&5, &owner, &Address::generate(&env));
+
+        client.execute_action(
+            &5,
+            &Address::generate(&env),
+            &String::from_str(&env, "act"),
+            &Bytes::new(&env),
+            &1,
+        );
+    }
 
+    #[test]
+    #[should_panic(expected = "Unauthorized: caller is not agent owner")]
+    fn test_revoke_agent_executor_requires_owner() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
         let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
         let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_agent_owner(&admin, &5, &owner);
+        client.register_agent_executor(&5, &owner, &executor);
+
+        client.revoke_agent_executor(&5, &executor, &executor);
+    }
 
+    #[test]
+    fn test_strict_mode_rejects_unknown_agents() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let nft =
+            agent_nft::AgentNFTClient::new(&env, &env.register_contract(None, agent_nft::AgentNFT));
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
         env.mock_all_auths();
         client.initialize(&admin);
+        nft.init_contract(&admin);
+        nft.mint_agent(
+            &1,
+            &admin,
+            &admin,
+            &String::from_str(&env, "ipfs://agent-1"),
+            &0,
+        );
 
-        let action = String::from_str(&env, "test_action");
-        let params = Bytes::from_array(&env, &[1]);
+        let action = String::from_str(&env, "act");
+        let params = Bytes::new(&env);
+        // Permissive by default
+        client.execute_action(&2, &executor, &action, &params, &1);
+
+        client.set_agent_nft(&admin, &nft.address);
+        client.set_strict_mode(&admin, &true);
+        assert!(client.is_strict_mode());
+        assert_eq!(
+            client.execute_action(&1, &executor, &action, &params, &1),
+            2
+        );
+        assert_eq!(
+            client
+                .try_execute_action(&2, &executor, &action, &params, &2)
+                .err(),
+            Some(Ok(ContractError::AgentNotFound.into()))
+        );
+    }
 
-        client.execute_action(&1, &executor, &action, &params, &1);
-        client.execute_action(&1, &executor, &action, &params, &2);
+    #[test]
+    fn test_receipts_filtered_by_action() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&Address::generate(&env));
+
+        let transfer = String::from_str(&env, "transfer");
+        let swap = String::from_str(&env, "swap");
+        let params = Bytes::new(&env);
+        for (nonce, action) in [
+            (1u64, &transfer),
+            (2, &swap),
+            (3, &transfer),
+            (4, &transfer),
+        ] {
+            client.execute_action(&1, &executor, action, &params, &nonce);
+        }
 
-        let history = client.get_history(&1, &10);
-        assert_eq!(history.len(), 2);
-        assert_eq!(client.get_action_count(&1), 2);
+        let transfers = client.get_agent_receipts_by_action(&1, &transfer, &10);
+        assert_eq!(transfers.len(), 3);
+        assert_eq!(transfers.get(0).unwrap().execution_id, 1);
+        assert_eq!(transfers.get(2).unwrap().execution_id, 4);
+
+        // The limit keeps the most recent matches
+        let latest = client.get_agent_receipts_by_action(&1, &transfer, &2);
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest.get(0).unwrap().execution_id, 3);
+
+        assert_eq!(
+            client
+                .get_agent_receipts_by_action(&1, &swap, &u32::MAX)
+                .len(),
+            1
+        );
+        let none = String::from_str(&env, "none");
+        assert_eq!(client.get_agent_receipts_by_action(&1, &none, &10).len(), 0);
     }
 
     #[test]
-    fn test_admin_transfer() {
+    fn test_failed_execution_records_retry_hint() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, ExecutionHub);
-        let client = ExecutionHubClient::new(&env, &contract_id);
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&Address::generate(&env));
+        env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+        let action = String::from_str(&env, "act");
+        let params = Bytes::new(&env);
+        let failed = client.execute_action(&1, &executor, &action, &params, &1);
+        let succeeded = client.execute_action(&1, &executor, &action, &params, &2);
+        let pending = client.get_execution_receipt(&failed).unwrap();
+        assert_eq!(pending.status, ExecutionStatus::Pending);
+
+        client.finalize_execution(&failed, &executor, &false, &Some(300));
+        let receipt = client.get_execution_receipt(&failed).unwrap();
+        assert_eq!(receipt.status, ExecutionStatus::Failed);
+        assert_eq!(receipt.retry_after, Some(1_300));
+
+        client.finalize_execution(&succeeded, &executor, &true, &None);
+        let receipt = client.get_execution_receipt(&succeeded).unwrap();
+        assert_eq!(receipt.status, ExecutionStatus::Succeeded);
+        assert_eq!(receipt.retry_after, None);
+
+        assert!(client
+            .try_finalize_execution(&failed, &executor, &false, &None)
+            .is_err());
+    }
 
-        let admin1 = Address::generate(&env);
-        let admin2 = Address::generate(&env);
+    #[test]
+    #[should_panic(expected = "Retry delay exceeds maximum allowed")]
+    fn test_retry_hint_is_bounded() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&Address::generate(&env));
+
+        let execution_id = client.execute_action(
+            &1,
+            &executor,
+            &String::from_str(&env, "act"),
+            &Bytes::new(&env),
+            &1,
+        );
+        client.finalize_execution(
+            &execution_id,
+            &executor,
+            &false,
+            &Some(MAX_RETRY_DELAY_SECONDS + 1),
+        );
+    }
 
+    #[test]
+    fn test_config_snapshot_tracks_admin_changes() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
         env.mock_all_auths();
-        client.initialize(&admin1);
+        client.initialize(&admin);
 
-        client.transfer_admin(&admin1, &admin2);
-        assert_eq!(client.get_admin(), admin2);
+        let config = client.get_config();
+        assert_eq!(config.admin, admin);
+        assert!(!config.paused);
+        assert!(!config.strict_mode);
+        assert_eq!(config.history_cap, MAX_HISTORY_SIZE);
+        assert_eq!(config.idempotency_window, 0);
+        assert_eq!(config.oracle, None);
+        assert_eq!(config.agent_nft, None);
+        assert!(!config.batch_summary_only);
+
+        let oracle = Address::generate(&env);
+        let agent_nft = Address::generate(&env);
+        client.set_paused(&admin, &true);
+        client.set_history_cap(&admin, &25);
+        client.set_idempotency_window(&admin, &600);
+        client.set_oracle(&admin, &oracle);
+        client.set_agent_nft(&admin, &agent_nft);
+        client.set_strict_mode(&admin, &true);
+        client.set_batch_summary_only(&admin, &true);
+
+        let config = client.get_config();
+        assert!(config.batch_summary_only);
+        assert!(config.paused);
+        assert!(config.strict_mode);
+        assert_eq!(config.history_cap, 25);
+        assert_eq!(config.idempotency_window, 600);
+        assert_eq!(config.oracle, Some(oracle));
+        assert_eq!(config.agent_nft, Some(agent_nft));
+    }
+
+    fn batch_of(env: &Env, size: u32) -> (Vec<String>, Vec<Bytes>) {
+        let mut actions = Vec::new(env);
+        let mut parameters = Vec::new(env);
+        for _ in 0..size {
+            actions.push_back(String::from_str(env, "act"));
+            parameters.push_back(Bytes::new(env));
+        }
+        (actions, parameters)
     }
 
     #[test]
-    fn test_rate_limiting() {
+    fn test_batch_emits_one_summary_event() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, ExecutionHub);
-        let client = ExecutionHubClient::new(&env, &contract_id);
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
+        let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.execute_action(
+            &7,
+            &executor,
+            &String::from_str(&env, "act"),
+            &Bytes::new(&env),
+            &1,
+        );
+
+        let (actions, parameters) = batch_of(&env, 3);
+        let ids = client.execute_batch(&7, &executor, &actions, &parameters, &2);
+        assert_eq!(ids, Vec::from_array(&env, [2u64, 3, 4]));
+
+        let summary_topics: Vec<soroban_sdk::Val> =
+            (Symbol::new(&env, "batch_exec"), 7u64).into_val(&env);
+        let events = env.events().all();
+        let summaries: alloc::vec::Vec<_> = events
+            .iter()
+            .filter(|(_, topics, _)| *topics == summary_topics)
+            .collect();
+        assert_eq!(summaries.len(), 1);
+        let (count, first, last): (u32, u64, u64) = summaries[0].2.into_val(&env);
+        assert_eq!((count, first, last), (3, 2, 4));
+
+        // Per-action events are still emitted by default (plus the earlier single action)
+        let item_topics: Vec<soroban_sdk::Val> = (symbol_short!("act_exec"), 7u64).into_val(&env);
+        assert_eq!(
+            events
+                .iter()
+                .filter(|(_, topics, _)| *topics == item_topics)
+                .count(),
+            4
+        );
+        assert_eq!(client.get_action_nonce(&7), 4);
+    }
 
+    #[test]
+    fn test_batch_summary_only_drops_per_action_events() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
         let admin = Address::generate(&env);
         let executor = Address::generate(&env);
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_batch_summary_only(&admin, &true);
+
+        let (actions, parameters) = batch_of(&env, 2);
+        client.execute_batch(&9, &executor, &actions, &parameters, &1);
+
+        let item_topics: Vec<soroban_sdk::Val> = (symbol_short!("act_exec"), 9u64).into_val(&env);
+        let events = env.events().all();
+        assert!(!events.iter().any(|(_, topics, _)| topics == item_topics));
+        let (_, topics, data) = events.last().unwrap();
+        assert_eq!(
+            topics,
+            (Symbol::new(&env, "batch_exec"), 9u64).into_val(&env)
+        );
+        let (count, first, last): (u32, u64, u64) = data.into_val(&env);
+        assert_eq!((count, first, last), (2, 1, 2));
+    }
 
+    #[test]
+    #[should_panic(expected = "Actions and parameters length mismatch")]
+    fn test_batch_rejects_mismatched_lengths() {
+        let env = Env::default();
+        let client = ExecutionHubClient::new(&env, &env.register_contract(None, ExecutionHub));
+        let admin = Address::generate(&env);
         env.mock_all_auths();
         client.initialize(&admin);
 
-        let action = String::from_str(&env, "test");
-        let params = Bytes::from_array(&env, &[1]);
+        let (actions, _) = batch_of(&env, 2);
+        let (_, parameters) = batch_of(&env, 1);
+        client.execute_batch(&1, &admin, &actions, &parameters, &1);
+    }
 
-        for i in 1..=10 {
-            client.execute_action(&1, &executor, &action, &params, &i);
+    mod mock_oracle {
+        use soroban_sdk::{contract, contractimpl, Address, Env, Symbol};
+        use stellai_lib::OracleData;
+
+        #[contract]
+        pub struct MockOracle;
+
+        #[contractimpl]
+        impl MockOracle {
+            pub fn set_value(env: Env, key: Symbol, value: i128, provider: Address) {
+                let data = OracleData {
+                    key: key.clone(),
+                    value,
+                    timestamp: env.ledger().timestamp(),
+                    provider,
+                    signature: None,
+                    source: None,
+                };
+                env.storage().instance().set(&key, &data);
+            }
+
+            pub fn get_data(env: Env, key: Symbol) -> Option<OracleData> {
+                env.storage().instance().get(&key)
+            }
         }
+    }
 
-        let result = client.execute_action(&1, &executor, &action, &params, &11);
-        assert!(result > 0);
+    fn setup_conditional_execution(
+        env: &Env,
+    ) -> (
+        ExecutionHubClient<'_>,
+        mock_oracle::MockOracleClient<'_>,
+        Address,
+    ) {
+        let client = ExecutionHubClient::new(env, &env.register_contract(None, ExecutionHub));
+        let oracle = mock_oracle::MockOracleClient::new(
+            env,
+            &env.register_contract(None, mock_oracle::MockOracle),
+        );
+        let admin = Address::generate(env);
+
+        env.mock_all_auths();
+        client.initialize(&admin);
+        client.set_oracle(&admin, &oracle.address);
+
+        (client, oracle, admin)
+    }
+
+    #[test]
+    fn test_execute_action_if_runs_only_when_condition_holds() {
+        let env = Env::default();
+        let (client, oracle, admin) = setup_conditional_execution(&env);
+        let executor = Address::generate(&env);
+        let key = Symbol::new(&env, "XLM_USD");
+        let action = String::from_str(&env, "buy");
+        let params = Bytes::new(&env);
+
+        oracle.set_value(&key, &150, &admin);
+        let executed = client.execute_action_if(
+            &1,
+            &executor,
+            &action,
+            &params,
+            &1,
+            &key,
+            &Comparator::GreaterThan,
+            &100,
+            &60,
+        );
+        assert_eq!(executed, Some(1));
+        assert_eq!(client.get_action_count(&1), 1);
+
+        oracle.set_value(&key, &90, &admin);
+        let skipped = client.execute_action_if(
+            &1,
+            &executor,
+            &action,
+            &params,
+            &2,
+            &key,
+            &Comparator::GreaterThan,
+            &100,
+            &60,
+        );
+        assert_eq!(skipped, None);
+        assert_eq!(client.get_action_count(&1), 1);
+        assert_eq!(client.get_execution_counter(), 1);
+
+        // The skipped nonce was not consumed
+        let executed = client.execute_action_if(
+            &1,
+            &executor,
+            &action,
+            &params,
+            &2,
+            &key,
+            &Comparator::LessOrEqual,
+            &100,
+            &60,
+        );
+        assert_eq!(executed, Some(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Oracle data is stale")]
+    fn test_execute_action_if_rejects_stale_oracle_data() {
+        let env = Env::default();
+        let (client, oracle, admin) = setup_conditional_execution(&env);
+        let executor = Address::generate(&env);
+        let key = Symbol::new(&env, "XLM_USD");
+
+        oracle.set_value(&key, &150, &admin);
+        env.ledger().with_mut(|li| li.timestamp += 61);
+        client.execute_action_if(
+            &1,
+            &executor,
+            &String::from_str(&env, "buy"),
+            &Bytes::new(&env),
+            &1,
+            &key,
+            &Comparator::GreaterThan,
+            &100,
+            &60,
+        );
     }
 }
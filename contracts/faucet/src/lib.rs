@@ -1,11 +1,14 @@
 #![no_std]
 extern crate alloc;
 
+mod tests;
+
 // use soroban_sdk::{contract, contractimpl, Address, Env, String, Symbol, Map};
-use soroban_sdk::{contract, contractimpl, Address, Env, Symbol};
+use soroban_sdk::{contract, contractimpl, Address, Env, String, Symbol, Vec};
 use stellai_lib::{
-    ADMIN_KEY, CLAIM_COOLDOWN_KEY, DEFAULT_COOLDOWN_SECONDS, DEFAULT_MAX_CLAIMS,
-    MAX_CLAIMS_PER_PERIOD_KEY, TESTNET_FLAG_KEY,
+    interfaces::AgentNftClient, ADMIN_KEY, AGENT_NFT_KEY, CLAIM_COOLDOWN_KEY,
+    DEFAULT_COOLDOWN_SECONDS, DEFAULT_MAX_CLAIMS, MAX_BATCH_SIZE, MAX_CLAIMS_PER_PERIOD_KEY,
+    TESTNET_FLAG_KEY,
 };
 
 #[contract]
@@ -40,6 +43,11 @@ impl Faucet {
             .set(&Symbol::new(&env, TESTNET_FLAG_KEY), &testnet_only);
     }
 
+    /// Whether the contract has been initialized with an admin
+    pub fn is_initialized(env: Env) -> bool {
+        env.storage().instance().has(&Symbol::new(&env, ADMIN_KEY))
+    }
+
     /// Verify caller is admin
     fn verify_admin(env: &Env, caller: &Address) {
         let admin: Address = env
@@ -75,7 +83,16 @@ impl Faucet {
             panic!("Address is not eligible for faucet claim at this time");
         }
 
-        let agent_id = 1u64; // Placeholder ID
+        // Mint before touching claim state so a failed mint never consumes the
+        // claimer's cooldown or claim count
+        let agent_id = match env
+            .storage()
+            .instance()
+            .get::<_, Address>(&Symbol::new(&env, AGENT_NFT_KEY))
+        {
+            Some(agent_nft) => Self::mint_for(&env, &agent_nft, &claimer),
+            None => 1u64, // Placeholder ID
+        };
         let now = env.ledger().timestamp();
 
         // Store last claim time using tuple key
@@ -92,6 +109,29 @@ impl Faucet {
         agent_id
     }
 
+    /// Mint the next free agent ID on the AgentNFT contract directly to `claimer`.
+    /// The faucet must be approved for all of the AgentNFT admin's agents.
+    fn mint_for(env: &Env, agent_nft: &Address, claimer: &Address) -> u64 {
+        let nft = AgentNftClient::new(env, agent_nft);
+        let agent_id = match nft.try_next_agent_id() {
+            Ok(Ok(id)) => id,
+            _ => panic!("Agent mint failed"),
+        };
+
+        let minted = nft.try_mint_agent_for(
+            &env.current_contract_address(),
+            &(agent_id as u128),
+            claimer,
+            &String::from_str(env, ""),
+            &0u32,
+        );
+        if !matches!(minted, Ok(Ok(()))) {
+            panic!("Agent mint failed");
+        }
+
+        agent_id
+    }
+
     /// Check if an address is eligible for a faucet claim
     pub fn check_eligibility(env: Env, address: Address) -> bool {
         let cooldown: u64 = env
@@ -129,6 +169,20 @@ impl Faucet {
         }
     }
 
+    /// Check eligibility for several addresses at once; results are positional
+    pub fn check_eligibility_batch(env: Env, addresses: Vec<Address>) -> Vec<bool> {
+        if addresses.len() > MAX_BATCH_SIZE {
+            panic!("Batch size exceeds maximum allowed");
+        }
+
+        let mut result = Vec::new(&env);
+        for address in addresses.iter() {
+            result.push_back(Self::check_eligibility(env.clone(), address));
+        }
+
+        result
+    }
+
     /// Admin function: Set faucet parameters
     pub fn set_parameters(
         env: Env,
@@ -162,6 +216,16 @@ impl Faucet {
         );
     }
 
+    /// Admin function: Set the AgentNFT contract claims are minted on
+    pub fn set_agent_nft(env: Env, admin: Address, agent_nft: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, AGENT_NFT_KEY), &agent_nft);
+    }
+
     /// Get current faucet parameters
     pub fn get_parameters(env: Env) -> (u64, u32) {
         let cooldown: u64 = env
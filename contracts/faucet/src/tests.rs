@@ -0,0 +1,107 @@
+#![cfg(test)]
+
+use crate::{Faucet, FaucetClient};
+use agent_nft::{AgentNFT, AgentNFTClient};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{vec, Address, Env, Vec};
+
+fn setup_faucet(env: &Env) -> (FaucetClient<'_>, Address) {
+    let contract_id = env.register_contract(None, Faucet);
+    let client = FaucetClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+
+    env.mock_all_auths();
+    client.init_faucet(&admin, &true);
+
+    (client, admin)
+}
+
+fn setup_agent_nft<'a>(
+    env: &Env,
+    client: &FaucetClient<'a>,
+    admin: &Address,
+) -> (AgentNFTClient<'a>, Address) {
+    let nft_id = env.register_contract(None, AgentNFT);
+    let nft = AgentNFTClient::new(env, &nft_id);
+    let nft_admin = Address::generate(env);
+    nft.init_contract(&nft_admin);
+    client.set_agent_nft(admin, &nft_id);
+
+    (nft, nft_admin)
+}
+
+#[test]
+fn test_claim_mints_agent_to_claimer() {
+    let env = Env::default();
+    let (client, admin) = setup_faucet(&env);
+    let (nft, nft_admin) = setup_agent_nft(&env, &client, &admin);
+    nft.set_approval_for_all(&nft_admin, &client.address, &true);
+
+    let claimer = Address::generate(&env);
+    let agent_id = client.claim_test_agent(&claimer);
+
+    assert_eq!(nft.get_agent_owner(&agent_id), claimer);
+    assert!(!client.check_eligibility(&claimer));
+}
+
+#[test]
+fn test_failed_mint_does_not_consume_claim() {
+    let env = Env::default();
+    let (client, admin) = setup_faucet(&env);
+    // The faucet is never approved as an operator, so every mint is rejected
+    let (nft, nft_admin) = setup_agent_nft(&env, &client, &admin);
+
+    let claimer = Address::generate(&env);
+    assert!(client.try_claim_test_agent(&claimer).is_err());
+
+    assert!(client.check_eligibility(&claimer));
+    assert_eq!(client.get_remaining_cooldown(&claimer), 0);
+
+    // Once minting works again the same claimer can still claim
+    nft.set_approval_for_all(&nft_admin, &client.address, &true);
+    let agent_id = client.claim_test_agent(&claimer);
+    assert_eq!(nft.get_agent_owner(&agent_id), claimer);
+}
+
+#[test]
+fn test_check_eligibility_batch_is_positional() {
+    let env = Env::default();
+    let (client, _admin) = setup_faucet(&env);
+
+    let claimed = Address::generate(&env);
+    let fresh = Address::generate(&env);
+    let other_claimed = Address::generate(&env);
+    client.claim_test_agent(&claimed);
+    client.claim_test_agent(&other_claimed);
+
+    let result = client.check_eligibility_batch(&vec![
+        &env,
+        fresh.clone(),
+        claimed.clone(),
+        Address::generate(&env),
+        other_claimed.clone(),
+    ]);
+    assert_eq!(result, vec![&env, true, false, true, false]);
+}
+
+#[test]
+fn test_check_eligibility_batch_empty() {
+    let env = Env::default();
+    let (client, _admin) = setup_faucet(&env);
+
+    assert_eq!(client.check_eligibility_batch(&Vec::new(&env)).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Batch size exceeds maximum allowed")]
+fn test_check_eligibility_batch_rejects_oversized_batch() {
+    let env = Env::default();
+    let (client, _admin) = setup_faucet(&env);
+
+    let mut addresses = Vec::new(&env);
+    for _ in 0..51 {
+        addresses.push_back(Address::generate(&env));
+    }
+    client.check_eligibility_batch(&addresses);
+}
@@ -1,7 +1,31 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, Address, Env, Symbol};
-use stellai_lib::{Listing, ListingType, RoyaltyInfo, ADMIN_KEY, LISTING_COUNTER_KEY};
+mod tests;
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, panic_with_error, token, Address, Env, Symbol, Vec,
+};
+use stellai_lib::{
+    errors::ContractError, interfaces::AgentNftClient, Auction, Listing, ListingPage, ListingType,
+    RoyaltyInfo, ADMIN_KEY, AGENT_NFT_KEY, LISTING_COUNTER_KEY, MAX_HISTORY_QUERY_LIMIT,
+    MAX_ROYALTY_FEE, MAX_SETTLEMENT_GRACE_SECONDS, PAYMENT_TOKEN_KEY, PRICE_LOWER_BOUND,
+    PRICE_UPPER_BOUND,
+};
+
+/// Snapshot of every admin-set parameter (see `get_config`)
+#[derive(Clone)]
+#[contracttype]
+pub struct ContractConfig {
+    pub admin: Address,
+    pub payment_token: Option<Address>,
+    pub price_floors: Vec<i128>, // indexed by ListingType
+    pub settlement_grace: u64,
+    pub whale_threshold: Option<i128>,
+    pub default_royalty_recipient: Option<Address>,
+    pub default_royalty_fee: u32,
+    pub buyer_fee_recipient: Option<Address>,
+    pub buyer_fee: u32,
+}
 
 #[contract]
 pub struct Marketplace;
@@ -27,7 +51,133 @@ impl Marketplace {
             .set(&Symbol::new(&env, LISTING_COUNTER_KEY), &0u64);
     }
 
-    /// Create a new listing
+    /// Whether the contract has been initialized with an admin
+    pub fn is_initialized(env: Env) -> bool {
+        env.storage().instance().has(&Symbol::new(&env, ADMIN_KEY))
+    }
+
+    /// Verify caller is admin
+    fn verify_admin(env: &Env, caller: &Address) {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(env, ADMIN_KEY))
+            .expect("Admin not set");
+
+        if caller != &admin {
+            panic!("Unauthorized: caller is not admin");
+        }
+    }
+
+    /// Get all admin-set parameters in one call, for diagnostics
+    pub fn get_config(env: Env) -> ContractConfig {
+        let mut price_floors = Vec::new(&env);
+        for listing_type in [ListingType::Sale, ListingType::Lease, ListingType::Auction] {
+            price_floors.push_back(Self::get_price_floor(env.clone(), listing_type.to_u32()));
+        }
+
+        let default_royalty: Option<RoyaltyInfo> = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, "default_royalty"));
+        let buyer_fee = Self::get_buyer_fee(env.clone());
+
+        ContractConfig {
+            admin: env
+                .storage()
+                .instance()
+                .get(&Symbol::new(&env, ADMIN_KEY))
+                .expect("Admin not set"),
+            payment_token: Self::get_payment_token(env.clone()),
+            price_floors,
+            settlement_grace: Self::get_settlement_grace(env.clone()),
+            whale_threshold: Self::get_whale_threshold(env.clone()),
+            default_royalty_fee: default_royalty.as_ref().map_or(0, |royalty| royalty.fee),
+            default_royalty_recipient: default_royalty.map(|royalty| royalty.recipient),
+            buyer_fee: buyer_fee.as_ref().map_or(0, |(_, fee)| *fee),
+            buyer_fee_recipient: buyer_fee.map(|(recipient, _)| recipient),
+        }
+    }
+
+    /// Set the token used to pay for listings (admin only)
+    pub fn set_payment_token(env: Env, admin: Address, token: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, PAYMENT_TOKEN_KEY), &token);
+
+        env.events()
+            .publish((Symbol::new(&env, "payment_token_set"),), token);
+    }
+
+    /// Get the configured payment token
+    pub fn get_payment_token(env: Env) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, PAYMENT_TOKEN_KEY))
+    }
+
+    fn payment_token_client(env: &Env) -> token::Client<'_> {
+        let payment_token: Address = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(env, PAYMENT_TOKEN_KEY))
+            .expect("Payment token not configured");
+        token::Client::new(env, &payment_token)
+    }
+
+    /// Set the AgentNFT contract whose recorded creators gate `set_royalty` (admin only)
+    pub fn set_agent_nft(env: Env, admin: Address, agent_nft: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, AGENT_NFT_KEY), &agent_nft);
+
+        env.events()
+            .publish((Symbol::new(&env, "agent_nft_set"),), agent_nft);
+    }
+
+    /// Get the configured AgentNFT contract
+    pub fn get_agent_nft(env: Env) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, AGENT_NFT_KEY))
+    }
+
+    /// Set the minimum listing price for a listing type (admin only, 0 disables)
+    pub fn set_price_floor(env: Env, admin: Address, listing_type: u32, floor: i128) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let listing_type = ListingType::try_from_u32(listing_type)
+            .unwrap_or_else(|_| panic!("Invalid listing type"));
+        if !(PRICE_LOWER_BOUND..=PRICE_UPPER_BOUND).contains(&floor) {
+            panic!("Price floor out of bounds");
+        }
+
+        let floor_key = (Symbol::new(&env, "price_floor"), listing_type.to_u32());
+        env.storage().instance().set(&floor_key, &floor);
+
+        env.events().publish(
+            (Symbol::new(&env, "price_floor_set"),),
+            (listing_type.to_u32(), floor),
+        );
+    }
+
+    /// Get the minimum listing price for a listing type
+    pub fn get_price_floor(env: Env, listing_type: u32) -> i128 {
+        let floor_key = (Symbol::new(&env, "price_floor"), listing_type);
+        env.storage()
+            .instance()
+            .get(&floor_key)
+            .unwrap_or(PRICE_LOWER_BOUND)
+    }
+
+    /// Create a new listing. A price of 0 lists the agent as a free giveaway.
     pub fn create_listing(
         env: Env,
         agent_id: u64,
@@ -40,11 +190,13 @@ impl Marketplace {
         if agent_id == 0 {
             panic!("Invalid agent ID");
         }
-        if listing_type > 2 {
-            panic!("Invalid listing type");
+        let listing_type = ListingType::try_from_u32(listing_type)
+            .unwrap_or_else(|_| panic!("Invalid listing type"));
+        if price < PRICE_LOWER_BOUND {
+            panic!("Price cannot be negative");
         }
-        if price <= 0 {
-            panic!("Price must be positive");
+        if price < Self::get_price_floor(env.clone(), listing_type.to_u32()) {
+            panic_with_error!(&env, ContractError::InvalidInput);
         }
 
         // Generate listing ID
@@ -60,12 +212,7 @@ impl Marketplace {
             agent_id,
             seller: seller.clone(),
             price,
-            listing_type: match listing_type {
-                0 => ListingType::Sale,
-                1 => ListingType::Lease,
-                2 => ListingType::Auction,
-                _ => panic!("Invalid listing type"),
-            },
+            listing_type,
             active: true,
             created_at: env.ledger().timestamp(),
         };
@@ -73,6 +220,8 @@ impl Marketplace {
         // Store listing using tuple key
         let listing_key = (Symbol::new(&env, "listing"), listing_id);
         env.storage().instance().set(&listing_key, &listing);
+        Self::adjust_listed_value(&env, price);
+        Self::adjust_active_listings(&env, 1);
 
         // Update counter
         env.storage()
@@ -87,13 +236,66 @@ impl Marketplace {
         listing_id
     }
 
+    /// Import a listing from a previous marketplace deployment, keeping its ID (admin only)
+    ///
+    /// The listing counter is advanced past the imported ID so later `create_listing`
+    /// calls never reuse it. Existing listings are never overwritten.
+    pub fn import_listing(env: Env, admin: Address, listing: Listing) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if listing.listing_id == 0 {
+            panic!("Invalid listing ID");
+        }
+        if listing.agent_id == 0 {
+            panic!("Invalid agent ID");
+        }
+        if listing.price < PRICE_LOWER_BOUND {
+            panic!("Price cannot be negative");
+        }
+
+        let listing_key = (Symbol::new(&env, "listing"), listing.listing_id);
+        if env.storage().instance().has(&listing_key) {
+            panic!("Listing already exists");
+        }
+        env.storage().instance().set(&listing_key, &listing);
+        if listing.active {
+            Self::adjust_listed_value(&env, listing.price);
+            Self::adjust_active_listings(&env, 1);
+        }
+
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, LISTING_COUNTER_KEY))
+            .unwrap_or(0);
+        if listing.listing_id > counter {
+            env.storage()
+                .instance()
+                .set(&Symbol::new(&env, LISTING_COUNTER_KEY), &listing.listing_id);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "listing_imported"),),
+            (listing.listing_id, listing.agent_id, listing.seller),
+        );
+    }
+
     /// Purchase an agent
-    pub fn buy_agent(env: Env, listing_id: u64, buyer: Address) {
+    ///
+    /// The buyer pays `amount` of the payment token into the marketplace and is refunded
+    /// anything above the listed price. The seller's share and any royalty are accrued
+    /// to their balances and pulled later via `withdraw_proceeds` / `withdraw_royalties`,
+    /// so a recipient that cannot receive tokens never blocks the sale.
+    pub fn buy_agent(env: Env, listing_id: u64, buyer: Address, amount: i128) {
         buyer.require_auth();
 
         if listing_id == 0 {
             panic!("Invalid listing ID");
         }
+        if amount > PRICE_UPPER_BOUND {
+            panic!("Payment amount exceeds safe maximum");
+        }
 
         let listing_key = (Symbol::new(&env, "listing"), listing_id);
         let mut listing: Listing = env
@@ -105,17 +307,593 @@ impl Marketplace {
         if !listing.active {
             panic!("Listing is not active");
         }
+        if listing.listing_type == ListingType::Auction {
+            panic!("Auction listings must be bid on");
+        }
+        let buyer_fee = Self::buyer_fee_amount(&env, listing.price);
+        let total_cost = Self::checked_total_cost(listing.price, buyer_fee);
+        if amount < total_cost {
+            panic!("Insufficient payment amount");
+        }
 
         // Mark listing as inactive
         listing.active = false;
         env.storage().instance().set(&listing_key, &listing);
+        Self::adjust_listed_value(&env, -listing.price);
+        Self::adjust_active_listings(&env, -1);
+
+        // Free listings are claimed without touching the payment token
+        if amount > 0 {
+            let token_client = Self::payment_token_client(&env);
+            token_client.transfer(&buyer, &env.current_contract_address(), &amount);
+
+            // Only the listed price and buyer fee are kept; any excess goes straight back
+            let overpayment = amount
+                .checked_sub(total_cost)
+                .expect("Arithmetic underflow in overpayment calculation");
+            if overpayment > 0 {
+                token_client.transfer(&env.current_contract_address(), &buyer, &overpayment);
+            }
+            Self::distribute_sale_proceeds(&env, &listing, listing.price);
+            if let Some((fee_recipient, _)) = Self::get_buyer_fee(env.clone()) {
+                if buyer_fee > 0 {
+                    Self::accrue_proceeds(&env, &fee_recipient, buyer_fee);
+                }
+            }
+        }
 
         env.events().publish(
             (Symbol::new(&env, "agent_sold"),),
-            (listing_id, listing.agent_id, buyer),
+            (listing_id, listing.agent_id, buyer.clone()),
+        );
+
+        // Overpayment is refunded, so the listed price is what was actually paid
+        if let Some(threshold) = Self::get_whale_threshold(env.clone()) {
+            if listing.price >= threshold {
+                env.events().publish(
+                    (Symbol::new(&env, "whale_sale"),),
+                    (listing.agent_id, listing.price, buyer),
+                );
+            }
+        }
+    }
+
+    /// Minimum `amount` a buyer must pass to `buy_agent` for a listing: the price
+    /// plus any buyer-side fee
+    pub fn get_total_cost(env: Env, listing_id: u64) -> i128 {
+        let listing = Self::get_listing(env.clone(), listing_id).expect("Listing not found");
+        if listing.listing_type == ListingType::Auction {
+            panic!("Auction listings must be bid on");
+        }
+
+        let buyer_fee = Self::buyer_fee_amount(&env, listing.price);
+        Self::checked_total_cost(listing.price, buyer_fee)
+    }
+
+    /// Set a fee, in basis points of the price, that buyers pay on top of the listed
+    /// price (admin only, 0 disables). It accrues to `recipient`'s proceeds.
+    pub fn set_buyer_fee(env: Env, admin: Address, recipient: Address, fee: u32) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if fee > MAX_ROYALTY_FEE {
+            panic!("Buyer fee exceeds maximum (100%)");
+        }
+
+        let fee_key = Symbol::new(&env, "buyer_fee");
+        if fee == 0 {
+            env.storage().instance().remove(&fee_key);
+        } else {
+            env.storage()
+                .instance()
+                .set(&fee_key, &RoyaltyInfo { recipient, fee });
+        }
+
+        env.events()
+            .publish((Symbol::new(&env, "buyer_fee_set"),), fee);
+    }
+
+    /// Get the buyer-side fee recipient and rate (basis points), if one is set
+    pub fn get_buyer_fee(env: Env) -> Option<(Address, u32)> {
+        env.storage()
+            .instance()
+            .get::<_, RoyaltyInfo>(&Symbol::new(&env, "buyer_fee"))
+            .map(|fee| (fee.recipient, fee.fee))
+    }
+
+    /// Buyer-side fee owed on `price`, rounded down. Split so that prices near
+    /// `PRICE_UPPER_BOUND` cannot overflow the multiplication.
+    fn buyer_fee_amount(env: &Env, price: i128) -> i128 {
+        let fee = match Self::get_buyer_fee(env.clone()) {
+            Some((_, fee)) => fee as i128,
+            None => return 0,
+        };
+        let scale = MAX_ROYALTY_FEE as i128;
+        (price / scale)
+            .checked_mul(fee)
+            .and_then(|whole| whole.checked_add((price % scale) * fee / scale))
+            .expect("Arithmetic overflow in buyer fee calculation")
+    }
+
+    fn checked_total_cost(price: i128, buyer_fee: i128) -> i128 {
+        match price.checked_add(buyer_fee) {
+            Some(total) if total <= PRICE_UPPER_BOUND => total,
+            _ => panic!("Total cost exceeds safe maximum"),
+        }
+    }
+
+    /// Accrue `amount`, already held by the marketplace, to the seller and any
+    /// royalty recipient for the listing's agent
+    fn distribute_sale_proceeds(env: &Env, listing: &Listing, amount: i128) {
+        let royalty_info = Self::resolve_royalty(env, listing);
+        let royalty_amount = match &royalty_info {
+            Some(royalty) => {
+                amount
+                    .checked_mul(royalty.fee as i128)
+                    .expect("Arithmetic overflow in royalty calculation")
+                    / MAX_ROYALTY_FEE as i128
+            }
+            None => 0,
+        };
+        let seller_amount = amount
+            .checked_sub(royalty_amount)
+            .expect("Arithmetic underflow in seller amount calculation");
+
+        if seller_amount > 0 {
+            Self::accrue_proceeds(env, &listing.seller, seller_amount);
+        }
+        if let Some(royalty) = royalty_info {
+            if royalty_amount > 0 {
+                Self::accrue_royalty(env, &royalty.recipient, royalty_amount);
+            }
+        }
+    }
+
+    fn accrue_royalty(env: &Env, recipient: &Address, amount: i128) {
+        Self::adjust_owed(env, amount);
+
+        let balance_key = (Symbol::new(env, "royalty_bal"), recipient.clone());
+        let balance: i128 = env.storage().instance().get(&balance_key).unwrap_or(0);
+        let new_balance = balance
+            .checked_add(amount)
+            .expect("Arithmetic overflow in royalty balance");
+        env.storage().instance().set(&balance_key, &new_balance);
+
+        env.events().publish(
+            (Symbol::new(env, "royalty_accrued"),),
+            (recipient.clone(), amount),
+        );
+    }
+
+    fn accrue_proceeds(env: &Env, seller: &Address, amount: i128) {
+        Self::adjust_owed(env, amount);
+
+        let balance_key = (Symbol::new(env, "proceeds"), seller.clone());
+        let balance: i128 = env.storage().instance().get(&balance_key).unwrap_or(0);
+        let new_balance = balance
+            .checked_add(amount)
+            .expect("Arithmetic overflow in proceeds balance");
+        env.storage().instance().set(&balance_key, &new_balance);
+
+        env.events().publish(
+            (Symbol::new(env, "proceeds_accrued"),),
+            (seller.clone(), amount),
+        );
+    }
+
+    /// Get the sale proceeds (and outbid or cancelled-auction bid refunds) accrued to an
+    /// address and not yet withdrawn
+    pub fn get_proceeds_balance(env: Env, seller: Address) -> i128 {
+        let balance_key = (Symbol::new(&env, "proceeds"), seller);
+        env.storage().instance().get(&balance_key).unwrap_or(0)
+    }
+
+    /// Withdraw the seller's accrued proceeds from all completed sales, plus any bid
+    /// refunds, in one transfer
+    pub fn withdraw_proceeds(env: Env, seller: Address) -> i128 {
+        seller.require_auth();
+        Self::enter_guard(&env);
+
+        let balance_key = (Symbol::new(&env, "proceeds"), seller.clone());
+        let balance: i128 = env.storage().instance().get(&balance_key).unwrap_or(0);
+        if balance <= 0 {
+            panic!("No proceeds to withdraw");
+        }
+
+        // Zero the balance before transferring out
+        env.storage().instance().remove(&balance_key);
+        Self::adjust_owed(&env, -balance);
+
+        let token_client = Self::payment_token_client(&env);
+        token_client.transfer(&env.current_contract_address(), &seller, &balance);
+
+        Self::exit_guard(&env);
+
+        env.events().publish(
+            (Symbol::new(&env, "proceeds_withdrawn"),),
+            (seller, balance),
+        );
+
+        balance
+    }
+
+    /// Reentrancy guard around outbound token transfers
+    fn enter_guard(env: &Env) {
+        let guard_key = Symbol::new(env, "withdraw_lock");
+        if env.storage().instance().has(&guard_key) {
+            panic_with_error!(env, ContractError::Reentrancy);
+        }
+        env.storage().instance().set(&guard_key, &true);
+    }
+
+    fn exit_guard(env: &Env) {
+        env.storage()
+            .instance()
+            .remove(&Symbol::new(env, "withdraw_lock"));
+    }
+
+    /// Track the total the marketplace owes out: accrued proceeds, royalties and escrowed bids
+    fn adjust_owed(env: &Env, delta: i128) {
+        let owed_key = Symbol::new(env, "total_owed");
+        let owed: i128 = env.storage().instance().get(&owed_key).unwrap_or(0);
+        let new_owed = owed
+            .checked_add(delta)
+            .expect("Arithmetic overflow in owed total");
+        env.storage().instance().set(&owed_key, &new_owed);
+    }
+
+    /// Track the summed price of all active listings
+    fn adjust_listed_value(env: &Env, delta: i128) {
+        let value_key = Symbol::new(env, "listed_value");
+        let value: i128 = env.storage().instance().get(&value_key).unwrap_or(0);
+        let new_value = value
+            .checked_add(delta)
+            .expect("Arithmetic overflow in listed value");
+        env.storage().instance().set(&value_key, &new_value);
+    }
+
+    /// Track how many listings are active, so paging does not have to count them
+    fn adjust_active_listings(env: &Env, delta: i32) {
+        let count = Self::active_listing_count(env);
+        let new_count = count
+            .checked_add_signed(delta)
+            .expect("Arithmetic overflow in active listing count");
+        env.storage()
+            .instance()
+            .set(&Symbol::new(env, "active_listings"), &new_count);
+    }
+
+    /// Number of active listings. Deployments that predate the running count fall back
+    /// to a one-off scan, which the next adjustment then persists.
+    fn active_listing_count(env: &Env) -> u32 {
+        if let Some(count) = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(env, "active_listings"))
+        {
+            return count;
+        }
+
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(env, LISTING_COUNTER_KEY))
+            .unwrap_or(0);
+        let mut count = 0u32;
+        for listing_id in 1..=counter {
+            let listing_key = (Symbol::new(env, "listing"), listing_id);
+            if let Some(listing) = env.storage().instance().get::<_, Listing>(&listing_key) {
+                if listing.active {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Get the summed price of all active listings
+    pub fn get_total_listed_value(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "listed_value"))
+            .unwrap_or(0)
+    }
+
+    /// Compare the marketplace's balance of `token` with what it owes out in that token.
+    /// Returns `(held, owed)`; obligations are only denominated in the payment token.
+    pub fn get_solvency(env: Env, token: Address) -> (i128, i128) {
+        let held = token::Client::new(&env, &token).balance(&env.current_contract_address());
+        let owed = if Self::get_payment_token(env.clone()) == Some(token) {
+            env.storage()
+                .instance()
+                .get(&Symbol::new(&env, "total_owed"))
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        (held, owed)
+    }
+
+    /// Get the royalties accrued to a recipient and not yet withdrawn
+    pub fn get_royalty_balance(env: Env, recipient: Address) -> i128 {
+        let balance_key = (Symbol::new(&env, "royalty_bal"), recipient);
+        env.storage().instance().get(&balance_key).unwrap_or(0)
+    }
+
+    /// Withdraw all royalties accrued to the recipient
+    pub fn withdraw_royalties(env: Env, recipient: Address) -> i128 {
+        recipient.require_auth();
+        Self::enter_guard(&env);
+
+        let balance_key = (Symbol::new(&env, "royalty_bal"), recipient.clone());
+        let balance: i128 = env.storage().instance().get(&balance_key).unwrap_or(0);
+        if balance <= 0 {
+            panic!("No royalties to withdraw");
+        }
+
+        // Zero the balance before transferring out
+        env.storage().instance().remove(&balance_key);
+        Self::adjust_owed(&env, -balance);
+
+        let token_client = Self::payment_token_client(&env);
+        token_client.transfer(&env.current_contract_address(), &recipient, &balance);
+
+        Self::exit_guard(&env);
+
+        env.events().publish(
+            (Symbol::new(&env, "royalties_withdrawn"),),
+            (recipient, balance),
+        );
+
+        balance
+    }
+
+    /// Open bidding on an auction listing (seller only)
+    ///
+    /// A winning bid placed within `anti_snipe_seconds` of the end pushes `ends_at`
+    /// back by `anti_snipe_seconds`, at most `max_extensions` times (0 = unlimited).
+    pub fn start_auction(
+        env: Env,
+        listing_id: u64,
+        seller: Address,
+        duration_seconds: u64,
+        anti_snipe_seconds: u64,
+        max_extensions: u32,
+    ) {
+        seller.require_auth();
+
+        if listing_id == 0 {
+            panic!("Invalid listing ID");
+        }
+        if duration_seconds == 0 {
+            panic!("Auction duration must be positive");
+        }
+
+        let listing_key = (Symbol::new(&env, "listing"), listing_id);
+        let listing: Listing = env
+            .storage()
+            .instance()
+            .get(&listing_key)
+            .expect("Listing not found");
+
+        if listing.seller != seller {
+            panic!("Unauthorized: only seller can start auction");
+        }
+        if !listing.active {
+            panic!("Listing is not active");
+        }
+        if listing.listing_type != ListingType::Auction {
+            panic!("Listing is not an auction");
+        }
+
+        let auction_key = (Symbol::new(&env, "auction"), listing_id);
+        if env.storage().instance().has(&auction_key) {
+            panic!("Auction already started");
+        }
+
+        let ends_at = env
+            .ledger()
+            .timestamp()
+            .checked_add(duration_seconds)
+            .expect("Arithmetic overflow in auction end time");
+        let auction = Auction {
+            listing_id,
+            ends_at,
+            highest_bid: 0,
+            highest_bidder: None,
+            anti_snipe_seconds,
+            max_extensions,
+            extensions: 0,
+        };
+        env.storage().instance().set(&auction_key, &auction);
+
+        env.events().publish(
+            (Symbol::new(&env, "auction_started"),),
+            (listing_id, listing.agent_id, ends_at),
         );
     }
 
+    /// Place a bid on a running auction. The bid is escrowed and the previous
+    /// highest bidder is refunded.
+    pub fn place_bid(env: Env, listing_id: u64, bidder: Address, amount: i128) {
+        bidder.require_auth();
+
+        if listing_id == 0 {
+            panic!("Invalid listing ID");
+        }
+        if amount > PRICE_UPPER_BOUND {
+            panic!("Bid amount exceeds safe maximum");
+        }
+
+        let listing_key = (Symbol::new(&env, "listing"), listing_id);
+        let listing: Listing = env
+            .storage()
+            .instance()
+            .get(&listing_key)
+            .expect("Listing not found");
+        if !listing.active {
+            panic!("Listing is not active");
+        }
+
+        let auction_key = (Symbol::new(&env, "auction"), listing_id);
+        let mut auction: Auction = env
+            .storage()
+            .instance()
+            .get(&auction_key)
+            .expect("Auction not found");
+
+        let now = env.ledger().timestamp();
+        if now >= auction.ends_at {
+            panic!("Auction has ended");
+        }
+        if amount < listing.price || amount <= auction.highest_bid {
+            panic!("Bid too low");
+        }
+
+        let token_client = Self::payment_token_client(&env);
+        let contract_address = env.current_contract_address();
+        token_client.transfer(&bidder, &contract_address, &amount);
+        Self::adjust_owed(&env, amount);
+        // The outbid amount is accrued for the previous bidder to withdraw, so a bidder
+        // that cannot receive tokens never blocks later bids
+        if let Some(previous_bidder) = auction.highest_bidder.clone() {
+            Self::adjust_owed(&env, -auction.highest_bid);
+            Self::accrue_proceeds(&env, &previous_bidder, auction.highest_bid);
+        }
+
+        auction.highest_bid = amount;
+        auction.highest_bidder = Some(bidder.clone());
+
+        // Anti-sniping: a late winning bid extends the auction
+        let can_extend = auction.max_extensions == 0 || auction.extensions < auction.max_extensions;
+        if auction.anti_snipe_seconds > 0
+            && auction.ends_at - now <= auction.anti_snipe_seconds
+            && can_extend
+        {
+            auction.ends_at = auction
+                .ends_at
+                .checked_add(auction.anti_snipe_seconds)
+                .expect("Arithmetic overflow in auction end time");
+            auction.extensions += 1;
+
+            env.events().publish(
+                (Symbol::new(&env, "auction_extended"),),
+                (listing_id, auction.ends_at),
+            );
+        }
+
+        env.storage().instance().set(&auction_key, &auction);
+
+        env.events().publish(
+            (Symbol::new(&env, "bid_placed"),),
+            (listing_id, bidder, amount),
+        );
+    }
+
+    /// Set how long after an auction ends its escrow stays locked before settlement,
+    /// letting in-flight transactions land first (admin only, 0 disables)
+    pub fn set_settlement_grace(env: Env, admin: Address, grace_seconds: u64) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if grace_seconds > MAX_SETTLEMENT_GRACE_SECONDS {
+            panic!("Settlement grace exceeds maximum allowed");
+        }
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "settle_grace"), &grace_seconds);
+
+        env.events()
+            .publish((Symbol::new(&env, "settlement_grace_set"),), grace_seconds);
+    }
+
+    /// Get the settlement grace period in seconds
+    pub fn get_settlement_grace(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "settle_grace"))
+            .unwrap_or(0)
+    }
+
+    /// Close an auction once it has ended and the settlement grace period has passed,
+    /// paying out the winning bid. Anyone may settle.
+    pub fn settle_auction(env: Env, listing_id: u64) {
+        if listing_id == 0 {
+            panic!("Invalid listing ID");
+        }
+
+        let listing_key = (Symbol::new(&env, "listing"), listing_id);
+        let mut listing: Listing = env
+            .storage()
+            .instance()
+            .get(&listing_key)
+            .expect("Listing not found");
+        if !listing.active {
+            panic!("Listing is not active");
+        }
+
+        let auction_key = (Symbol::new(&env, "auction"), listing_id);
+        let auction: Auction = env
+            .storage()
+            .instance()
+            .get(&auction_key)
+            .expect("Auction not found");
+        let now = env.ledger().timestamp();
+        if now < auction.ends_at {
+            panic!("Auction has not ended");
+        }
+        let grace = Self::get_settlement_grace(env.clone());
+        if now < auction.ends_at.saturating_add(grace) {
+            panic!("Auction is within settlement grace period");
+        }
+
+        listing.active = false;
+        env.storage().instance().set(&listing_key, &listing);
+        Self::adjust_listed_value(&env, -listing.price);
+        Self::adjust_active_listings(&env, -1);
+
+        if auction.highest_bidder.is_some() {
+            // The escrowed bid becomes seller proceeds and royalties
+            Self::adjust_owed(&env, -auction.highest_bid);
+            Self::distribute_sale_proceeds(&env, &listing, auction.highest_bid);
+        }
+
+        env.events().publish(
+            (Symbol::new(&env, "auction_settled"),),
+            (
+                listing_id,
+                listing.agent_id,
+                auction.highest_bidder,
+                auction.highest_bid,
+            ),
+        );
+    }
+
+    /// Get the bidding state of an auction listing
+    pub fn get_auction(env: Env, listing_id: u64) -> Option<Auction> {
+        if listing_id == 0 {
+            panic!("Invalid listing ID");
+        }
+
+        let auction_key = (Symbol::new(&env, "auction"), listing_id);
+        env.storage().instance().get(&auction_key)
+    }
+
+    /// Seconds until the listing expires. Only started auctions have an expiry (their
+    /// end time); inactive, expired and unexpiring listings return 0.
+    pub fn get_time_remaining(env: Env, listing_id: u64) -> u64 {
+        let listing = Self::get_listing(env.clone(), listing_id).expect("Listing not found");
+        if !listing.active {
+            return 0;
+        }
+
+        match Self::get_auction(env.clone(), listing_id) {
+            Some(auction) => auction.ends_at.saturating_sub(env.ledger().timestamp()),
+            None => 0,
+        }
+    }
+
     /// Cancel a listing
     pub fn cancel_listing(env: Env, listing_id: u64, seller: Address) {
         seller.require_auth();
@@ -135,6 +913,19 @@ impl Marketplace {
             panic!("Unauthorized: only seller can cancel listing");
         }
 
+        // Once an auction with a bid has ended, the winning bid stands
+        let auction_key = (Symbol::new(&env, "auction"), listing_id);
+        if let Some(auction) = env.storage().instance().get::<_, Auction>(&auction_key) {
+            if auction.highest_bidder.is_some() && env.ledger().timestamp() >= auction.ends_at {
+                panic!("Cannot cancel an ended auction with bids");
+            }
+        }
+
+        if listing.active {
+            Self::adjust_listed_value(&env, -listing.price);
+            Self::adjust_active_listings(&env, -1);
+            Self::refund_highest_bid(&env, listing_id);
+        }
         listing.active = false;
         env.storage().instance().set(&listing_key, &listing);
 
@@ -144,6 +935,22 @@ impl Marketplace {
         );
     }
 
+    /// Accrue the escrowed highest bid of a started auction back to its bidder for
+    /// withdrawal, so a cancelled auction never strands funds
+    fn refund_highest_bid(env: &Env, listing_id: u64) {
+        let auction_key = (Symbol::new(env, "auction"), listing_id);
+        let mut auction: Auction = match env.storage().instance().get(&auction_key) {
+            Some(auction) => auction,
+            None => return,
+        };
+        if let Some(bidder) = auction.highest_bidder.take() {
+            Self::adjust_owed(env, -auction.highest_bid);
+            Self::accrue_proceeds(env, &bidder, auction.highest_bid);
+            auction.highest_bid = 0;
+            env.storage().instance().set(&auction_key, &auction);
+        }
+    }
+
     /// Get a specific listing
     pub fn get_listing(env: Env, listing_id: u64) -> Option<Listing> {
         if listing_id == 0 {
@@ -154,9 +961,101 @@ impl Marketplace {
         env.storage().instance().get(&listing_key)
     }
 
-    /// Set royalty info for an agent
-    pub fn set_royalty(env: Env, agent_id: u64, creator: Address, recipient: Address, fee: u32) {
-        creator.require_auth();
+    /// Get the active listings among IDs `offset + 1 ..= offset + limit`, in ID order.
+    /// Unlike `get_listings_page`, this only reads the requested ID range, so callers
+    /// page by advancing `offset` by `limit` until it passes the listing counter.
+    pub fn get_listings(env: Env, offset: u32, limit: u32) -> Vec<Listing> {
+        if limit == 0 || limit > MAX_HISTORY_QUERY_LIMIT {
+            panic!("Limit must be between 1 and 500");
+        }
+
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, LISTING_COUNTER_KEY))
+            .unwrap_or(0);
+        let end = (offset as u64).saturating_add(limit as u64).min(counter);
+
+        let mut listings = Vec::new(&env);
+        for listing_id in (offset as u64).saturating_add(1)..=end {
+            let listing_key = (Symbol::new(&env, "listing"), listing_id);
+            if let Some(listing) = env.storage().instance().get::<_, Listing>(&listing_key) {
+                if listing.active {
+                    listings.push_back(listing);
+                }
+            }
+        }
+        listings
+    }
+
+    /// Get a page of active listings, with the total number of active listings
+    pub fn get_listings_page(env: Env, offset: u32, limit: u32) -> ListingPage {
+        if limit > MAX_HISTORY_QUERY_LIMIT {
+            panic!("Limit exceeds maximum allowed (500)");
+        }
+
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, LISTING_COUNTER_KEY))
+            .unwrap_or(0);
+
+        // The running count gives the total; the scan stops once the page is full
+        let total = Self::active_listing_count(&env);
+        let mut items = Vec::new(&env);
+        let mut seen: u32 = 0;
+        for listing_id in 1..=counter {
+            if items.len() >= limit || seen >= total {
+                break;
+            }
+            let listing_key = (Symbol::new(&env, "listing"), listing_id);
+            if let Some(listing) = env.storage().instance().get::<_, Listing>(&listing_key) {
+                if !listing.active {
+                    continue;
+                }
+                if seen >= offset {
+                    items.push_back(listing);
+                }
+                seen += 1;
+            }
+        }
+
+        ListingPage {
+            items,
+            total,
+            offset,
+        }
+    }
+
+    /// Set the sale amount at or above which `buy_agent` emits `whale_sale` (admin only)
+    pub fn set_whale_threshold(env: Env, admin: Address, threshold: i128) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if threshold <= 0 || threshold > PRICE_UPPER_BOUND {
+            panic!("Whale threshold out of bounds");
+        }
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "whale_threshold"), &threshold);
+
+        env.events()
+            .publish((Symbol::new(&env, "whale_threshold_set"),), threshold);
+    }
+
+    /// Get the whale sale threshold, if one is configured
+    pub fn get_whale_threshold(env: Env) -> Option<i128> {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "whale_threshold"))
+    }
+
+    /// Set royalty info for an agent. The royalty belongs to its current recipient once
+    /// set, so only they may replace it. The first royalty must be set by the agent's
+    /// recorded creator when an AgentNFT is configured.
+    pub fn set_royalty(env: Env, agent_id: u64, caller: Address, recipient: Address, fee: u32) {
+        caller.require_auth();
 
         if agent_id == 0 {
             panic!("Invalid agent ID");
@@ -166,9 +1065,30 @@ impl Marketplace {
             panic!("Royalty fee exceeds maximum (100%)");
         }
 
-        let royalty_info = RoyaltyInfo { recipient, fee };
-
         let royalty_key = (Symbol::new(&env, "royalty"), agent_id);
+        match env.storage().instance().get::<_, RoyaltyInfo>(&royalty_key) {
+            // An existing royalty can only be replaced by its current recipient
+            Some(existing) => {
+                if existing.recipient != caller {
+                    panic!("Unauthorized: caller is not the royalty recipient");
+                }
+            }
+            // Only the agent's creator may set its first royalty, once an AgentNFT is configured
+            None => {
+                if let Some(agent_nft) = Self::get_agent_nft(env.clone()) {
+                    let agent_creator = AgentNftClient::new(&env, &agent_nft)
+                        .try_get_creator(&agent_id)
+                        .ok()
+                        .and_then(|creator| creator.ok())
+                        .expect("Agent not found");
+                    if agent_creator != caller {
+                        panic!("Unauthorized: caller is not the agent creator");
+                    }
+                }
+            }
+        }
+
+        let royalty_info = RoyaltyInfo { recipient, fee };
         env.storage().instance().set(&royalty_key, &royalty_info);
 
         env.events()
@@ -184,4 +1104,95 @@ impl Marketplace {
         let royalty_key = (Symbol::new(&env, "royalty"), agent_id);
         env.storage().instance().get(&royalty_key)
     }
+
+    /// Hand an agent's future royalties to `new_recipient` (current recipient only)
+    pub fn transfer_royalty_recipient(
+        env: Env,
+        agent_id: u64,
+        current_recipient: Address,
+        new_recipient: Address,
+    ) {
+        current_recipient.require_auth();
+
+        let royalty_key = (Symbol::new(&env, "royalty"), agent_id);
+        let mut royalty_info: RoyaltyInfo = env
+            .storage()
+            .instance()
+            .get(&royalty_key)
+            .expect("Royalty not found");
+
+        if royalty_info.recipient != current_recipient {
+            panic!("Unauthorized: caller is not the royalty recipient");
+        }
+
+        royalty_info.recipient = new_recipient.clone();
+        env.storage().instance().set(&royalty_key, &royalty_info);
+
+        env.events().publish(
+            (Symbol::new(&env, "royalty_recipient_changed"),),
+            (agent_id, current_recipient, new_recipient),
+        );
+    }
+
+    /// Set the royalty applied to agents without their own royalty (admin only)
+    pub fn set_default_royalty(env: Env, admin: Address, recipient: Address, fee: u32) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if fee > MAX_ROYALTY_FEE {
+            panic!("Royalty fee exceeds maximum (100%)");
+        }
+
+        let royalty_info = RoyaltyInfo { recipient, fee };
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "default_royalty"), &royalty_info);
+
+        env.events()
+            .publish((Symbol::new(&env, "default_royalty_set"),), fee);
+    }
+
+    /// Override the royalty for a single listing (admin only)
+    pub fn set_listing_royalty(
+        env: Env,
+        admin: Address,
+        listing_id: u64,
+        recipient: Address,
+        fee: u32,
+    ) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if fee > MAX_ROYALTY_FEE {
+            panic!("Royalty fee exceeds maximum (100%)");
+        }
+        if Self::get_listing(env.clone(), listing_id).is_none() {
+            panic!("Listing not found");
+        }
+
+        let royalty_info = RoyaltyInfo { recipient, fee };
+        let royalty_key = (Symbol::new(&env, "listing_royalty"), listing_id);
+        env.storage().instance().set(&royalty_key, &royalty_info);
+
+        env.events().publish(
+            (Symbol::new(&env, "listing_royalty_set"),),
+            (listing_id, fee),
+        );
+    }
+
+    /// Royalty recipient and fee (basis points) a sale of this listing would pay, or
+    /// None if no royalty applies. Precedence: listing override > agent royalty > default.
+    pub fn get_effective_royalty(env: Env, listing_id: u64) -> Option<(Address, u32)> {
+        let listing = Self::get_listing(env.clone(), listing_id).expect("Listing not found");
+        Self::resolve_royalty(&env, &listing).map(|royalty| (royalty.recipient, royalty.fee))
+    }
+
+    /// Royalty that applies to a sale of `listing`, by override precedence
+    fn resolve_royalty(env: &Env, listing: &Listing) -> Option<RoyaltyInfo> {
+        let storage = env.storage().instance();
+        storage
+            .get(&(Symbol::new(env, "listing_royalty"), listing.listing_id))
+            .or_else(|| storage.get(&(Symbol::new(env, "royalty"), listing.agent_id)))
+            .or_else(|| storage.get(&Symbol::new(env, "default_royalty")))
+    }
 }
@@ -0,0 +1,1011 @@
+#![cfg(test)]
+
+use crate::{Marketplace, MarketplaceClient};
+use agent_nft::{AgentNFT, AgentNFTClient};
+use soroban_sdk::testutils::{Address as _, Events, IssuerFlags, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{Address, Env, IntoVal, Symbol, Val};
+use stellai_lib::{Listing, ListingType, PRICE_UPPER_BOUND};
+
+fn setup_marketplace(env: &Env) -> (MarketplaceClient<'_>, Address, Address) {
+    let contract_id = env.register_contract(None, Marketplace);
+    let client = MarketplaceClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    let seller = Address::generate(env);
+
+    env.mock_all_auths();
+    client.init_contract(&admin);
+
+    (client, admin, seller)
+}
+
+/// Registers a payment token on the marketplace and mints `amount` to `holder`
+fn setup_payment_token<'a>(
+    env: &Env,
+    client: &MarketplaceClient,
+    admin: &Address,
+    holder: &Address,
+    amount: i128,
+) -> TokenClient<'a> {
+    let token = env.register_stellar_asset_contract_v2(admin.clone());
+    StellarAssetClient::new(env, &token.address()).mint(holder, &amount);
+    client.set_payment_token(admin, &token.address());
+    TokenClient::new(env, &token.address())
+}
+
+#[test]
+fn test_is_initialized() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, Marketplace);
+    let client = MarketplaceClient::new(&env, &contract_id);
+    assert!(!client.is_initialized());
+
+    env.mock_all_auths();
+    client.init_contract(&Address::generate(&env));
+    assert!(client.is_initialized());
+}
+
+#[test]
+fn test_create_listing_accepts_all_listing_types() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+
+    for (raw, expected) in [
+        (0u32, ListingType::Sale),
+        (1u32, ListingType::Lease),
+        (2u32, ListingType::Auction),
+    ] {
+        let listing_id = client.create_listing(&(raw as u64 + 1), &seller, &raw, &1000);
+        let listing = client.get_listing(&listing_id).unwrap();
+        assert!(listing.listing_type == expected);
+        assert_eq!(listing.listing_type.to_u32(), raw);
+    }
+}
+
+#[test]
+#[should_panic(expected = "Invalid listing type")]
+fn test_create_listing_rejects_unknown_listing_type() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+
+    client.create_listing(&1, &seller, &3, &1000);
+}
+
+#[test]
+fn test_get_listings_skips_cancelled_listings() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+
+    for agent_id in 1..=5u64 {
+        client.create_listing(&agent_id, &seller, &0, &1000);
+    }
+    client.cancel_listing(&3, &seller);
+
+    let mut ids = soroban_sdk::vec![&env];
+    for listing in client.get_listings(&0, &5).iter() {
+        assert!(listing.active);
+        ids.push_back(listing.listing_id);
+    }
+    assert_eq!(ids, soroban_sdk::vec![&env, 1u64, 2, 4, 5]);
+
+    // The window covers listing IDs, not positions among active listings
+    let page = client.get_listings(&2, &2);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().listing_id, 4);
+
+    // Windows past the counter stop early
+    assert_eq!(client.get_listings(&4, &10).len(), 1);
+    assert_eq!(client.get_listings(&5, &10).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Limit must be between 1 and 500")]
+fn test_get_listings_rejects_zero_limit() {
+    let env = Env::default();
+    let (client, _admin, _seller) = setup_marketplace(&env);
+
+    client.get_listings(&0, &0);
+}
+
+#[test]
+fn test_sale_accrues_royalty_for_withdrawal() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let token = setup_payment_token(&env, &client, &admin, &buyer, 10_000);
+
+    // 5% royalty
+    client.set_royalty(&1, &creator, &recipient, &500);
+    let listing_id = client.create_listing(&1, &seller, &0, &1000);
+    client.buy_agent(&listing_id, &buyer, &1000);
+
+    assert_eq!(client.get_proceeds_balance(&seller), 950);
+    assert_eq!(token.balance(&recipient), 0);
+    assert_eq!(client.get_royalty_balance(&recipient), 50);
+
+    let withdrawn = client.withdraw_royalties(&recipient);
+    assert_eq!(withdrawn, 50);
+    assert_eq!(token.balance(&recipient), 50);
+    assert_eq!(client.get_royalty_balance(&recipient), 0);
+}
+
+#[test]
+fn test_royalty_cannot_be_hijacked_by_third_party() {
+    let env = Env::default();
+    let (client, _admin, _seller) = setup_marketplace(&env);
+    let creator = Address::generate(&env);
+    let attacker = Address::generate(&env);
+
+    client.set_royalty(&1, &creator, &creator, &500);
+    let result = client.try_set_royalty(&1, &attacker, &attacker, &10_000);
+    assert!(result.is_err());
+    assert_eq!(client.get_royalty(&1).unwrap().recipient, creator);
+
+    // The current recipient may still update it
+    client.set_royalty(&1, &creator, &creator, &250);
+    assert_eq!(client.get_royalty(&1).unwrap().fee, 250);
+}
+
+#[test]
+fn test_royalty_restricted_to_agent_creator() {
+    let env = Env::default();
+    let (client, admin, _seller) = setup_marketplace(&env);
+    let creator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+
+    let nft_id = env.register_contract(None, AgentNFT);
+    let nft = AgentNFTClient::new(&env, &nft_id);
+    let nft_admin = Address::generate(&env);
+    nft.init_contract(&nft_admin);
+    nft.mint_agent(
+        &1,
+        &nft_admin,
+        &creator,
+        &soroban_sdk::String::from_str(&env, "ipfs://agent-1"),
+        &0,
+    );
+    nft.transfer_agent(&1, &creator, &creator, &buyer);
+    client.set_agent_nft(&admin, &nft_id);
+    assert_eq!(client.get_agent_nft(), Some(nft_id));
+
+    // The current owner is not the creator
+    let result = client.try_set_royalty(&1, &buyer, &buyer, &500);
+    assert!(result.is_err());
+    assert!(client.get_royalty(&1).is_none());
+
+    client.set_royalty(&1, &creator, &creator, &500);
+    assert_eq!(client.get_royalty(&1).unwrap().recipient, creator);
+
+    // Unknown agents have no creator
+    assert!(client
+        .try_set_royalty(&2, &creator, &creator, &500)
+        .is_err());
+}
+
+#[test]
+fn test_royalty_owned_by_recipient_after_transfer() {
+    let env = Env::default();
+    let (client, admin, _seller) = setup_marketplace(&env);
+    let creator = Address::generate(&env);
+    let treasury = Address::generate(&env);
+
+    let nft_id = env.register_contract(None, AgentNFT);
+    let nft = AgentNFTClient::new(&env, &nft_id);
+    let nft_admin = Address::generate(&env);
+    nft.init_contract(&nft_admin);
+    nft.mint_agent(
+        &1,
+        &nft_admin,
+        &creator,
+        &soroban_sdk::String::from_str(&env, "ipfs://agent-1"),
+        &0,
+    );
+    client.set_agent_nft(&admin, &nft_id);
+
+    client.set_royalty(&1, &creator, &creator, &500);
+    client.transfer_royalty_recipient(&1, &creator, &treasury);
+
+    // The new recipient can update the royalty, even though it is not the creator
+    client.set_royalty(&1, &treasury, &treasury, &250);
+    assert_eq!(client.get_royalty(&1).unwrap().fee, 250);
+
+    // The creator gave up control with the transfer
+    assert!(client
+        .try_set_royalty(&1, &creator, &creator, &750)
+        .is_err());
+    assert_eq!(client.get_royalty(&1).unwrap().recipient, treasury);
+}
+
+#[test]
+fn test_total_cost_without_buyer_fee_equals_price() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+
+    let listing_id = client.create_listing(&1, &seller, &0, &1000);
+    assert_eq!(client.get_total_cost(&listing_id), 1000);
+
+    let listing_id = client.create_listing(&2, &seller, &0, &PRICE_UPPER_BOUND);
+    assert_eq!(client.get_total_cost(&listing_id), PRICE_UPPER_BOUND);
+}
+
+#[test]
+fn test_total_cost_includes_buyer_fee() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    let fee_recipient = Address::generate(&env);
+    let token = setup_payment_token(&env, &client, &admin, &buyer, 10_000);
+
+    // 2.5% buyer fee on top of the price; the seller's share is unchanged
+    client.set_buyer_fee(&admin, &fee_recipient, &250);
+    let listing_id = client.create_listing(&1, &seller, &0, &1000);
+    assert_eq!(client.get_total_cost(&listing_id), 1025);
+
+    let result = client.try_buy_agent(&listing_id, &buyer, &1000);
+    assert!(result.is_err());
+
+    client.buy_agent(&listing_id, &buyer, &1100);
+    assert_eq!(token.balance(&buyer), 10_000 - 1025);
+    assert_eq!(client.get_proceeds_balance(&seller), 1000);
+    assert_eq!(client.get_proceeds_balance(&fee_recipient), 25);
+
+    // Large prices do not overflow the fee calculation
+    let price = PRICE_UPPER_BOUND / 2;
+    let listing_id = client.create_listing(&2, &seller, &0, &price);
+    assert_eq!(
+        client.get_total_cost(&listing_id),
+        price + price / 10_000 * 250 + price % 10_000 * 250 / 10_000
+    );
+}
+
+#[test]
+#[should_panic(expected = "Total cost exceeds safe maximum")]
+fn test_total_cost_above_price_bound_rejected() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+
+    client.set_buyer_fee(&admin, &Address::generate(&env), &1);
+    let listing_id = client.create_listing(&1, &seller, &0, &PRICE_UPPER_BOUND);
+    client.get_total_cost(&listing_id);
+}
+
+#[test]
+fn test_transferred_royalty_recipient_receives_later_sales() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    setup_payment_token(&env, &client, &admin, &buyer, 10_000);
+
+    client.set_royalty(&1, &creator, &recipient, &500);
+    let first = client.create_listing(&1, &seller, &0, &1000);
+    client.buy_agent(&first, &buyer, &1000);
+
+    client.transfer_royalty_recipient(&1, &recipient, &treasury);
+    assert_eq!(client.get_royalty(&1).unwrap().recipient, treasury);
+    assert_eq!(client.get_royalty(&1).unwrap().fee, 500);
+
+    let second = client.create_listing(&1, &seller, &0, &2000);
+    client.buy_agent(&second, &buyer, &2000);
+
+    assert_eq!(client.get_royalty_balance(&recipient), 50);
+    assert_eq!(client.get_royalty_balance(&treasury), 100);
+}
+
+#[test]
+#[should_panic(expected = "Unauthorized: caller is not the royalty recipient")]
+fn test_transfer_royalty_recipient_requires_current_recipient() {
+    let env = Env::default();
+    let (client, _admin, _seller) = setup_marketplace(&env);
+    let creator = Address::generate(&env);
+    let recipient = Address::generate(&env);
+
+    client.set_royalty(&1, &creator, &recipient, &500);
+    client.transfer_royalty_recipient(&1, &creator, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "Royalty not found")]
+fn test_transfer_royalty_recipient_without_royalty_rejected() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+
+    client.transfer_royalty_recipient(&1, &seller, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "No royalties to withdraw")]
+fn test_double_royalty_withdrawal_rejected() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    setup_payment_token(&env, &client, &admin, &buyer, 10_000);
+
+    client.set_royalty(&1, &creator, &recipient, &500);
+    let listing_id = client.create_listing(&1, &seller, &0, &1000);
+    client.buy_agent(&listing_id, &buyer, &1000);
+
+    client.withdraw_royalties(&recipient);
+    client.withdraw_royalties(&recipient);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #6)")]
+fn test_sale_below_price_floor_rejected() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+
+    client.set_price_floor(&admin, &ListingType::Sale.to_u32(), &500);
+    client.create_listing(&1, &seller, &ListingType::Sale.to_u32(), &499);
+}
+
+#[test]
+fn test_auction_at_price_floor_accepted() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+
+    client.set_price_floor(&admin, &ListingType::Auction.to_u32(), &2000);
+    assert_eq!(client.get_price_floor(&ListingType::Auction.to_u32()), 2000);
+
+    let listing_id = client.create_listing(&1, &seller, &ListingType::Auction.to_u32(), &2000);
+    assert_eq!(client.get_listing(&listing_id).unwrap().price, 2000);
+
+    // The auction floor does not apply to sales
+    client.create_listing(&2, &seller, &ListingType::Sale.to_u32(), &1);
+}
+
+#[test]
+fn test_unset_price_floors_preserve_behavior() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+
+    for raw in 0u32..3 {
+        assert_eq!(client.get_price_floor(&raw), 0);
+        client.create_listing(&(raw as u64 + 1), &seller, &raw, &1);
+    }
+}
+
+#[test]
+fn test_listings_page_total_is_independent_of_window() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+
+    for agent_id in 1..=5u64 {
+        client.create_listing(&agent_id, &seller, &0, &(agent_id as i128 * 100));
+    }
+    client.cancel_listing(&2, &seller);
+
+    let page = client.get_listings_page(&1, &2);
+    assert_eq!(page.total, 4);
+    assert_eq!(page.offset, 1);
+    assert_eq!(page.items.len(), 2);
+    assert_eq!(page.items.get(0).unwrap().listing_id, 3);
+    assert_eq!(page.items.get(1).unwrap().listing_id, 4);
+
+    let past_end = client.get_listings_page(&10, &2);
+    assert_eq!(past_end.total, 4);
+    assert_eq!(past_end.items.len(), 0);
+}
+
+#[test]
+fn test_listings_page_recounts_when_running_count_is_missing() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+
+    for agent_id in 1..=3u64 {
+        client.create_listing(&agent_id, &seller, &0, &100);
+    }
+    client.cancel_listing(&1, &seller);
+
+    // A deployment from before the running count was kept
+    env.as_contract(&client.address, || {
+        env.storage()
+            .instance()
+            .remove(&Symbol::new(&env, "active_listings"));
+    });
+    assert_eq!(client.get_listings_page(&0, &10).total, 2);
+
+    client.cancel_listing(&2, &seller);
+    let page = client.get_listings_page(&0, &10);
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items.get(0).unwrap().listing_id, 3);
+}
+
+/// Creates and starts an auction for agent 1 with a 1000s duration and reserve price 100
+fn start_auction(
+    env: &Env,
+    client: &MarketplaceClient,
+    seller: &Address,
+    anti_snipe_seconds: u64,
+    max_extensions: u32,
+) -> u64 {
+    let listing_id = client.create_listing(&1, seller, &ListingType::Auction.to_u32(), &100);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.start_auction(
+        &listing_id,
+        seller,
+        &1_000,
+        &anti_snipe_seconds,
+        &max_extensions,
+    );
+    listing_id
+}
+
+#[test]
+fn test_late_bid_extends_auction() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let bidder = Address::generate(&env);
+    setup_payment_token(&env, &client, &admin, &bidder, 10_000);
+
+    let listing_id = start_auction(&env, &client, &seller, 60, 0);
+    assert_eq!(client.get_auction(&listing_id).unwrap().ends_at, 2_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_950);
+    client.place_bid(&listing_id, &bidder, &200);
+
+    let auction = client.get_auction(&listing_id).unwrap();
+    assert_eq!(auction.ends_at, 2_060);
+    assert_eq!(auction.extensions, 1);
+}
+
+#[test]
+fn test_cancelled_auction_refunds_highest_bidder() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let bidder = Address::generate(&env);
+    let token = setup_payment_token(&env, &client, &admin, &bidder, 10_000);
+
+    let listing_id = start_auction(&env, &client, &seller, 60, 0);
+    client.place_bid(&listing_id, &bidder, &200);
+    assert_eq!(client.get_solvency(&token.address), (200, 200));
+
+    client.cancel_listing(&listing_id, &seller);
+    assert_eq!(client.get_proceeds_balance(&bidder), 200);
+    assert_eq!(client.get_solvency(&token.address), (200, 200));
+    let auction = client.get_auction(&listing_id).unwrap();
+    assert_eq!(auction.highest_bidder, None);
+    assert_eq!(auction.highest_bid, 0);
+
+    // Cancelling again does not refund twice
+    client.cancel_listing(&listing_id, &seller);
+    assert_eq!(client.get_proceeds_balance(&bidder), 200);
+
+    assert_eq!(client.withdraw_proceeds(&bidder), 200);
+    assert_eq!(token.balance(&bidder), 10_000);
+    assert_eq!(client.get_solvency(&token.address), (0, 0));
+}
+
+#[test]
+#[should_panic(expected = "Cannot cancel an ended auction with bids")]
+fn test_ended_auction_with_bid_cannot_be_cancelled() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let bidder = Address::generate(&env);
+    setup_payment_token(&env, &client, &admin, &bidder, 10_000);
+    client.set_settlement_grace(&admin, &300);
+
+    let listing_id = start_auction(&env, &client, &seller, 0, 0);
+    client.place_bid(&listing_id, &bidder, &200);
+
+    // Inside the settlement grace window the winning bid already stands
+    env.ledger().with_mut(|li| li.timestamp = 2_100);
+    client.cancel_listing(&listing_id, &seller);
+}
+
+#[test]
+fn test_ended_auction_without_bids_can_be_cancelled() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+
+    let listing_id = start_auction(&env, &client, &seller, 0, 0);
+    env.ledger().with_mut(|li| li.timestamp = 2_100);
+    client.cancel_listing(&listing_id, &seller);
+    assert!(!client.get_listing(&listing_id).unwrap().active);
+}
+
+#[test]
+fn test_unreachable_outbid_bidder_does_not_block_auction() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let asset = env.register_stellar_asset_contract_v2(admin.clone());
+    asset.issuer().set_flag(IssuerFlags::RevocableFlag);
+    client.set_payment_token(&admin, &asset.address());
+    let token = TokenClient::new(&env, &asset.address());
+    let sac = StellarAssetClient::new(&env, &asset.address());
+    sac.mint(&first, &10_000);
+    sac.mint(&second, &10_000);
+
+    let listing_id = start_auction(&env, &client, &seller, 0, 0);
+    client.place_bid(&listing_id, &first, &200);
+
+    // The first bidder can no longer receive the token
+    sac.set_authorized(&first, &false);
+    client.place_bid(&listing_id, &second, &300);
+    assert_eq!(
+        client.get_auction(&listing_id).unwrap().highest_bidder,
+        Some(second)
+    );
+    assert_eq!(client.get_proceeds_balance(&first), 200);
+
+    sac.set_authorized(&first, &true);
+    assert_eq!(client.withdraw_proceeds(&first), 200);
+    assert_eq!(token.balance(&first), 10_000);
+}
+
+#[test]
+fn test_early_bid_does_not_extend_auction() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let bidder = Address::generate(&env);
+    setup_payment_token(&env, &client, &admin, &bidder, 10_000);
+
+    let listing_id = start_auction(&env, &client, &seller, 60, 0);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_500);
+    client.place_bid(&listing_id, &bidder, &200);
+
+    let auction = client.get_auction(&listing_id).unwrap();
+    assert_eq!(auction.ends_at, 2_000);
+    assert_eq!(auction.extensions, 0);
+}
+
+#[test]
+fn test_repeated_late_bids_extend_up_to_cap() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let token = setup_payment_token(&env, &client, &admin, &first, 10_000);
+    StellarAssetClient::new(&env, &token.address).mint(&second, &10_000);
+
+    let listing_id = start_auction(&env, &client, &seller, 60, 2);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_990);
+    client.place_bid(&listing_id, &first, &200);
+    env.ledger().with_mut(|li| li.timestamp = 2_050);
+    client.place_bid(&listing_id, &second, &300);
+    assert_eq!(client.get_auction(&listing_id).unwrap().ends_at, 2_120);
+
+    // Cap reached: a third late bid no longer extends
+    env.ledger().with_mut(|li| li.timestamp = 2_110);
+    client.place_bid(&listing_id, &first, &400);
+    let auction = client.get_auction(&listing_id).unwrap();
+    assert_eq!(auction.ends_at, 2_120);
+    assert_eq!(auction.extensions, 2);
+
+    // Outbid escrow is accrued for withdrawal and the winner's bid goes to the seller
+    assert_eq!(client.withdraw_proceeds(&second), 300);
+    assert_eq!(token.balance(&second), 10_000);
+    assert_eq!(client.withdraw_proceeds(&first), 200);
+    env.ledger().with_mut(|li| li.timestamp = 2_120);
+    client.settle_auction(&listing_id);
+    assert_eq!(client.withdraw_proceeds(&seller), 400);
+    assert_eq!(token.balance(&seller), 400);
+    assert_eq!(token.balance(&first), 9_600);
+    assert!(!client.get_listing(&listing_id).unwrap().active);
+}
+
+#[test]
+fn test_withdraw_proceeds_sweeps_all_sales() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    let token = setup_payment_token(&env, &client, &admin, &buyer, 10_000);
+
+    let first = client.create_listing(&1, &seller, &0, &1000);
+    let second = client.create_listing(&2, &seller, &0, &2500);
+    client.buy_agent(&first, &buyer, &1000);
+    client.buy_agent(&second, &buyer, &2500);
+
+    assert_eq!(token.balance(&seller), 0);
+    assert_eq!(client.get_proceeds_balance(&seller), 3500);
+
+    assert_eq!(client.withdraw_proceeds(&seller), 3500);
+    assert_eq!(token.balance(&seller), 3500);
+    assert_eq!(client.get_proceeds_balance(&seller), 0);
+}
+
+#[test]
+#[should_panic(expected = "No proceeds to withdraw")]
+fn test_withdraw_proceeds_with_zero_balance_rejected() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    setup_payment_token(&env, &client, &admin, &buyer, 10_000);
+
+    let listing_id = client.create_listing(&1, &seller, &0, &1000);
+    client.buy_agent(&listing_id, &buyer, &1000);
+
+    client.withdraw_proceeds(&seller);
+    client.withdraw_proceeds(&seller);
+}
+
+#[test]
+fn test_solvency_covers_accrued_obligations() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    let bidder = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let token = setup_payment_token(&env, &client, &admin, &buyer, 10_000);
+    StellarAssetClient::new(&env, &token.address).mint(&bidder, &10_000);
+
+    client.set_royalty(&1, &creator, &recipient, &500);
+    let first = client.create_listing(&1, &seller, &0, &1000);
+    let second = client.create_listing(&2, &seller, &0, &2000);
+    client.buy_agent(&first, &buyer, &1000);
+    client.buy_agent(&second, &buyer, &2000);
+
+    let (held, owed) = client.get_solvency(&token.address);
+    assert_eq!(held, 3000);
+    assert_eq!(owed, 3000);
+
+    // Escrowed bids count as obligations too
+    let auction = client.create_listing(&3, &seller, &ListingType::Auction.to_u32(), &100);
+    client.start_auction(&auction, &seller, &1_000, &0, &0);
+    client.place_bid(&auction, &bidder, &500);
+    let (held, owed) = client.get_solvency(&token.address);
+    assert_eq!((held, owed), (3500, 3500));
+
+    client.withdraw_proceeds(&seller);
+    client.withdraw_royalties(&recipient);
+    let (held, owed) = client.get_solvency(&token.address);
+    assert!(held >= owed);
+    assert_eq!(owed, 500);
+}
+
+#[test]
+fn test_effective_royalty_precedence() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let creator = Address::generate(&env);
+    let platform = Address::generate(&env);
+    let agent_recipient = Address::generate(&env);
+    let listing_recipient = Address::generate(&env);
+
+    let listing_id = client.create_listing(&1, &seller, &0, &1000);
+    assert_eq!(client.get_effective_royalty(&listing_id), None);
+
+    client.set_default_royalty(&admin, &platform, &100);
+    assert_eq!(
+        client.get_effective_royalty(&listing_id),
+        Some((platform.clone(), 100))
+    );
+
+    client.set_royalty(&1, &creator, &agent_recipient, &500);
+    assert_eq!(
+        client.get_effective_royalty(&listing_id),
+        Some((agent_recipient.clone(), 500))
+    );
+
+    client.set_listing_royalty(&admin, &listing_id, &listing_recipient, &250);
+    assert_eq!(
+        client.get_effective_royalty(&listing_id),
+        Some((listing_recipient, 250))
+    );
+
+    // Other listings of the same agent still use the agent royalty
+    let other = client.create_listing(&1, &seller, &0, &1000);
+    assert_eq!(
+        client.get_effective_royalty(&other),
+        Some((agent_recipient, 500))
+    );
+}
+
+#[test]
+fn test_sale_pays_effective_royalty() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let agent_recipient = Address::generate(&env);
+    let listing_recipient = Address::generate(&env);
+    setup_payment_token(&env, &client, &admin, &buyer, 10_000);
+
+    client.set_royalty(&1, &creator, &agent_recipient, &500);
+    let listing_id = client.create_listing(&1, &seller, &0, &1000);
+    client.set_listing_royalty(&admin, &listing_id, &listing_recipient, &250);
+    client.buy_agent(&listing_id, &buyer, &1000);
+
+    assert_eq!(client.get_royalty_balance(&listing_recipient), 25);
+    assert_eq!(client.get_royalty_balance(&agent_recipient), 0);
+    assert_eq!(client.get_proceeds_balance(&seller), 975);
+}
+
+#[test]
+fn test_time_remaining_counts_down_to_auction_end() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+
+    let listing_id = start_auction(&env, &client, &seller, 0, 0);
+    assert_eq!(client.get_time_remaining(&listing_id), 1_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_750);
+    assert_eq!(client.get_time_remaining(&listing_id), 250);
+
+    env.ledger().with_mut(|li| li.timestamp = 2_500);
+    assert_eq!(client.get_time_remaining(&listing_id), 0);
+}
+
+#[test]
+fn test_time_remaining_is_zero_without_expiry() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+
+    let sale = client.create_listing(&1, &seller, &ListingType::Sale.to_u32(), &1000);
+    let unstarted = client.create_listing(&2, &seller, &ListingType::Auction.to_u32(), &1000);
+    assert_eq!(client.get_time_remaining(&sale), 0);
+    assert_eq!(client.get_time_remaining(&unstarted), 0);
+}
+
+#[test]
+fn test_exact_payment_has_no_refund() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    let token = setup_payment_token(&env, &client, &admin, &buyer, 10_000);
+
+    let listing_id = client.create_listing(&1, &seller, &0, &1000);
+    client.buy_agent(&listing_id, &buyer, &1000);
+
+    assert_eq!(token.balance(&buyer), 9_000);
+    assert_eq!(client.get_proceeds_balance(&seller), 1000);
+}
+
+#[test]
+fn test_overpayment_is_refunded_to_buyer() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let token = setup_payment_token(&env, &client, &admin, &buyer, 10_000);
+
+    client.set_royalty(&1, &creator, &recipient, &500);
+    let listing_id = client.create_listing(&1, &seller, &0, &1000);
+    client.buy_agent(&listing_id, &buyer, &1500);
+
+    assert_eq!(token.balance(&buyer), 9_000);
+    assert_eq!(client.get_royalty_balance(&recipient), 50);
+    assert_eq!(client.get_proceeds_balance(&seller), 950);
+
+    let (held, owed) = client.get_solvency(&token.address);
+    assert_eq!((held, owed), (1000, 1000));
+}
+
+/// Returns whether the last invocation published a `whale_sale` event
+fn emitted_whale_sale(env: &Env) -> bool {
+    let topics: soroban_sdk::Vec<Val> = (Symbol::new(env, "whale_sale"),).into_val(env);
+    env.events()
+        .all()
+        .iter()
+        .any(|(_, event_topics, _)| event_topics == topics)
+}
+
+#[test]
+fn test_whale_sale_event_at_threshold() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    setup_payment_token(&env, &client, &admin, &buyer, 100_000);
+
+    client.set_whale_threshold(&admin, &5_000);
+    let listing_id = client.create_listing(&1, &seller, &0, &5_000);
+    client.buy_agent(&listing_id, &buyer, &5_000);
+    assert!(emitted_whale_sale(&env));
+
+    let listing_id = client.create_listing(&2, &seller, &0, &10_000);
+    client.buy_agent(&listing_id, &buyer, &10_000);
+    assert!(emitted_whale_sale(&env));
+}
+
+#[test]
+fn test_small_sale_emits_no_whale_event() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    setup_payment_token(&env, &client, &admin, &buyer, 100_000);
+
+    client.set_whale_threshold(&admin, &5_000);
+    let listing_id = client.create_listing(&1, &seller, &0, &4_999);
+    // Overpaying does not count towards the threshold
+    client.buy_agent(&listing_id, &buyer, &6_000);
+    assert!(!emitted_whale_sale(&env));
+}
+
+fn legacy_listing(env: &Env, listing_id: u64, agent_id: u64, seller: &Address) -> Listing {
+    Listing {
+        listing_id,
+        agent_id,
+        seller: seller.clone(),
+        price: 1_000 * listing_id as i128,
+        listing_type: ListingType::Sale,
+        active: true,
+        created_at: env.ledger().timestamp(),
+    }
+}
+
+#[test]
+fn test_imported_listings_are_individually_retrievable() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+
+    client.import_listing(&admin, &legacy_listing(&env, 3, 30, &seller));
+    client.import_listing(&admin, &legacy_listing(&env, 7, 70, &seller));
+    client.import_listing(&admin, &legacy_listing(&env, 5, 50, &seller));
+
+    for (listing_id, agent_id) in [(3u64, 30u64), (7, 70), (5, 50)] {
+        let listing = client.get_listing(&listing_id).unwrap();
+        assert_eq!(listing.agent_id, agent_id);
+        assert_eq!(listing.price, 1_000 * listing_id as i128);
+        assert_eq!(listing.seller, seller);
+    }
+    assert_eq!(client.get_listings_page(&0, &10).total, 3);
+
+    // New listings continue after the highest imported ID
+    let listing_id = client.create_listing(&1, &seller, &0, &500);
+    assert_eq!(listing_id, 8);
+}
+
+#[test]
+#[should_panic(expected = "Listing already exists")]
+fn test_import_listing_rejects_duplicate_id() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+
+    let listing_id = client.create_listing(&1, &seller, &0, &500);
+    client.import_listing(&admin, &legacy_listing(&env, listing_id, 99, &seller));
+}
+
+#[test]
+fn test_settlement_waits_out_grace_period() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let bidder = Address::generate(&env);
+    setup_payment_token(&env, &client, &admin, &bidder, 10_000);
+    client.set_settlement_grace(&admin, &300);
+    assert_eq!(client.get_settlement_grace(), 300);
+
+    let listing_id = start_auction(&env, &client, &seller, 0, 0);
+    client.place_bid(&listing_id, &bidder, &500);
+
+    // Ended, but still inside the grace window
+    env.ledger().with_mut(|li| li.timestamp = 2_299);
+    assert!(client.try_settle_auction(&listing_id).is_err());
+    assert!(client.get_listing(&listing_id).unwrap().active);
+
+    env.ledger().with_mut(|li| li.timestamp = 2_300);
+    client.settle_auction(&listing_id);
+    assert!(!client.get_listing(&listing_id).unwrap().active);
+    assert_eq!(client.get_proceeds_balance(&seller), 500);
+}
+
+#[test]
+#[should_panic(expected = "Auction is within settlement grace period")]
+fn test_settlement_rejected_inside_grace_period() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    client.set_settlement_grace(&admin, &300);
+
+    let listing_id = start_auction(&env, &client, &seller, 0, 0);
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    client.settle_auction(&listing_id);
+}
+
+#[test]
+fn test_total_listed_value_tracks_active_listings() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    setup_payment_token(&env, &client, &admin, &buyer, 10_000);
+    assert_eq!(client.get_total_listed_value(), 0);
+
+    let sold = client.create_listing(&1, &seller, &0, &1_000);
+    let cancelled = client.create_listing(&2, &seller, &0, &2_500);
+    client.create_listing(&3, &seller, &0, &400);
+    client.import_listing(&admin, &legacy_listing(&env, 9, 9, &seller));
+    assert_eq!(client.get_total_listed_value(), 12_900);
+
+    client.buy_agent(&sold, &buyer, &1_000);
+    assert_eq!(client.get_total_listed_value(), 11_900);
+
+    client.cancel_listing(&cancelled, &seller);
+    assert_eq!(client.get_total_listed_value(), 9_400);
+
+    // Cancelling an already inactive listing does not subtract twice
+    client.cancel_listing(&cancelled, &seller);
+    assert_eq!(client.get_total_listed_value(), 9_400);
+
+    let auction = start_auction(&env, &client, &seller, 0, 0);
+    assert_eq!(client.get_total_listed_value(), 9_500);
+    env.ledger().with_mut(|li| li.timestamp = 2_000);
+    client.settle_auction(&auction);
+    assert_eq!(client.get_total_listed_value(), 9_400);
+}
+
+#[test]
+fn test_zero_price_listing_is_claimed_for_free() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+
+    // No payment token is needed to claim a giveaway
+    let listing_id = client.create_listing(&1, &seller, &0, &0);
+    client.buy_agent(&listing_id, &buyer, &0);
+
+    assert!(!client.get_listing(&listing_id).unwrap().active);
+    assert_eq!(client.get_proceeds_balance(&seller), 0);
+    assert_eq!(client.get_total_listed_value(), 0);
+}
+
+#[test]
+fn test_zero_price_sale_accrues_no_royalty() {
+    let env = Env::default();
+    let (client, admin, seller) = setup_marketplace(&env);
+    let buyer = Address::generate(&env);
+    let creator = Address::generate(&env);
+    let token = setup_payment_token(&env, &client, &admin, &buyer, 1_000);
+    client.set_royalty(&1, &creator, &creator, &1_000);
+
+    let listing_id = client.create_listing(&1, &seller, &0, &0);
+    client.buy_agent(&listing_id, &buyer, &0);
+
+    assert_eq!(client.get_royalty_balance(&creator), 0);
+    assert_eq!(client.get_proceeds_balance(&seller), 0);
+    assert_eq!(token.balance(&buyer), 1_000);
+    assert_eq!(client.get_solvency(&token.address), (0, 0));
+}
+
+#[test]
+#[should_panic(expected = "Price cannot be negative")]
+fn test_negative_price_listing_rejected() {
+    let env = Env::default();
+    let (client, _admin, seller) = setup_marketplace(&env);
+    client.create_listing(&1, &seller, &0, &-1);
+}
+
+#[test]
+fn test_config_snapshot_tracks_admin_changes() {
+    let env = Env::default();
+    let (client, admin, _seller) = setup_marketplace(&env);
+
+    let config = client.get_config();
+    assert_eq!(config.admin, admin);
+    assert_eq!(config.payment_token, None);
+    assert_eq!(config.price_floors, soroban_sdk::vec![&env, 0, 0, 0]);
+    assert_eq!(config.settlement_grace, 0);
+    assert_eq!(config.whale_threshold, None);
+    assert_eq!(config.default_royalty_recipient, None);
+    assert_eq!(config.default_royalty_fee, 0);
+    assert_eq!(config.buyer_fee_recipient, None);
+    assert_eq!(config.buyer_fee, 0);
+
+    let token = setup_payment_token(&env, &client, &admin, &admin, 0);
+    let recipient = Address::generate(&env);
+    client.set_price_floor(&admin, &ListingType::Auction.to_u32(), &700);
+    client.set_settlement_grace(&admin, &120);
+    client.set_whale_threshold(&admin, &50_000);
+    client.set_default_royalty(&admin, &recipient, &250);
+    client.set_buyer_fee(&admin, &recipient, &100);
+
+    let config = client.get_config();
+    assert_eq!(config.payment_token, Some(token.address));
+    assert_eq!(config.price_floors, soroban_sdk::vec![&env, 0, 0, 700]);
+    assert_eq!(config.settlement_grace, 120);
+    assert_eq!(config.whale_threshold, Some(50_000));
+    assert_eq!(config.default_royalty_recipient, Some(recipient.clone()));
+    assert_eq!(config.default_royalty_fee, 250);
+    assert_eq!(config.buyer_fee_recipient, Some(recipient));
+    assert_eq!(config.buyer_fee, 100);
+}
@@ -3,8 +3,13 @@
 mod tests;
 mod testutils;
 
-use soroban_sdk::{contract, contractimpl, Address, Env, Symbol, Vec};
-use stellai_lib::{OracleData, ADMIN_KEY, PROVIDER_LIST_KEY};
+use soroban_sdk::{contract, contractimpl, panic_with_error, Address, Env, Symbol, Vec};
+use stellai_lib::{
+    errors::ContractError, DataView, OracleData, RateLimit, ADMIN_KEY, DEFAULT_ORACLE_HISTORY_CAP,
+    HISTORY_TTL_EXTEND_TO, HISTORY_TTL_THRESHOLD, MAX_AGE_SECONDS, MAX_HISTORY_QUERY_LIMIT,
+    MAX_KEYS_PER_PROVIDER, MAX_ORACLE_HISTORY_CAP, MAX_TRACKED_KEYS, PROVIDER_LIST_KEY,
+    TRACKED_KEYS_KEY,
+};
 
 #[contract]
 pub struct Oracle;
@@ -29,12 +34,17 @@ impl Oracle {
             .set(&Symbol::new(&env, PROVIDER_LIST_KEY), &providers);
     }
 
+    /// Whether the contract has been initialized with an admin
+    pub fn is_initialized(env: Env) -> bool {
+        env.storage().instance().has(&Symbol::new(&env, ADMIN_KEY))
+    }
+
     fn verify_admin(env: &Env, caller: &Address) {
         let admin: Address = env
             .storage()
             .instance()
             .get(&Symbol::new(env, ADMIN_KEY))
-            .unwrap_or_else(|| panic!("Contract not initialized"));
+            .unwrap_or_else(|| panic_with_error!(env, ContractError::NotInitialized));
 
         if caller != &admin {
             panic!("Caller is not admin");
@@ -87,7 +97,20 @@ impl Oracle {
         provider.require_auth();
 
         if !Self::is_authorized_provider(&env, &provider) {
-            panic!("Unauthorized: provider not registered");
+            panic_with_error!(&env, ContractError::ProviderNotRegistered);
+        }
+
+        if Self::is_deprecated(env.clone(), key.clone()) {
+            panic_with_error!(&env, ContractError::Deprecated);
+        }
+
+        Self::check_provider_rate_limit(&env, &provider);
+
+        let previous: Option<OracleData> = env.storage().instance().get(&key);
+        match &previous {
+            // The first submission for a key has nothing to deviate from
+            None => Self::track_key(&env, &key),
+            Some(previous) => Self::check_deviation(&env, previous.value, value),
         }
 
         let timestamp = env.ledger().timestamp();
@@ -102,6 +125,8 @@ impl Oracle {
         };
 
         env.storage().instance().set(&key, &oracle_data);
+        Self::record_history(&env, &key, &oracle_data);
+        Self::record_provider_key(&env, &provider, &key);
 
         env.events().publish(
             (Symbol::new(&env, "data_submitted"),),
@@ -110,9 +135,389 @@ impl Oracle {
     }
 
     pub fn get_data(env: Env, key: Symbol) -> Option<OracleData> {
+        if Self::is_deprecated(env.clone(), key.clone()) {
+            panic_with_error!(&env, ContractError::Deprecated);
+        }
+
         env.storage().instance().get(&key)
     }
 
+    /// Get the latest value for a key, rejecting data older than `max_age_seconds`
+    pub fn require_fresh_value(env: Env, key: Symbol, max_age_seconds: u64) -> i128 {
+        if max_age_seconds > MAX_AGE_SECONDS {
+            panic!("Max age exceeds maximum allowed");
+        }
+
+        let data = Self::get_data(env.clone(), key).expect("No data for key");
+        let age = env.ledger().timestamp().saturating_sub(data.timestamp);
+        if age > max_age_seconds {
+            panic!("Oracle data is stale");
+        }
+
+        data.value
+    }
+
+    /// Get the latest value with its provider, timestamp and freshness in one call
+    pub fn get_data_full(env: Env, key: Symbol, max_age_seconds: u64) -> Option<DataView> {
+        if max_age_seconds > MAX_AGE_SECONDS {
+            panic!("Max age exceeds maximum allowed");
+        }
+
+        let data = Self::get_data(env.clone(), key)?;
+        let age = env.ledger().timestamp().saturating_sub(data.timestamp);
+        Some(DataView {
+            value: data.value,
+            provider: data.provider,
+            timestamp: data.timestamp,
+            is_fresh: age <= max_age_seconds,
+        })
+    }
+
+    /// Retire a feed: reads and submissions for the key are rejected from now on (admin only)
+    pub fn deprecate_key(env: Env, admin: Address, key: Symbol) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let deprecated_key = (Symbol::new(&env, "deprecated"), key.clone());
+        if env.storage().instance().has(&deprecated_key) {
+            panic!("Key already deprecated");
+        }
+        env.storage().instance().set(&deprecated_key, &true);
+
+        env.events()
+            .publish((Symbol::new(&env, "key_deprecated"),), (admin, key));
+    }
+
+    /// Remove the latest value and history for a key to reclaim storage (admin only).
+    /// Returns whether anything was removed. Deprecation marks are left in place.
+    pub fn prune_key(env: Env, admin: Address, key: Symbol) -> bool {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let history_key = Self::history_key(&env, &key);
+        let had_data = env.storage().instance().has(&key);
+        let had_history = env.storage().persistent().has(&history_key);
+        if !had_data && !had_history {
+            return false;
+        }
+
+        env.storage().instance().remove(&key);
+        env.storage().persistent().remove(&history_key);
+        Self::untrack_key(&env, &key);
+
+        env.events()
+            .publish((Symbol::new(&env, "key_pruned"),), (admin, key));
+
+        true
+    }
+
+    /// Cap how many submissions a provider may make per window (admin only).
+    /// Providers without a limit are unrestricted.
+    pub fn set_provider_rate_limit(env: Env, admin: Address, provider: Address, limit: RateLimit) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if limit.max_operations == 0 || limit.window_seconds == 0 {
+            panic!("Rate limit must be positive");
+        }
+
+        env.storage()
+            .instance()
+            .set(&(Symbol::new(&env, "prov_limit"), provider.clone()), &limit);
+
+        env.events().publish(
+            (Symbol::new(&env, "provider_limit_set"),),
+            (admin, provider, limit.max_operations, limit.window_seconds),
+        );
+    }
+
+    pub fn get_provider_rate_limit(env: Env, provider: Address) -> Option<RateLimit> {
+        env.storage()
+            .instance()
+            .get(&(Symbol::new(&env, "prov_limit"), provider))
+    }
+
+    /// Reject submissions that move a feed by more than `max_deviation_bps`
+    /// from its latest value (admin only, 0 disables)
+    pub fn set_max_deviation(env: Env, admin: Address, max_deviation_bps: u32) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "max_deviation"), &max_deviation_bps);
+
+        env.events().publish(
+            (Symbol::new(&env, "max_deviation_set"),),
+            (admin, max_deviation_bps),
+        );
+    }
+
+    pub fn get_max_deviation(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "max_deviation"))
+            .unwrap_or(0)
+    }
+
+    /// Time-weighted average of a key's recorded history over the last `window_seconds`.
+    /// Each sample is weighted by how long it stayed the latest value within the window;
+    /// with a single sample (or no elapsed time) the latest value is returned.
+    pub fn get_twap(env: Env, key: Symbol, window_seconds: u64) -> i128 {
+        if window_seconds == 0 || window_seconds > MAX_AGE_SECONDS {
+            panic!("Invalid TWAP window");
+        }
+
+        let history = Self::load_history(&env, &key);
+        let latest = history.last().expect("No data for key");
+
+        // Walk back from the newest sample; nothing older than the one in force at
+        // the window start can carry weight
+        let now = env.ledger().timestamp();
+        let window_start = now.saturating_sub(window_seconds);
+        let mut weighted_sum: i128 = 0;
+        let mut total_weight: u64 = 0;
+        let mut valid_until = now;
+        for i in (0..history.len()).rev() {
+            let sample = history.get_unchecked(i);
+            let from = sample.timestamp.max(window_start);
+            let to = valid_until.min(now);
+            valid_until = sample.timestamp;
+            if to > from {
+                let weight = to - from;
+                weighted_sum = sample
+                    .value
+                    .checked_mul(weight as i128)
+                    .and_then(|term| weighted_sum.checked_add(term))
+                    .expect("Arithmetic overflow in TWAP");
+                total_weight += weight;
+            }
+            if sample.timestamp <= window_start {
+                break;
+            }
+        }
+
+        if total_weight == 0 {
+            return latest.value;
+        }
+        weighted_sum / total_weight as i128
+    }
+
+    pub fn is_deprecated(env: Env, key: Symbol) -> bool {
+        let deprecated_key = (Symbol::new(&env, "deprecated"), key);
+        env.storage().instance().has(&deprecated_key)
+    }
+
+    /// Set the per-key history cap, at most `MAX_ORACLE_HISTORY_CAP`; older entries
+    /// are evicted first (admin only)
+    pub fn set_history_cap(env: Env, admin: Address, cap: u32) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        if cap == 0 || cap > MAX_ORACLE_HISTORY_CAP {
+            panic!("History cap out of bounds");
+        }
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "history_cap"), &cap);
+
+        env.events()
+            .publish((Symbol::new(&env, "history_cap_set"),), (admin, cap));
+    }
+
+    pub fn get_history_cap(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "history_cap"))
+            .unwrap_or(DEFAULT_ORACLE_HISTORY_CAP)
+    }
+
+    /// Get up to `limit` most recent submissions for a key, oldest first
+    pub fn get_history(env: Env, key: Symbol, limit: u32) -> Vec<OracleData> {
+        if limit > MAX_HISTORY_QUERY_LIMIT {
+            panic!("Limit exceeds maximum allowed (500)");
+        }
+
+        let history = Self::load_history(&env, &key);
+        let start = history.len().saturating_sub(limit);
+        history.slice(start..history.len())
+    }
+
+    /// Storage key of a key's history. Histories live in persistent storage, one
+    /// entry per key, so they do not grow the shared instance entry.
+    fn history_key(env: &Env, key: &Symbol) -> (Symbol, Symbol) {
+        (Symbol::new(env, "history"), key.clone())
+    }
+
+    fn load_history(env: &Env, key: &Symbol) -> Vec<OracleData> {
+        env.storage()
+            .persistent()
+            .get(&Self::history_key(env, key))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    /// Append a submission to the key's history, evicting the oldest entries
+    /// once the configured cap is reached
+    fn record_history(env: &Env, key: &Symbol, data: &OracleData) {
+        let history_key = Self::history_key(env, key);
+        let mut history = Self::load_history(env, key);
+
+        let cap = Self::get_history_cap(env.clone());
+        while history.len() >= cap {
+            history.pop_front();
+        }
+
+        history.push_back(data.clone());
+        env.storage().persistent().set(&history_key, &history);
+        env.storage().persistent().extend_ttl(
+            &history_key,
+            HISTORY_TTL_THRESHOLD,
+            HISTORY_TTL_EXTEND_TO,
+        );
+    }
+
+    // Consume one submission from the provider's current window, if a limit is configured
+    fn check_provider_rate_limit(env: &Env, provider: &Address) {
+        let limit = match Self::get_provider_rate_limit(env.clone(), provider.clone()) {
+            Some(limit) => limit,
+            None => return,
+        };
+
+        let now = env.ledger().timestamp();
+        let window_key = (Symbol::new(env, "prov_window"), provider.clone());
+        let (last_reset, count): (u64, u32) = env
+            .storage()
+            .instance()
+            .get(&window_key)
+            .unwrap_or((now, 0));
+
+        let window = if now.saturating_sub(last_reset) > limit.window_seconds {
+            (now, 1)
+        } else if count < limit.max_operations {
+            (last_reset, count + 1)
+        } else {
+            panic!("Provider rate limit exceeded");
+        };
+
+        env.storage().instance().set(&window_key, &window);
+    }
+
+    fn check_deviation(env: &Env, previous: i128, value: i128) {
+        let max_deviation_bps = Self::get_max_deviation(env.clone());
+        if max_deviation_bps == 0 || previous == 0 {
+            return;
+        }
+
+        let deviation_bps = value
+            .checked_sub(previous)
+            .and_then(|delta| delta.checked_mul(10_000))
+            .map(|scaled| (scaled / previous).unsigned_abs())
+            .expect("Arithmetic overflow in deviation check");
+        if deviation_bps > max_deviation_bps as u128 {
+            panic!("Submission deviates too far from latest value");
+        }
+    }
+
+    fn track_key(env: &Env, key: &Symbol) {
+        let mut keys: Vec<Symbol> = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(env, TRACKED_KEYS_KEY))
+            .unwrap_or_else(|| Vec::new(env));
+
+        if keys.len() >= MAX_TRACKED_KEYS {
+            panic!("Maximum number of tracked keys reached");
+        }
+
+        keys.push_back(key.clone());
+        env.storage()
+            .instance()
+            .set(&Symbol::new(env, TRACKED_KEYS_KEY), &keys);
+    }
+
+    fn untrack_key(env: &Env, key: &Symbol) {
+        let mut keys: Vec<Symbol> = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(env, TRACKED_KEYS_KEY))
+            .unwrap_or_else(|| Vec::new(env));
+
+        if let Some(index) = keys.first_index_of(key) {
+            keys.remove(index);
+            env.storage()
+                .instance()
+                .set(&Symbol::new(env, TRACKED_KEYS_KEY), &keys);
+        }
+    }
+
+    pub fn get_tracked_keys(env: Env, offset: u32, limit: u32) -> Vec<Symbol> {
+        if limit > MAX_HISTORY_QUERY_LIMIT {
+            panic!("Limit exceeds maximum allowed (500)");
+        }
+
+        let keys: Vec<Symbol> = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, TRACKED_KEYS_KEY))
+            .unwrap_or_else(|| Vec::new(&env));
+
+        let mut result = Vec::new(&env);
+        let end = offset.saturating_add(limit).min(keys.len());
+        for i in offset..end {
+            if let Some(key) = keys.get(i) {
+                result.push_back(key);
+            }
+        }
+
+        result
+    }
+
+    /// Get the keys a provider has submitted to, oldest first. Only the most recent
+    /// MAX_KEYS_PER_PROVIDER distinct keys are remembered.
+    pub fn get_provider_keys(env: Env, provider: Address, offset: u32, limit: u32) -> Vec<Symbol> {
+        if limit > MAX_HISTORY_QUERY_LIMIT {
+            panic!("Limit exceeds maximum allowed (500)");
+        }
+
+        let keys: Vec<Symbol> = env
+            .storage()
+            .instance()
+            .get(&(Symbol::new(&env, "provider_keys"), provider))
+            .unwrap_or_else(|| Vec::new(&env));
+
+        let start = offset.min(keys.len());
+        let end = offset.saturating_add(limit).min(keys.len());
+        keys.slice(start..end)
+    }
+
+    fn record_provider_key(env: &Env, provider: &Address, key: &Symbol) {
+        let provider_keys_key = (Symbol::new(env, "provider_keys"), provider.clone());
+        let mut keys: Vec<Symbol> = env
+            .storage()
+            .instance()
+            .get(&provider_keys_key)
+            .unwrap_or_else(|| Vec::new(env));
+
+        if keys.contains(key) {
+            return;
+        }
+        if keys.len() >= MAX_KEYS_PER_PROVIDER {
+            keys.pop_front();
+        }
+
+        keys.push_back(key.clone());
+        env.storage().instance().set(&provider_keys_key, &keys);
+    }
+
+    pub fn key_count(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get::<_, Vec<Symbol>>(&Symbol::new(&env, TRACKED_KEYS_KEY))
+            .map(|keys| keys.len())
+            .unwrap_or(0)
+    }
+
     pub fn deregister_provider(env: Env, admin: Address, provider: Address) {
         admin.require_auth();
         Self::verify_admin(&env, &admin);
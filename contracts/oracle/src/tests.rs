@@ -1,8 +1,37 @@
 #![cfg(test)]
 
 use crate::testutils::MockOracle;
-use soroban_sdk::testutils::Address as _;
-use soroban_sdk::{Address, Env};
+use crate::{Oracle, OracleClient};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{vec, Address, Env, Symbol};
+use stellai_lib::errors::ContractError;
+use stellai_lib::{RateLimit, DEFAULT_ORACLE_HISTORY_CAP, MAX_ORACLE_HISTORY_CAP};
+
+fn setup_oracle(env: &Env) -> (OracleClient<'_>, Address, Address) {
+    let contract_id = env.register_contract(None, Oracle);
+    let client = OracleClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    let provider = Address::generate(env);
+
+    env.mock_all_auths();
+    client.init_contract(&admin);
+    client.register_provider(&admin, &provider);
+
+    (client, admin, provider)
+}
+
+#[test]
+fn test_is_initialized() {
+    let env = Env::default();
+    let contract_id = env.register_contract(None, Oracle);
+    let client = OracleClient::new(&env, &contract_id);
+    assert!(!client.is_initialized());
+
+    env.mock_all_auths();
+    client.init_contract(&Address::generate(&env));
+    assert!(client.is_initialized());
+}
 
 #[test]
 fn test_mock_oracle_generates_valid_attestation() {
@@ -46,3 +75,353 @@ fn test_mock_oracle_generates_data() {
     assert_eq!(data.value, soroban_sdk::String::from_str(&env, "100"));
     assert_eq!(data.source, soroban_sdk::String::from_str(&env, "binance"));
 }
+
+#[test]
+fn test_tracked_keys_count_and_paging() {
+    let env = Env::default();
+    let (client, _admin, provider) = setup_oracle(&env);
+
+    assert_eq!(client.key_count(), 0);
+
+    let btc = Symbol::new(&env, "btc_usd");
+    let eth = Symbol::new(&env, "eth_usd");
+    let xlm = Symbol::new(&env, "xlm_usd");
+
+    client.submit_data(&provider, &btc, &100);
+    client.submit_data(&provider, &eth, &200);
+    client.submit_data(&provider, &xlm, &300);
+    // Resubmitting an existing key must not add it twice
+    client.submit_data(&provider, &btc, &101);
+
+    assert_eq!(client.key_count(), 3);
+
+    let page = client.get_tracked_keys(&0, &2);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.get(0).unwrap(), btc);
+    assert_eq!(page.get(1).unwrap(), eth);
+
+    let page = client.get_tracked_keys(&2, &2);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap(), xlm);
+
+    assert_eq!(client.get_tracked_keys(&5, &2).len(), 0);
+}
+
+#[test]
+fn test_history_cap_evicts_oldest_entries() {
+    let env = Env::default();
+    let (client, admin, provider) = setup_oracle(&env);
+    let key = Symbol::new(&env, "BTC_USD");
+
+    assert_eq!(client.get_history_cap(), DEFAULT_ORACLE_HISTORY_CAP);
+    client.set_history_cap(&admin, &3);
+
+    for value in 1..=5i128 {
+        client.submit_data(&provider, &key, &value);
+    }
+
+    let history = client.get_history(&key, &10);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(0).unwrap().value, 3);
+    assert_eq!(history.get(2).unwrap().value, 5);
+    assert_eq!(client.get_data(&key).unwrap().value, 5);
+
+    let latest = client.get_history(&key, &2);
+    assert_eq!(latest.len(), 2);
+    assert_eq!(latest.get(0).unwrap().value, 4);
+}
+
+#[test]
+#[should_panic(expected = "History cap out of bounds")]
+fn test_history_cap_rejects_zero() {
+    let env = Env::default();
+    let (client, admin, _provider) = setup_oracle(&env);
+
+    client.set_history_cap(&admin, &0);
+}
+
+#[test]
+#[should_panic(expected = "History cap out of bounds")]
+fn test_history_cap_rejects_values_above_maximum() {
+    let env = Env::default();
+    let (client, admin, _provider) = setup_oracle(&env);
+
+    client.set_history_cap(&admin, &(MAX_ORACLE_HISTORY_CAP + 1));
+}
+
+#[test]
+fn test_history_is_kept_outside_instance_storage() {
+    let env = Env::default();
+    let (client, _admin, provider) = setup_oracle(&env);
+    let key = Symbol::new(&env, "BTC_USD");
+
+    client.submit_data(&provider, &key, &42);
+
+    env.as_contract(&client.address, || {
+        let history_key = (Symbol::new(&env, "history"), key.clone());
+        assert!(!env.storage().instance().has(&history_key));
+        assert!(env.storage().persistent().has(&history_key));
+    });
+    assert_eq!(client.get_history(&key, &10).len(), 1);
+}
+
+#[test]
+fn test_deprecated_key_rejects_reads_and_writes() {
+    let env = Env::default();
+    let (client, admin, provider) = setup_oracle(&env);
+    let retired = Symbol::new(&env, "LUNA_USD");
+    let live = Symbol::new(&env, "XLM_USD");
+
+    client.submit_data(&provider, &retired, &100);
+    client.submit_data(&provider, &live, &200);
+    assert!(!client.is_deprecated(&retired));
+
+    client.deprecate_key(&admin, &retired);
+    assert!(client.is_deprecated(&retired));
+
+    assert_eq!(
+        client.try_get_data(&retired).err(),
+        Some(Ok(ContractError::Deprecated.into()))
+    );
+    assert_eq!(
+        client.try_require_fresh_value(&retired, &3600).err(),
+        Some(Ok(ContractError::Deprecated.into()))
+    );
+    assert_eq!(
+        client.try_submit_data(&provider, &retired, &101).err(),
+        Some(Ok(ContractError::Deprecated.into()))
+    );
+
+    // Other feeds are unaffected
+    client.submit_data(&provider, &live, &201);
+    assert_eq!(client.get_data(&live).unwrap().value, 201);
+    assert_eq!(client.require_fresh_value(&live, &3600), 201);
+}
+
+#[test]
+fn test_provider_and_init_failures_map_to_error_codes() {
+    let env = Env::default();
+    let (client, _admin, _provider) = setup_oracle(&env);
+    let stranger = Address::generate(&env);
+
+    assert_eq!(
+        client
+            .try_submit_data(&stranger, &Symbol::new(&env, "XLM_USD"), &1)
+            .err(),
+        Some(Ok(ContractError::ProviderNotRegistered.into()))
+    );
+
+    let uninitialized = OracleClient::new(&env, &env.register_contract(None, Oracle));
+    assert_eq!(
+        uninitialized
+            .try_register_provider(&stranger, &stranger)
+            .err(),
+        Some(Ok(ContractError::NotInitialized.into()))
+    );
+}
+
+#[test]
+#[should_panic(expected = "Oracle data is stale")]
+fn test_require_fresh_value_rejects_stale_data() {
+    let env = Env::default();
+    let (client, _admin, provider) = setup_oracle(&env);
+    let key = Symbol::new(&env, "XLM_USD");
+
+    client.submit_data(&provider, &key, &200);
+    env.ledger().with_mut(|li| li.timestamp += 3601);
+    client.require_fresh_value(&key, &3600);
+}
+
+#[test]
+fn test_get_data_full_reports_fresh_data() {
+    let env = Env::default();
+    let (client, _admin, provider) = setup_oracle(&env);
+    let key = Symbol::new(&env, "XLM_USD");
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    client.submit_data(&provider, &key, &200);
+    env.ledger().with_mut(|li| li.timestamp += 3600);
+
+    let view = client.get_data_full(&key, &3600).unwrap();
+    assert_eq!(view.value, 200);
+    assert_eq!(view.provider, provider);
+    assert_eq!(view.timestamp, 1_000);
+    assert!(view.is_fresh);
+}
+
+#[test]
+fn test_get_data_full_flags_stale_data() {
+    let env = Env::default();
+    let (client, _admin, provider) = setup_oracle(&env);
+    let key = Symbol::new(&env, "XLM_USD");
+
+    client.submit_data(&provider, &key, &200);
+    env.ledger().with_mut(|li| li.timestamp += 3601);
+
+    let view = client.get_data_full(&key, &3600).unwrap();
+    assert_eq!(view.value, 200);
+    assert!(!view.is_fresh);
+}
+
+#[test]
+fn test_get_data_full_missing_key_returns_none() {
+    let env = Env::default();
+    let (client, _admin, _provider) = setup_oracle(&env);
+
+    assert!(client
+        .get_data_full(&Symbol::new(&env, "NOPE"), &3600)
+        .is_none());
+}
+
+#[test]
+fn test_prune_key_removes_value_and_history() {
+    let env = Env::default();
+    let (client, admin, provider) = setup_oracle(&env);
+    let stale = Symbol::new(&env, "OLD_FEED");
+    let live = Symbol::new(&env, "XLM_USD");
+
+    client.submit_data(&provider, &stale, &1);
+    client.submit_data(&provider, &stale, &2);
+    client.submit_data(&provider, &live, &3);
+    assert_eq!(client.key_count(), 2);
+
+    assert!(client.prune_key(&admin, &stale));
+    assert!(client.get_data(&stale).is_none());
+    assert_eq!(client.get_history(&stale, &10).len(), 0);
+    assert_eq!(client.key_count(), 1);
+    assert_eq!(client.get_data(&live).unwrap().value, 3);
+
+    // Pruned keys can be submitted again
+    client.submit_data(&provider, &stale, &4);
+    assert_eq!(client.key_count(), 2);
+}
+
+#[test]
+fn test_prune_missing_key_returns_false() {
+    let env = Env::default();
+    let (client, admin, _provider) = setup_oracle(&env);
+
+    assert!(!client.prune_key(&admin, &Symbol::new(&env, "MISSING")));
+}
+
+#[test]
+fn test_provider_rate_limit_rejects_then_recovers() {
+    let env = Env::default();
+    let (client, admin, provider) = setup_oracle(&env);
+    let key = Symbol::new(&env, "XLM_USD");
+    let limit = RateLimit {
+        window_seconds: 60,
+        max_operations: 2,
+    };
+
+    client.set_provider_rate_limit(&admin, &provider, &limit);
+    client.submit_data(&provider, &key, &1);
+    client.submit_data(&provider, &key, &2);
+    assert!(client.try_submit_data(&provider, &key, &3).is_err());
+    assert_eq!(client.get_data(&key).unwrap().value, 2);
+
+    env.ledger().with_mut(|li| li.timestamp += 61);
+    client.submit_data(&provider, &key, &3);
+    assert_eq!(client.get_data(&key).unwrap().value, 3);
+}
+
+#[test]
+fn test_provider_rate_limit_does_not_affect_other_providers() {
+    let env = Env::default();
+    let (client, admin, provider) = setup_oracle(&env);
+    let other = Address::generate(&env);
+    client.register_provider(&admin, &other);
+    let key = Symbol::new(&env, "XLM_USD");
+    let limit = RateLimit {
+        window_seconds: 60,
+        max_operations: 1,
+    };
+
+    client.set_provider_rate_limit(&admin, &provider, &limit);
+    client.submit_data(&provider, &key, &1);
+    assert!(client.try_submit_data(&provider, &key, &2).is_err());
+
+    assert!(client.get_provider_rate_limit(&other).is_none());
+    for value in 0..5 {
+        client.submit_data(&other, &key, &value);
+    }
+    assert_eq!(client.get_history(&key, &10).len(), 6);
+}
+
+#[test]
+fn test_first_submission_bypasses_deviation_check() {
+    let env = Env::default();
+    let (client, admin, provider) = setup_oracle(&env);
+    let key = Symbol::new(&env, "XLM_USD");
+
+    client.set_max_deviation(&admin, &1_000);
+    client.submit_data(&provider, &key, &1_000_000);
+    assert_eq!(client.get_data(&key).unwrap().value, 1_000_000);
+
+    // Within 10% of the latest value
+    client.submit_data(&provider, &key, &1_100_000);
+    assert!(client.try_submit_data(&provider, &key, &2_000_000).is_err());
+    assert_eq!(client.get_data(&key).unwrap().value, 1_100_000);
+}
+
+#[test]
+fn test_twap_with_single_sample_returns_it() {
+    let env = Env::default();
+    let (client, _admin, provider) = setup_oracle(&env);
+    let key = Symbol::new(&env, "XLM_USD");
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    client.submit_data(&provider, &key, &250);
+    assert_eq!(client.get_twap(&key, &600), 250);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_300);
+    assert_eq!(client.get_twap(&key, &600), 250);
+}
+
+#[test]
+fn test_twap_weights_samples_by_duration() {
+    let env = Env::default();
+    let (client, _admin, provider) = setup_oracle(&env);
+    let key = Symbol::new(&env, "XLM_USD");
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.submit_data(&provider, &key, &100);
+    env.ledger().with_mut(|li| li.timestamp = 1_300);
+    client.submit_data(&provider, &key, &200);
+    env.ledger().with_mut(|li| li.timestamp = 1_400);
+
+    // 100 for 300s, then 200 for 100s
+    assert_eq!(client.get_twap(&key, &400), 125);
+    // Only the last 200s: 100 for 100s, 200 for 100s
+    assert_eq!(client.get_twap(&key, &200), 150);
+}
+
+#[test]
+fn test_provider_keys_lists_submitted_feeds() {
+    let env = Env::default();
+    let (client, admin, provider) = setup_oracle(&env);
+    let other = Address::generate(&env);
+    client.register_provider(&admin, &other);
+    let xlm = Symbol::new(&env, "XLM_USD");
+    let btc = Symbol::new(&env, "BTC_USD");
+    let eth = Symbol::new(&env, "ETH_USD");
+
+    client.submit_data(&provider, &xlm, &1);
+    client.submit_data(&provider, &btc, &2);
+    client.submit_data(&provider, &xlm, &3);
+    client.submit_data(&provider, &eth, &4);
+    client.submit_data(&other, &eth, &5);
+
+    let keys = client.get_provider_keys(&provider, &0, &10);
+    assert_eq!(keys, vec![&env, xlm, btc.clone(), eth.clone()]);
+    assert_eq!(client.get_provider_keys(&provider, &1, &1), vec![&env, btc]);
+    assert_eq!(client.get_provider_keys(&other, &0, &10), vec![&env, eth]);
+}
+
+#[test]
+fn test_provider_without_submissions_has_no_keys() {
+    let env = Env::default();
+    let (client, _admin, provider) = setup_oracle(&env);
+
+    assert_eq!(client.get_provider_keys(&provider, &0, &10).len(), 0);
+}
@@ -0,0 +1,155 @@
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, Env, String, Symbol};
+
+use crate::MAX_STRING_LENGTH;
+
+// ============================================================================
+// Canonical encoding for hashing
+// ============================================================================
+//
+// Every part is written as
+//
+//     tag (1 byte) || payload length (u32, big-endian) || payload
+//
+// Integers are big-endian and fixed width, strings are their raw UTF-8 bytes,
+// and addresses and symbols are their XDR encoding. The tag and length prefix make the
+// encoding injective: moving bytes between neighbouring fields, or swapping a
+// field for one of another type, always changes the output. Off-chain signers
+// must reproduce this layout byte for byte, so existing tags must never change.
+
+const TAG_U32: u8 = 0x01;
+const TAG_U64: u8 = 0x02;
+const TAG_I128: u8 = 0x03;
+const TAG_BYTES: u8 = 0x04;
+const TAG_STRING: u8 = 0x05;
+const TAG_ADDRESS: u8 = 0x06;
+const TAG_SYMBOL: u8 = 0x07;
+
+/// A single field fed into `encode_for_hash`
+#[derive(Clone)]
+pub enum EncodablePart {
+    U32(u32),
+    U64(u64),
+    I128(i128),
+    Bytes(Bytes),
+    String(String), // at most MAX_STRING_LENGTH bytes
+    Address(Address),
+    Symbol(Symbol),
+}
+
+/// Concatenate `parts` into a deterministic, length-prefixed byte string
+/// suitable for hashing or signing.
+pub fn encode_for_hash(env: &Env, parts: &[EncodablePart]) -> Bytes {
+    let mut out = Bytes::new(env);
+    for part in parts {
+        match part {
+            EncodablePart::U32(value) => append_field(
+                &mut out,
+                TAG_U32,
+                &Bytes::from_array(env, &value.to_be_bytes()),
+            ),
+            EncodablePart::U64(value) => append_field(
+                &mut out,
+                TAG_U64,
+                &Bytes::from_array(env, &value.to_be_bytes()),
+            ),
+            EncodablePart::I128(value) => append_field(
+                &mut out,
+                TAG_I128,
+                &Bytes::from_array(env, &value.to_be_bytes()),
+            ),
+            EncodablePart::Bytes(value) => append_field(&mut out, TAG_BYTES, value),
+            EncodablePart::String(value) => {
+                if value.len() > MAX_STRING_LENGTH {
+                    panic!("String exceeds maximum length");
+                }
+                let mut buf = [0u8; MAX_STRING_LENGTH as usize];
+                let slice = &mut buf[..value.len() as usize];
+                value.copy_into_slice(slice);
+                append_field(&mut out, TAG_STRING, &Bytes::from_slice(env, slice));
+            }
+            EncodablePart::Address(value) => {
+                append_field(&mut out, TAG_ADDRESS, &value.clone().to_xdr(env))
+            }
+            EncodablePart::Symbol(value) => {
+                append_field(&mut out, TAG_SYMBOL, &value.clone().to_xdr(env))
+            }
+        }
+    }
+    out
+}
+
+fn append_field(out: &mut Bytes, tag: u8, payload: &Bytes) {
+    out.push_back(tag);
+    out.extend_from_array(&payload.len().to_be_bytes());
+    out.append(payload);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+
+    fn strings(env: &Env, a: &str, b: &str) -> Bytes {
+        encode_for_hash(
+            env,
+            &[
+                EncodablePart::String(String::from_str(env, a)),
+                EncodablePart::String(String::from_str(env, b)),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_shifted_field_boundaries_do_not_collide() {
+        let env = Env::default();
+        assert_ne!(strings(&env, "ab", "c"), strings(&env, "a", "bc"));
+        assert_ne!(strings(&env, "abc", ""), strings(&env, "", "abc"));
+    }
+
+    #[test]
+    fn test_field_types_do_not_collide() {
+        let env = Env::default();
+        let as_u32 = encode_for_hash(&env, &[EncodablePart::U32(7)]);
+        let as_bytes = encode_for_hash(
+            &env,
+            &[EncodablePart::Bytes(Bytes::from_array(
+                &env,
+                &7u32.to_be_bytes(),
+            ))],
+        );
+        let as_u64 = encode_for_hash(&env, &[EncodablePart::U64(7)]);
+        assert_ne!(as_u32, as_bytes);
+        assert_ne!(as_u32, as_u64);
+    }
+
+    #[test]
+    fn test_identical_inputs_encode_identically() {
+        let env = Env::default();
+        let address = Address::generate(&env);
+        let parts = [
+            EncodablePart::U64(42),
+            EncodablePart::I128(-5),
+            EncodablePart::Address(address.clone()),
+            EncodablePart::String(String::from_str(&env, "model")),
+        ];
+
+        assert_eq!(
+            encode_for_hash(&env, &parts),
+            encode_for_hash(&env, &parts.clone())
+        );
+        assert_ne!(
+            encode_for_hash(&env, &parts),
+            encode_for_hash(&env, &[EncodablePart::Address(address)])
+        );
+    }
+
+    #[test]
+    fn test_layout_is_tag_length_payload() {
+        let env = Env::default();
+        let encoded = encode_for_hash(&env, &[EncodablePart::U32(0x0102_0304)]);
+        assert_eq!(
+            encoded,
+            Bytes::from_array(&env, &[TAG_U32, 0, 0, 0, 4, 1, 2, 3, 4])
+        );
+    }
+}
@@ -4,6 +4,38 @@ use soroban_sdk::{contracterror, contracttype, Address, Bytes, String, Vec};
 // ============================================================================
 // Contract Error Enum
 // ============================================================================
+//
+// Discriminants are part of the public ABI: new variants must be appended with
+// the next free code, never inserted or renumbered.
+//
+// | Code | Variant               | Raised when                                          |
+// |------|-----------------------|------------------------------------------------------|
+// | 1    | AlreadyInitialized    | init called on an initialized contract               |
+// | 2    | Unauthorized          | caller is not admin / approved minter                |
+// | 3    | DuplicateAgentId      | minting an agent ID that already exists              |
+// | 4    | AgentNotFound         | agent ID has no stored record                        |
+// | 5    | InvalidAgentId        | agent ID is zero                                     |
+// | 6    | InvalidInput          | length, count or range validation failed             |
+// | 7    | AgentLeased           | mutating or transferring a leased agent              |
+// | 8    | OverflowError         | checked arithmetic overflowed                        |
+// | 9    | SameAddressTransfer   | transfer where `from == to`                          |
+// | 10   | NotOwner              | caller does not own the agent                        |
+// | 11   | InvalidAmount         | non-positive or out-of-bounds token amount           |
+// | 12   | NotEnoughBalance      | insufficient balance for a withdrawal or payment     |
+// | 13   | AlreadyExists         | creating a record whose key is already taken         |
+// | 14   | InvalidMetadata       | malformed metadata CID or model hash                 |
+// | 15   | OracleError           | oracle read failed or returned unusable data         |
+// | 16   | RateLimitExceeded     | operation exceeds the configured rate limit          |
+// | 17   | Paused                | state-changing call while the contract is paused     |
+// | 18   | Reentrancy            | re-entrant call into a guarded function              |
+// | 19   | NotInitialized        | admin-gated call before init                         |
+// | 20   | OnCooldown            | evolution or claim attempted inside its cooldown     |
+// | 21   | SupplyCapReached      | mint would exceed the configured supply cap          |
+// | 22   | MetadataFrozen        | metadata update on an agent whose metadata is frozen |
+// | 23   | ProviderNotRegistered | oracle submission from an unregistered provider      |
+// | 24   | StakeBelowMinimum     | evolution stake below the configured minimum         |
+// | 25   | TransferLocked        | transferring an agent its owner has transfer-locked  |
+// | 26   | Deprecated            | reading or writing a retired oracle feed             |
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
@@ -24,4 +56,55 @@ pub enum ContractError {
     InvalidMetadata = 14,
     OracleError = 15,
     RateLimitExceeded = 16,
+    Paused = 17,
+    Reentrancy = 18,
+    NotInitialized = 19,
+    OnCooldown = 20,
+    SupplyCapReached = 21,
+    MetadataFrozen = 22,
+    ProviderNotRegistered = 23,
+    StakeBelowMinimum = 24,
+    TransferLocked = 25,
+    Deprecated = 26,
+}
+
+#[cfg(test)]
+mod test {
+    use super::ContractError;
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        let expected = [
+            (ContractError::AlreadyInitialized, 1),
+            (ContractError::Unauthorized, 2),
+            (ContractError::DuplicateAgentId, 3),
+            (ContractError::AgentNotFound, 4),
+            (ContractError::InvalidAgentId, 5),
+            (ContractError::InvalidInput, 6),
+            (ContractError::AgentLeased, 7),
+            (ContractError::OverflowError, 8),
+            (ContractError::SameAddressTransfer, 9),
+            (ContractError::NotOwner, 10),
+            (ContractError::InvalidAmount, 11),
+            (ContractError::NotEnoughBalance, 12),
+            (ContractError::AlreadyExists, 13),
+            (ContractError::InvalidMetadata, 14),
+            (ContractError::OracleError, 15),
+            (ContractError::RateLimitExceeded, 16),
+            (ContractError::Paused, 17),
+            (ContractError::Reentrancy, 18),
+            (ContractError::NotInitialized, 19),
+            (ContractError::OnCooldown, 20),
+            (ContractError::SupplyCapReached, 21),
+            (ContractError::MetadataFrozen, 22),
+            (ContractError::ProviderNotRegistered, 23),
+            (ContractError::StakeBelowMinimum, 24),
+            (ContractError::TransferLocked, 25),
+            (ContractError::Deprecated, 26),
+        ];
+
+        for (variant, code) in expected {
+            assert_eq!(variant as u32, code);
+        }
+    }
 }
@@ -0,0 +1,48 @@
+use soroban_sdk::{contractclient, Address, Env, String, Symbol};
+
+use crate::{errors::ContractError, Agent, OracleData};
+
+// ============================================================================
+// Cross-contract interfaces
+// ============================================================================
+//
+// Only the entry points other contracts call are declared here; signatures
+// must match the implementing contract exactly.
+
+/// Subset of the AgentNFT contract used by other StellAIverse contracts
+#[contractclient(name = "AgentNftClient")]
+pub trait AgentNftInterface {
+    fn get_agent(env: Env, agent_id: u64) -> Result<Agent, ContractError>;
+    fn get_agent_owner(env: Env, agent_id: u64) -> Result<Address, ContractError>;
+    fn get_creator(env: Env, agent_id: u64) -> Result<Address, ContractError>;
+    fn transfer_agent(
+        env: Env,
+        agent_id: u64,
+        spender: Address,
+        from: Address,
+        to: Address,
+    ) -> Result<(), ContractError>;
+    fn mint_agent(
+        env: Env,
+        agent_id: u128,
+        minter: Address,
+        owner: Address,
+        metadata_cid: String,
+        initial_evolution_level: u32,
+    ) -> Result<(), ContractError>;
+    fn mint_agent_for(
+        env: Env,
+        operator: Address,
+        agent_id: u128,
+        recipient: Address,
+        metadata_cid: String,
+        initial_evolution_level: u32,
+    ) -> Result<(), ContractError>;
+    fn next_agent_id(env: Env) -> Result<u64, ContractError>;
+}
+
+/// Subset of the Oracle contract used by other StellAIverse contracts
+#[contractclient(name = "OracleDataClient")]
+pub trait OracleInterface {
+    fn get_data(env: Env, key: Symbol) -> Option<OracleData>;
+}
@@ -1,5 +1,8 @@
 #![no_std]
+pub mod encoding;
 pub mod errors;
+pub mod interfaces;
+pub mod signatures;
 
 use soroban_sdk::{contracttype, symbol_short, Address, Bytes, String, Symbol, Vec};
 
@@ -15,12 +18,23 @@ pub struct OracleData {
     pub source: Option<String>,
 }
 
+/// Latest oracle value together with who reported it and whether it is fresh
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct DataView {
+    pub value: i128,
+    pub provider: Address,
+    pub timestamp: u64,
+    pub is_fresh: bool,
+}
+
 /// Represents an agent's metadata and state
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[contracttype]
 pub struct Agent {
     pub id: u64,
     pub owner: Address,
+    pub creator: Address,
     pub name: String,
     pub model_hash: String,
     pub metadata_cid: String,
@@ -54,6 +68,50 @@ pub struct Listing {
     pub created_at: u64,
 }
 
+/// Bidding state for an auction listing
+#[derive(Clone)]
+#[contracttype]
+pub struct Auction {
+    pub listing_id: u64,
+    pub ends_at: u64,
+    pub highest_bid: i128,
+    pub highest_bidder: Option<Address>,
+    pub anti_snipe_seconds: u64, // 0 disables extensions
+    pub max_extensions: u32,     // 0 = unlimited
+    pub extensions: u32,
+}
+
+/// One page of listings plus the size of the full result set.
+/// `#[contracttype]` cannot be generic, so each paginated getter has its own
+/// page type following this `items` / `total` / `offset` shape.
+#[derive(Clone)]
+#[contracttype]
+pub struct ListingPage {
+    pub items: Vec<Listing>,
+    pub total: u32,
+    pub offset: u32,
+}
+
+/// One entry in an agent's ExecutionHub action history
+#[derive(Clone)]
+#[contracttype]
+pub struct ActionRecord {
+    pub execution_id: u64,
+    pub action: String,
+    pub executor: Address,
+    pub timestamp: u64,
+    pub nonce: u64,
+}
+
+/// One page of an agent's action history
+#[derive(Clone)]
+#[contracttype]
+pub struct ActionRecordPage {
+    pub items: Vec<ActionRecord>,
+    pub total: u32,
+    pub offset: u32,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 #[contracttype]
 #[repr(u32)]
@@ -63,6 +121,23 @@ pub enum ListingType {
     Auction = 2,
 }
 
+impl ListingType {
+    /// Convert a raw `u32` discriminant into a listing type, rejecting unknown values
+    pub fn try_from_u32(value: u32) -> Result<ListingType, errors::ContractError> {
+        match value {
+            0 => Ok(ListingType::Sale),
+            1 => Ok(ListingType::Lease),
+            2 => Ok(ListingType::Auction),
+            _ => Err(errors::ContractError::InvalidInput),
+        }
+    }
+
+    /// Raw `u32` discriminant for this listing type
+    pub fn to_u32(self) -> u32 {
+        self as u32
+    }
+}
+
 /// Represents an evolution/upgrade request
 #[derive(Clone)]
 #[contracttype]
@@ -118,7 +193,22 @@ pub const MAX_HISTORY_SIZE: u32 = 1000;
 pub const MAX_HISTORY_QUERY_LIMIT: u32 = 500;
 pub const DEFAULT_RATE_LIMIT_OPERATIONS: u32 = 100;
 pub const DEFAULT_RATE_LIMIT_WINDOW_SECONDS: u64 = 60;
+pub const MAX_RATE_LIMIT_OPERATIONS: u32 = 10_000; // Ceiling for per-agent overrides
+pub const MAX_IDEMPOTENCY_WINDOW_SECONDS: u64 = 3600; // 1 hour max retry window
+pub const MAX_RETRY_DELAY_SECONDS: u64 = 86400; // Longest retry hint on a failed execution
+pub const MAX_SETTLEMENT_GRACE_SECONDS: u64 = 86400; // Longest wait between auction end and settlement
+pub const MAX_TRACKED_KEYS: u32 = 500;
+pub const DEFAULT_ORACLE_HISTORY_CAP: u32 = 100; // Submissions kept per oracle key by default
+pub const MAX_ORACLE_HISTORY_CAP: u32 = 250; // Keeps one key's history entry well under the ledger-entry size limit
+pub const HISTORY_TTL_THRESHOLD: u32 = 518_400; // ~30 days of ledgers
+pub const HISTORY_TTL_EXTEND_TO: u32 = 1_036_800; // ~60 days of ledgers
+pub const MAX_KEYS_PER_PROVIDER: u32 = 100; // Most recent keys remembered per oracle provider
+pub const MAX_BATCH_SIZE: u32 = 50; // Max entries per batch call
+pub const MAX_EXECUTORS_PER_AGENT: u32 = 20;
+pub const MAX_AGENTS_PER_OWNER: u32 = 1000; // Bound on each owner's agent index
+pub const MAX_RULES_PER_AGENT: u32 = 50;
 pub const MAX_CAPABILITIES: usize = 32;
+pub const MAX_ATTRIBUTES: u32 = 32; // Max custom attributes per agent
 pub const MAX_ROYALTY_PERCENTAGE: u32 = 10000; // 100%
 pub const MIN_ROYALTY_PERCENTAGE: u32 = 0;
 pub const SAFE_ARITHMETIC_CHECK_OVERFLOW: u128 = u128::MAX;
@@ -128,18 +218,50 @@ pub const MAX_DURATION_DAYS: u64 = 36500; // ~100 years max lease duration
 pub const MAX_AGE_SECONDS: u64 = 365 * 24 * 60 * 60; // ~1 year max data age
 pub const ATTESTATION_SIGNATURE_SIZE: usize = 64; // Ed25519 signature size
 pub const MAX_ATTESTATION_DATA_SIZE: usize = 1024; // Max size for attestation data
+pub const LEVELS_PER_TIER: u32 = 10; // Evolution levels sharing one set of tier params
 
 // Storage keys
 pub const EXEC_CTR_KEY: Symbol = symbol_short!("exec_ctr");
 pub const REQUEST_COUNTER_KEY: &str = "request_counter";
+pub const EVOLUTION_COOLDOWN_KEY: &str = "evo_cooldown";
+pub const STAKE_TOKEN_KEY: &str = "stake_token";
+pub const MIN_STAKE_KEY: &str = "min_stake";
+pub const MAX_PENDING_REQUESTS: u32 = 5; // Max open evolution requests per agent
+pub const MAX_ATTESTATION_QUORUM: u32 = 10; // Max distinct providers required per evolution
+pub const AGENT_NFT_KEY: &str = "agent_nft";
 pub const CLAIM_COOLDOWN_KEY: &str = "claim_cooldown";
 pub const MAX_CLAIMS_PER_PERIOD_KEY: &str = "max_claims_per_period";
 pub const TESTNET_FLAG_KEY: &str = "testnet_mode";
 pub const DEFAULT_COOLDOWN_SECONDS: u64 = 86400; // 24 hours
 pub const DEFAULT_MAX_CLAIMS: u32 = 1;
 pub const LISTING_COUNTER_KEY: &str = "listing_counter";
+pub const PAYMENT_TOKEN_KEY: &str = "payment_token";
 pub const PROVIDER_LIST_KEY: &str = "providers";
+pub const TRACKED_KEYS_KEY: &str = "tracked_keys";
 pub const AGENT_COUNTER_KEY: &str = "agent_counter";
 pub const AGENT_KEY_PREFIX: &str = "agent_";
 pub const AGENT_LEASE_STATUS_PREFIX: &str = "agent_lease_";
 pub const APPROVED_MINTERS_KEY: &str = "approved_minters";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_listing_type_round_trip() {
+        for listing_type in [ListingType::Sale, ListingType::Lease, ListingType::Auction] {
+            let raw = listing_type.to_u32();
+            assert!(ListingType::try_from_u32(raw) == Ok(listing_type));
+        }
+        assert_eq!(ListingType::Sale.to_u32(), 0);
+        assert_eq!(ListingType::Lease.to_u32(), 1);
+        assert_eq!(ListingType::Auction.to_u32(), 2);
+    }
+
+    #[test]
+    fn test_listing_type_rejects_unknown_values() {
+        for raw in [3u32, 4, 100, u32::MAX] {
+            assert!(ListingType::try_from_u32(raw) == Err(errors::ContractError::InvalidInput));
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use soroban_sdk::{Bytes, BytesN, Env};
+
+use crate::{
+    encoding::{encode_for_hash, EncodablePart},
+    OracleData,
+};
+
+// ============================================================================
+// Oracle data signatures
+// ============================================================================
+//
+// A provider signs the canonical encoding of the data it reports (see
+// `oracle_data_message`) with Ed25519 and stores the 64-byte signature in
+// `OracleData::signature` as 128 lowercase or uppercase hex characters.
+
+/// Canonical message a provider signs for `data`: key, value, timestamp and
+/// provider, followed by the source when one is set
+pub fn oracle_data_message(env: &Env, data: &OracleData) -> Bytes {
+    let key = EncodablePart::Symbol(data.key.clone());
+    let value = EncodablePart::I128(data.value);
+    let timestamp = EncodablePart::U64(data.timestamp);
+    let provider = EncodablePart::Address(data.provider.clone());
+    match &data.source {
+        Some(source) => encode_for_hash(
+            env,
+            &[
+                key,
+                value,
+                timestamp,
+                provider,
+                EncodablePart::String(source.clone()),
+            ],
+        ),
+        None => encode_for_hash(env, &[key, value, timestamp, provider]),
+    }
+}
+
+/// Assert that `data.signature` was produced by `pubkey`, without consulting the
+/// oracle contract.
+///
+/// This is an assertion, not a predicate: a missing or malformed signature panics,
+/// and a well-formed signature that does not verify traps in the host's Ed25519
+/// check, which the calling contract cannot catch. Either way the invocation aborts.
+pub fn require_oracle_signature(env: &Env, data: &OracleData, pubkey: &BytesN<32>) {
+    let signature = match &data.signature {
+        Some(signature) => signature,
+        None => panic!("Missing oracle signature"),
+    };
+    if signature.len() != 128 {
+        panic!("Invalid oracle signature");
+    }
+
+    let mut hex = [0u8; 128];
+    signature.copy_into_slice(&mut hex);
+    let mut raw = [0u8; 64];
+    for (i, pair) in hex.chunks(2).enumerate() {
+        match (hex_value(pair[0]), hex_value(pair[1])) {
+            (Some(high), Some(low)) => raw[i] = (high << 4) | low,
+            _ => panic!("Invalid oracle signature"),
+        }
+    }
+
+    let message = oracle_data_message(env, data);
+    env.crypto()
+        .ed25519_verify(pubkey, &message, &BytesN::from_array(env, &raw));
+}
+
+fn hex_value(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use soroban_sdk::testutils::Address as _;
+    use soroban_sdk::{Address, String, Symbol};
+
+    fn signed_data(env: &Env, signer: &SigningKey) -> OracleData {
+        let mut data = OracleData {
+            key: Symbol::new(env, "XLM_USD"),
+            value: 1_234,
+            timestamp: 1_700_000_000,
+            provider: Address::generate(env),
+            signature: None,
+            source: Some(String::from_str(env, "feed")),
+        };
+
+        let message = oracle_data_message(env, &data);
+        let mut buf = [0u8; 512];
+        let len = message.len() as usize;
+        message.copy_into_slice(&mut buf[..len]);
+        let signature = signer.sign(&buf[..len]).to_bytes();
+
+        let mut hex = [0u8; 128];
+        for (i, byte) in signature.iter().enumerate() {
+            hex[2 * i] = b"0123456789abcdef"[(byte >> 4) as usize];
+            hex[2 * i + 1] = b"0123456789abcdef"[(byte & 0x0f) as usize];
+        }
+        data.signature = Some(String::from_bytes(env, &hex));
+        data
+    }
+
+    fn public_key(env: &Env, signer: &SigningKey) -> BytesN<32> {
+        BytesN::from_array(env, &signer.verifying_key().to_bytes())
+    }
+
+    #[test]
+    fn test_valid_signature_verifies() {
+        let env = Env::default();
+        let signer = SigningKey::from_bytes(&[7u8; 32]);
+        let data = signed_data(&env, &signer);
+
+        require_oracle_signature(&env, &data, &public_key(&env, &signer));
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing oracle signature")]
+    fn test_missing_signature_is_rejected() {
+        let env = Env::default();
+        let signer = SigningKey::from_bytes(&[7u8; 32]);
+        let mut data = signed_data(&env, &signer);
+
+        data.signature = None;
+        require_oracle_signature(&env, &data, &public_key(&env, &signer));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid oracle signature")]
+    fn test_short_signature_is_rejected() {
+        let env = Env::default();
+        let signer = SigningKey::from_bytes(&[7u8; 32]);
+        let mut data = signed_data(&env, &signer);
+
+        data.signature = Some(String::from_str(&env, "abcd"));
+        require_oracle_signature(&env, &data, &public_key(&env, &signer));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid oracle signature")]
+    fn test_non_hex_signature_is_rejected() {
+        let env = Env::default();
+        let signer = SigningKey::from_bytes(&[7u8; 32]);
+        let mut data = signed_data(&env, &signer);
+
+        data.signature = Some(String::from_bytes(&env, &[b'z'; 128]));
+        require_oracle_signature(&env, &data, &public_key(&env, &signer));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_signature_over_other_data_traps() {
+        let env = Env::default();
+        let signer = SigningKey::from_bytes(&[7u8; 32]);
+        let mut data = signed_data(&env, &signer);
+
+        data.value += 1;
+        require_oracle_signature(&env, &data, &public_key(&env, &signer));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_signature_from_other_key_traps() {
+        let env = Env::default();
+        let signer = SigningKey::from_bytes(&[7u8; 32]);
+        let data = signed_data(&env, &signer);
+
+        let other = SigningKey::from_bytes(&[9u8; 32]);
+        require_oracle_signature(&env, &data, &public_key(&env, &other));
+    }
+}